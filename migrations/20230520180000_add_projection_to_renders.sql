@@ -0,0 +1 @@
+ALTER TABLE renders ADD COLUMN projection text NOT NULL DEFAULT 'gnomonic';
@@ -0,0 +1 @@
+ALTER TABLE renders ADD COLUMN render_mode text NOT NULL DEFAULT 'framed';
@@ -0,0 +1,5 @@
+ALTER TABLE renders
+    ADD COLUMN aperture_diameter real, -- Metres
+    ADD COLUMN psf_model text,
+    ADD COLUMN psf_fwhm real, -- Arcseconds
+    ADD COLUMN psf_beta real;
@@ -0,0 +1,10 @@
+ALTER TABLE renders
+    ADD COLUMN exposure_time real, -- Seconds
+    ADD COLUMN sky_brightness real, -- Magnitudes per square arcsecond
+    ADD COLUMN noise_seed bigint,
+    ADD COLUMN quantum_efficiency real,
+    ADD COLUMN read_noise real, -- Electrons
+    ADD COLUMN dark_current real, -- Electrons per second
+    ADD COLUMN gain real, -- Electrons per ADU
+    ADD COLUMN full_well real, -- Electrons
+    ADD COLUMN bit_depth smallint;
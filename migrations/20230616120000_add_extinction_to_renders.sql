@@ -0,0 +1 @@
+ALTER TABLE renders ADD COLUMN extinction text;
@@ -0,0 +1,3 @@
+ALTER TABLE renders
+    ADD COLUMN epoch double precision, -- Julian year
+    ADD COLUMN light_travel_time boolean NOT NULL DEFAULT false;
@@ -0,0 +1 @@
+ALTER TABLE renders ADD COLUMN observer_velocity real[3]; -- Fraction of the speed of light
@@ -0,0 +1,5 @@
+ALTER TABLE renders
+    ADD COLUMN site_latitude real, -- Geographic, degrees
+    ADD COLUMN site_longitude real, -- Geographic, degrees east
+    ADD COLUMN site_elevation real, -- Metres
+    ADD COLUMN observation_time timestamptz;
@@ -0,0 +1,3 @@
+ALTER TABLE renders
+    ADD COLUMN sky_bortle_class smallint,
+    ADD COLUMN sky_zenith_brightness real; -- V magnitudes per square arcsecond
@@ -0,0 +1,2 @@
+ALTER TABLE renders
+    ADD COLUMN solar_system boolean NOT NULL DEFAULT false;
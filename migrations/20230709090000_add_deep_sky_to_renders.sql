@@ -0,0 +1,2 @@
+ALTER TABLE renders
+    ADD COLUMN deep_sky boolean NOT NULL DEFAULT false;
@@ -0,0 +1,3 @@
+-- Artifacts are addressed relative to the storage backend, so the public host can change
+ALTER TABLE renders
+    RENAME COLUMN image_url TO image_key;
@@ -0,0 +1,3 @@
+-- Artifacts of a render are no longer served past this time
+ALTER TABLE renders
+    ADD COLUMN expires_at timestamptz;
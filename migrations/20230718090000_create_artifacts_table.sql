@@ -0,0 +1,14 @@
+-- Derivatives of renders, such as thumbnails and previews
+CREATE TABLE artifacts(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    render_id uuid NOT NULL REFERENCES renders (id) ON DELETE CASCADE,
+    kind text NOT NULL,
+    storage_key text NOT NULL,
+    content_type text NOT NULL,
+    width integer NOT NULL,
+    height integer NOT NULL,
+    size bigint NOT NULL,
+    created_at timestamptz NOT NULL,
+    UNIQUE (render_id, kind)
+);
@@ -0,0 +1 @@
+ALTER TABLE renders ADD COLUMN output text NOT NULL DEFAULT 'image';
@@ -0,0 +1,12 @@
+-- All-sky HiPS surveys, one per filter, seen from an observer
+CREATE TABLE surveys(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    created_at timestamptz NOT NULL,
+    email text NOT NULL,
+    observer_position real[3] NOT NULL,
+    narrowband_filters real[] NOT NULL,
+    broadband_filters text[] NOT NULL,
+    max_order smallint NOT NULL,
+    tile_width integer NOT NULL
+);
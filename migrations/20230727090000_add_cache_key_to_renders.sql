@@ -0,0 +1,3 @@
+-- Content address of the render's parameters, shared by identical jobs
+ALTER TABLE renders ADD COLUMN cache_key text;
+CREATE INDEX renders_cache_key_idx ON renders (cache_key);
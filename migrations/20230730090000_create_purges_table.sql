@@ -0,0 +1,12 @@
+-- Renders whose artifacts were removed, kept as tombstones until deleted
+ALTER TABLE renders ADD COLUMN purged_at timestamptz;
+-- Audit of every run of the retention purge
+CREATE TABLE purges(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    ran_at timestamptz NOT NULL,
+    queued_deleted integer NOT NULL,
+    renders_expired integer NOT NULL,
+    files_deleted integer NOT NULL,
+    tombstones_deleted integer NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Audit of erasures of a user's data, identified by the SHA-256 of their email only
+CREATE TABLE erasures(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    erased_at timestamptz NOT NULL,
+    email_sha256 text NOT NULL,
+    renders_deleted integer NOT NULL,
+    surveys_deleted integer NOT NULL,
+    files_deleted integer NOT NULL,
+    signature text NOT NULL
+);
@@ -0,0 +1,14 @@
+-- Keys required to submit render jobs, stored as SHA-256 hashes
+CREATE TABLE api_keys(
+    id uuid NOT NULL,
+    PRIMARY KEY (id),
+    owner text NOT NULL,
+    key_hash text NOT NULL UNIQUE,
+    created_at timestamptz NOT NULL,
+    revoked_at timestamptz,
+    max_jobs_per_day integer NOT NULL,
+    max_concurrent_jobs integer NOT NULL,
+    max_pixels_per_job bigint NOT NULL
+);
+ALTER TABLE renders ADD COLUMN api_key_id uuid REFERENCES api_keys (id) ON DELETE SET NULL;
+CREATE INDEX renders_api_key_id_idx ON renders (api_key_id, created_at);
@@ -0,0 +1,3 @@
+-- Files an erasure could not delete from storage, left to be removed by hand
+ALTER TABLE erasures
+    ADD COLUMN files_failed integer NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Survey jobs count towards the jobs per day quota of the key that submitted them
+ALTER TABLE surveys ADD COLUMN api_key_id uuid REFERENCES api_keys (id) ON DELETE SET NULL;
+CREATE INDEX surveys_api_key_id_idx ON surveys (api_key_id, created_at);
@@ -0,0 +1,2 @@
+-- Unfinished survey jobs count towards the concurrent jobs quota of their API key
+ALTER TABLE surveys ADD COLUMN finished_at timestamptz;
@@ -0,0 +1,2 @@
+-- Signed report of each erasure as JSON, so that its signature can be checked again later
+ALTER TABLE erasures ADD COLUMN report text;
@@ -0,0 +1,311 @@
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header;
+use actix_web::{web, HttpMessage, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::LocalBoxFuture;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// Header carrying the API key of job submissions
+pub const API_KEY_HEADER: &str = "X-API-Key";
+/// Seconds after which clients at their concurrent jobs quota should try again
+const CONCURRENT_RETRY_SECONDS: i64 = 60;
+
+/// Limits on the jobs submitted with an API key
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quotas {
+    /// Render and survey jobs submitted over any 24 hours
+    pub max_jobs_per_day: i32,
+    /// Render and survey jobs queued and not yet finished
+    pub max_concurrent_jobs: i32,
+    /// Pixels of the image of each render job, or of the deepest tiles of each survey job
+    pub max_pixels_per_job: i64,
+}
+
+impl Quotas {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_jobs_per_day <= 0
+            || self.max_concurrent_jobs <= 0
+            || self.max_pixels_per_job <= 0
+        {
+            return Err("Quotas must be positive.".into());
+        }
+        Ok(())
+    }
+}
+
+/// API key that authenticated a request, as handed to handlers by [`RequireApiKey`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKey {
+    pub id: Uuid,
+    /// Email of the key's owner
+    pub owner: String,
+    pub quotas: Quotas,
+}
+
+#[derive(Debug)]
+pub enum ApiKeyError {
+    Missing,
+    /// Unknown or revoked
+    Invalid,
+    QuotaExceeded {
+        message: String,
+        /// Seconds until the request may succeed
+        retry_after: i64,
+    },
+    Database(sqlx::Error),
+}
+
+impl ApiKeyError {
+    pub fn response(&self) -> HttpResponse {
+        match self {
+            ApiKeyError::Missing => HttpResponse::Unauthorized().body(format!(
+                "An API key is required in the {} header.",
+                API_KEY_HEADER
+            )),
+            ApiKeyError::Invalid => {
+                HttpResponse::Unauthorized().body("The API key is unknown or revoked.")
+            }
+            ApiKeyError::QuotaExceeded {
+                message,
+                retry_after,
+            } => HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.max(&1).to_string()))
+                .body(message.clone()),
+            ApiKeyError::Database(e) => {
+                tracing::error!("Failed to execute query: {:?}", e);
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiKeyError {
+    fn from(e: sqlx::Error) -> Self {
+        ApiKeyError::Database(e)
+    }
+}
+
+/// Hex SHA-256 under which a key is stored, so a leaked database does not leak the keys
+pub fn key_hash(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Create an API key for `owner`, returning it with the key itself, which is never stored
+pub async fn create_api_key(
+    db_pool: &PgPool,
+    owner: &str,
+    quotas: Quotas,
+) -> Result<(ApiKey, String), sqlx::Error> {
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let key = format!("st_{}", hex::encode(secret));
+    let api_key = ApiKey {
+        id: Uuid::new_v4(),
+        owner: owner.to_string(),
+        quotas,
+    };
+    sqlx::query!(
+        r#"
+        INSERT INTO api_keys (
+            id, owner, key_hash, created_at, max_jobs_per_day, max_concurrent_jobs,
+            max_pixels_per_job
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        api_key.id,
+        api_key.owner,
+        key_hash(&key),
+        Utc::now(),
+        quotas.max_jobs_per_day,
+        quotas.max_concurrent_jobs,
+        quotas.max_pixels_per_job,
+    )
+    .execute(db_pool)
+    .await?;
+    Ok((api_key, key))
+}
+
+impl ApiKey {
+    /// Unrevoked API key whose hash matches `key`
+    pub async fn authenticate(db_pool: &PgPool, key: &str) -> Result<Self, ApiKeyError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, owner, max_jobs_per_day, max_concurrent_jobs, max_pixels_per_job
+            FROM api_keys
+            WHERE key_hash = $1 AND revoked_at IS NULL
+            "#,
+            key_hash(key)
+        )
+        .fetch_optional(db_pool)
+        .await?
+        .ok_or(ApiKeyError::Invalid)?;
+        Ok(Self {
+            id: row.id,
+            owner: row.owner,
+            quotas: Quotas {
+                max_jobs_per_day: row.max_jobs_per_day,
+                max_concurrent_jobs: row.max_concurrent_jobs,
+                max_pixels_per_job: row.max_pixels_per_job,
+            },
+        })
+    }
+
+    /// Check that the key may submit one more job at `now`, in the transaction saving the job
+    ///
+    /// Locks the key's row until the transaction ends, so concurrent submissions with the same key
+    /// are checked one after the other and cannot both take the last job of a quota.
+    pub async fn check_job_quotas(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        now: DateTime<Utc>,
+    ) -> Result<(), ApiKeyError> {
+        sqlx::query!("SELECT id FROM api_keys WHERE id = $1 FOR UPDATE", self.id)
+            .fetch_one(&mut *transaction)
+            .await?;
+        let day = sqlx::query!(
+            r#"
+            SELECT count(*) AS "count!", min(created_at) AS oldest
+            FROM (
+                SELECT created_at FROM renders WHERE api_key_id = $1 AND created_at > $2
+                UNION ALL
+                SELECT created_at FROM surveys WHERE api_key_id = $1 AND created_at > $2
+            ) AS jobs
+            "#,
+            self.id,
+            now - Duration::days(1)
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+        if day.count >= self.quotas.max_jobs_per_day as i64 {
+            // The oldest job of the last 24 hours is the first to leave the window
+            let retry_after = day
+                .oldest
+                .map_or(Duration::days(1), |oldest| oldest + Duration::days(1) - now);
+            return Err(ApiKeyError::QuotaExceeded {
+                message: format!(
+                    "The API key is limited to {} jobs per day.",
+                    self.quotas.max_jobs_per_day
+                ),
+                retry_after: retry_after.num_seconds() + 1,
+            });
+        }
+        let queued = sqlx::query!(
+            r#"
+            SELECT count(*) AS "count!"
+            FROM (
+                SELECT id FROM renders WHERE api_key_id = $1 AND image_key IS NULL
+                UNION ALL
+                SELECT id FROM surveys WHERE api_key_id = $1 AND finished_at IS NULL
+            ) AS jobs
+            "#,
+            self.id
+        )
+        .fetch_one(&mut *transaction)
+        .await?
+        .count;
+        if queued >= self.quotas.max_concurrent_jobs as i64 {
+            return Err(ApiKeyError::QuotaExceeded {
+                message: format!(
+                    "The API key is limited to {} unfinished jobs.",
+                    self.quotas.max_concurrent_jobs
+                ),
+                retry_after: CONCURRENT_RETRY_SECONDS,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that the key may render an image of `pixels`
+    pub fn check_pixels(&self, pixels: i64) -> Result<(), ApiKeyError> {
+        if pixels > self.quotas.max_pixels_per_job {
+            return Err(ApiKeyError::QuotaExceeded {
+                message: format!(
+                    "The API key is limited to {} pixels per job.",
+                    self.quotas.max_pixels_per_job
+                ),
+                // Retrying cannot help until the quota is raised
+                retry_after: Duration::days(1).num_seconds(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Middleware rejecting requests without a valid API key
+///
+/// The key is then available to handlers as `web::ReqData<ApiKey>`. Handlers check its quotas
+/// once the job is resolved, see [`ApiKey::check_pixels`], and when saving it, see
+/// [`ApiKey::check_job_quotas`].
+pub struct RequireApiKey;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireApiKey
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RequireApiKeyMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireApiKeyMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequireApiKeyMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireApiKeyMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        Box::pin(async move {
+            let db_pool = request
+                .app_data::<web::Data<PgPool>>()
+                .expect("The database pool is registered as app data")
+                .clone();
+            let key = request
+                .headers()
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let authenticated = match key {
+                None => Err(ApiKeyError::Missing),
+                Some(key) => ApiKey::authenticate(&db_pool, &key).await,
+            };
+            match authenticated {
+                Ok(api_key) => {
+                    request.extensions_mut().insert(api_key);
+                    service
+                        .call(request)
+                        .await
+                        .map(ServiceResponse::map_into_left_body)
+                }
+                Err(e) => {
+                    let (request, _) = request.into_parts();
+                    Ok(ServiceResponse::new(request, e.response()).map_into_right_body())
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,258 @@
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::catalog::octree::Octree;
+use crate::render::derivatives::{derivatives, DerivativeKind};
+use crate::render::filter::AstronomicalFilter;
+use crate::render::fits;
+use crate::render::hips::{filter_label, HipsSurvey};
+use crate::render::image::Image;
+use crate::render::tiles::{descriptor_key, levels, tile_key, tiles, Output, TilePyramid};
+use crate::storage::{content_type, render_key, survey_key, ArtifactStorage, StorageError};
+
+#[derive(Debug)]
+pub enum ArtifactError {
+    Encoding(String),
+    Storage(StorageError),
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArtifactError::Encoding(message) => write!(f, "Failed to encode artifact: {}", message),
+            ArtifactError::Storage(e) => e.fmt(f),
+            ArtifactError::Database(e) => write!(f, "Failed to record artifact: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactError {}
+
+/// Store the artifacts of the finished render `render_id` and mark it as finished
+///
+/// The entry point of workers once they rendered `image`. Renders with the image output get their
+/// derivatives and FITS image, renders with the tiles output their tile pyramid, and the render's
+/// `image_key` then points at the image or the Deep Zoom descriptor.
+#[tracing::instrument(name = "Finishing render", skip(db_pool, storage, image))]
+pub async fn finish_render(
+    db_pool: &PgPool,
+    storage: &dyn ArtifactStorage,
+    render_id: Uuid,
+    image: &Image,
+) -> Result<(), ArtifactError> {
+    let output = sqlx::query!("SELECT output FROM renders WHERE id = $1", render_id)
+        .fetch_one(db_pool)
+        .await
+        .map_err(ArtifactError::Database)?
+        .output;
+    let image_key = match Output::try_from(output.as_str()) {
+        Ok(Output::Tiles) => {
+            store_tiles(storage, render_id, image).await?;
+            descriptor_key(render_id)
+        }
+        _ => {
+            store_derivatives(db_pool, storage, render_id, image).await?;
+            render_key(render_id, DerivativeKind::Png16.file_name())
+        }
+    };
+    sqlx::query!(
+        "UPDATE renders SET image_key = $1 WHERE id = $2",
+        image_key,
+        render_id
+    )
+    .execute(db_pool)
+    .await
+    .map_err(ArtifactError::Database)?;
+    Ok(())
+}
+
+/// Generate, store and record the derivatives of the finished render `render_id`
+///
+/// Run by [`finish_render`] once a render is done, before it points the render's `image_key` at
+/// the [`DerivativeKind::Png16`] artifact. The render's FITS image is stored next to it, with the
+/// flux of every pixel. Running it again replaces the previous derivatives.
+#[tracing::instrument(name = "Storing render derivatives", skip(db_pool, storage, image))]
+pub async fn store_derivatives(
+    db_pool: &PgPool,
+    storage: &dyn ArtifactStorage,
+    render_id: Uuid,
+    image: &Image,
+) -> Result<(), ArtifactError> {
+    for derivative in derivatives(image).map_err(ArtifactError::Encoding)? {
+        let key = render_key(render_id, derivative.kind.file_name());
+        let size = derivative.body.len() as i64;
+        storage
+            .put(&key, derivative.body)
+            .await
+            .map_err(ArtifactError::Storage)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO artifacts (
+                id, render_id, kind, storage_key, content_type, width, height, size, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (render_id, kind) DO UPDATE SET
+                storage_key = EXCLUDED.storage_key,
+                content_type = EXCLUDED.content_type,
+                width = EXCLUDED.width,
+                height = EXCLUDED.height,
+                size = EXCLUDED.size,
+                created_at = EXCLUDED.created_at
+            "#,
+            Uuid::new_v4(),
+            render_id,
+            derivative.kind.as_str(),
+            key,
+            content_type(&key),
+            derivative.width as i32,
+            derivative.height as i32,
+            size,
+            Utc::now(),
+        )
+        .execute(db_pool)
+        .await
+        .map_err(ArtifactError::Database)?;
+    }
+    storage
+        .put(&render_key(render_id, "image.fits"), fits::encode(image))
+        .await
+        .map_err(ArtifactError::Storage)
+}
+
+/// Generate and store the tile pyramid of the finished render `render_id`
+///
+/// Run by [`finish_render`] once a render with [`Output::Tiles`] is done, before it points the
+/// render's `image_key` at the Deep Zoom descriptor. Levels are encoded one at a time, so only the
+/// full resolution image and one level are held in memory.
+#[tracing::instrument(name = "Storing render tiles", skip(storage, image))]
+pub async fn store_tiles(
+    storage: &dyn ArtifactStorage,
+    render_id: Uuid,
+    image: &Image,
+) -> Result<(), ArtifactError> {
+    let descriptor_key = descriptor_key(render_id);
+    for (level, level_image) in levels(image) {
+        for tile in tiles(level, &level_image).map_err(ArtifactError::Encoding)? {
+            storage
+                .put(
+                    &tile_key(&descriptor_key, tile.level, tile.column, tile.row),
+                    tile.body,
+                )
+                .await
+                .map_err(ArtifactError::Storage)?;
+        }
+    }
+    // Viewers only find the tiles once they are all stored
+    storage
+        .put(
+            &descriptor_key,
+            TilePyramid::new(image.width, image.height)
+                .descriptor()
+                .into_bytes(),
+        )
+        .await
+        .map_err(ArtifactError::Storage)
+}
+
+/// Render and store the HiPS of the survey `survey_id` in `filter`
+///
+/// Run by the worker once per filter of a survey job. Base pixels are rendered one at a time, so
+/// only the tiles below one of them are held in memory.
+#[tracing::instrument(name = "Storing survey tiles", skip(storage, survey, catalog))]
+pub async fn store_survey(
+    storage: &dyn ArtifactStorage,
+    survey_id: Uuid,
+    survey: &HipsSurvey,
+    catalog: &Octree,
+    filter: &AstronomicalFilter,
+) -> Result<(), ArtifactError> {
+    let label = filter_label(filter);
+    for face in 0..12 {
+        for tile in survey
+            .face_tiles(catalog, filter, face)
+            .map_err(ArtifactError::Encoding)?
+        {
+            storage
+                .put(&survey_key(survey_id, &label, &tile.path()), tile.body)
+                .await
+                .map_err(ArtifactError::Storage)?;
+        }
+    }
+    // Viewers only find the tiles once they are all stored
+    storage
+        .put(
+            &survey_key(survey_id, &label, "properties"),
+            survey
+                .properties(survey_id, filter, Utc::now())
+                .into_bytes(),
+        )
+        .await
+        .map_err(ArtifactError::Storage)
+}
+
+/// Mark the survey `survey_id` as finished
+///
+/// Run by the worker once [`store_survey`] stored the HiPS of every filter of the survey, which
+/// then stops counting towards its API key's concurrent jobs.
+#[tracing::instrument(name = "Finishing survey", skip(db_pool))]
+pub async fn finish_survey(db_pool: &PgPool, survey_id: Uuid) -> Result<(), ArtifactError> {
+    sqlx::query!(
+        "UPDATE surveys SET finished_at = $1 WHERE id = $2",
+        Utc::now(),
+        survey_id
+    )
+    .execute(db_pool)
+    .await
+    .map_err(ArtifactError::Database)?;
+    Ok(())
+}
+
+/// Artifact of a render, as recorded in the database
+#[derive(Debug, Clone)]
+pub struct ArtifactRecord {
+    pub kind: DerivativeKind,
+    pub storage_key: String,
+    pub content_type: String,
+    pub width: u32,
+    pub height: u32,
+    /// Size in bytes
+    pub size: u64,
+}
+
+/// Artifacts recorded for `render_id`, in the order of [`DerivativeKind::ALL`]
+pub async fn render_artifacts(
+    db_pool: &PgPool,
+    render_id: Uuid,
+) -> Result<Vec<ArtifactRecord>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT kind, storage_key, content_type, width, height, size
+        FROM artifacts
+        WHERE render_id = $1
+        "#,
+        render_id
+    )
+    .fetch_all(db_pool)
+    .await?;
+    let mut artifacts: Vec<ArtifactRecord> = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(ArtifactRecord {
+                // Kinds dropped from newer versions are no longer exposed
+                kind: DerivativeKind::try_from(row.kind.as_str()).ok()?,
+                storage_key: row.storage_key,
+                content_type: row.content_type,
+                width: row.width as u32,
+                height: row.height as u32,
+                size: row.size as u64,
+            })
+        })
+        .collect();
+    artifacts.sort_by_key(|artifact| {
+        DerivativeKind::ALL
+            .iter()
+            .position(|kind| *kind == artifact.kind)
+    });
+    Ok(artifacts)
+}
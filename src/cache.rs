@@ -0,0 +1,104 @@
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::catalog::astrometry::CATALOG_VERSION;
+
+/// Version of the renderer, bumped whenever a change alters the images of unchanged jobs
+pub const RENDERER_VERSION: u32 = 1;
+
+/// Content address of a render, as the hex SHA-256 of its canonical parameters
+///
+/// `parameters` are named values of the normalized job, in a fixed order. The catalog and
+/// renderer versions are part of the address, so renders made from older stars or code are never
+/// reused.
+pub fn cache_key(parameters: &[(&str, String)]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!(
+        "catalog={}\nrenderer={}\n",
+        CATALOG_VERSION, RENDERER_VERSION
+    ));
+    for (name, value) in parameters {
+        hasher.update(format!("{}={}\n", name, value));
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Noise seed of exposures without one, so identical jobs render identical noise
+pub fn default_seed(cache_key: &str) -> u64 {
+    let bytes = hex::decode(&cache_key[..16]).expect("Cache keys are hex SHA-256 digests");
+    u64::from_be_bytes(bytes.try_into().expect("Eight bytes were decoded"))
+}
+
+/// Complete the queued render `render_id` with the images and artifacts of an identical render
+///
+/// Only finished renders that have not expired are reused, the most recent first. The new render
+/// shares their stored files and expires with them. Returns the identifier of the reused render,
+/// or `None` when there is none and the render must be queued for the worker.
+#[tracing::instrument(name = "Reusing a cached render", skip(transaction))]
+pub async fn reuse_cached_render(
+    transaction: &mut Transaction<'_, Postgres>,
+    render_id: Uuid,
+    cache_key: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let Some(cached) = sqlx::query!(
+        r#"
+        SELECT id, image_key, expires_at
+        FROM renders
+        WHERE cache_key = $1
+            AND id <> $2
+            AND image_key IS NOT NULL
+            AND (expires_at IS NULL OR expires_at > $3)
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        cache_key,
+        render_id,
+        Utc::now(),
+    )
+    .fetch_optional(&mut *transaction)
+    .await?
+    else {
+        return Ok(None);
+    };
+    sqlx::query!(
+        "UPDATE renders SET image_key = $1, expires_at = $2 WHERE id = $3",
+        cached.image_key,
+        cached.expires_at,
+        render_id
+    )
+    .execute(&mut *transaction)
+    .await?;
+    let artifacts = sqlx::query!(
+        r#"
+        SELECT kind, storage_key, content_type, width, height, size
+        FROM artifacts
+        WHERE render_id = $1
+        "#,
+        cached.id
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+    for artifact in artifacts {
+        sqlx::query!(
+            r#"
+            INSERT INTO artifacts (
+                id, render_id, kind, storage_key, content_type, width, height, size, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            Uuid::new_v4(),
+            render_id,
+            artifact.kind,
+            artifact.storage_key,
+            artifact.content_type,
+            artifact.width,
+            artifact.height,
+            artifact.size,
+            Utc::now(),
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+    Ok(Some(cached.id))
+}
@@ -0,0 +1,104 @@
+use nalgebra as na;
+
+use crate::catalog::Star;
+
+/// Julian year of the catalog's positions and velocities
+pub const CATALOG_EPOCH: f64 = 2016.0;
+/// Release of the catalog, changed whenever its stars change so cached renders are not reused
+pub const CATALOG_VERSION: &str = "gaia-dr3";
+/// Speed of light in parsecs per Julian year
+pub const SPEED_OF_LIGHT: f64 = 0.306_601_4;
+/// Parsecs per Julian year travelled at one kilometre per second
+pub const KILOMETRES_PER_SECOND: f64 = 1.022_712e-6;
+/// Kilometres per second of tangential velocity at one milliarcsecond of parallax and one
+/// milliarcsecond per year of proper motion
+const TANGENTIAL_VELOCITY_FACTOR: f64 = 4.740_470;
+/// Iterations when solving for the emission time of light reaching an observer
+const LIGHT_TIME_ITERATIONS: usize = 3;
+
+/// Rotation from ICRS equatorial to galactic coordinates
+#[rustfmt::skip]
+pub fn icrs_to_galactic() -> na::Matrix3<f64> {
+    na::Matrix3::new(
+        -0.054_875_560_416_215, -0.873_437_090_234_885, -0.483_835_015_548_713,
+        0.494_109_427_875_584, -0.444_829_629_960_011, 0.746_982_244_497_219,
+        -0.867_666_149_019_005, -0.198_076_373_431_202, 0.455_983_776_175_067,
+    )
+}
+
+/// Astrometric solution of a star as published by surveys such as Gaia
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Astrometry {
+    /// Right ascension in degrees
+    pub ra: f64,
+    /// Declination in degrees
+    pub dec: f64,
+    /// Parallax in milliarcseconds
+    pub parallax: f64,
+    /// Proper motion in right ascension, multiplied by the cosine of the declination, in
+    /// milliarcseconds per year
+    pub pm_ra: f64,
+    /// Proper motion in declination in milliarcseconds per year
+    pub pm_dec: f64,
+    /// Radial velocity in kilometres per second, positive when receding
+    pub radial_velocity: f64,
+}
+
+impl Astrometry {
+    /// Heliocentric galactic position in parsecs and velocity in kilometres per second
+    ///
+    /// Returns `None` when the parallax is not positive, as surveys publish for stars too distant
+    /// or too faint to measure it, since it implies no distance.
+    pub fn position_and_velocity(&self) -> Option<(na::Vector3<f32>, na::Vector3<f32>)> {
+        if !self.parallax.is_finite() || self.parallax <= 0.0 {
+            return None;
+        }
+        let (sin_ra, cos_ra) = self.ra.to_radians().sin_cos();
+        let (sin_dec, cos_dec) = self.dec.to_radians().sin_cos();
+        let radial = na::Vector3::new(cos_dec * cos_ra, cos_dec * sin_ra, sin_dec);
+        let east = na::Vector3::new(-sin_ra, cos_ra, 0.0);
+        let north = na::Vector3::new(-sin_dec * cos_ra, -sin_dec * sin_ra, cos_dec);
+
+        let distance = 1000.0 / self.parallax;
+        let velocity = self.radial_velocity * radial
+            + TANGENTIAL_VELOCITY_FACTOR / self.parallax
+                * (self.pm_ra * east + self.pm_dec * north);
+        let rotation = icrs_to_galactic();
+        Some((
+            (rotation * radial * distance).cast(),
+            (rotation * velocity).cast(),
+        ))
+    }
+}
+
+/// Move `stars` from the catalog epoch to `epoch`, in Julian years, along straight lines
+///
+/// With `light_travel_time`, each star is instead placed where it emitted the light reaching
+/// `observer_position` at `epoch`.
+pub fn propagate(
+    stars: &[Star],
+    epoch: f64,
+    observer_position: &na::Vector3<f32>,
+    light_travel_time: bool,
+) -> Vec<Star> {
+    let observer_position = observer_position.cast::<f64>();
+    stars
+        .iter()
+        .map(|star| {
+            let position = star.position.cast::<f64>();
+            let velocity = star.velocity.cast::<f64>() * KILOMETRES_PER_SECOND;
+            let mut elapsed = epoch - CATALOG_EPOCH;
+            if light_travel_time {
+                let arrival = elapsed;
+                for _ in 0..LIGHT_TIME_ITERATIONS {
+                    let distance = (position + velocity * elapsed - observer_position).norm();
+                    elapsed = arrival - distance / SPEED_OF_LIGHT;
+                }
+            }
+            Star {
+                position: (position + velocity * elapsed).cast(),
+                ..star.clone()
+            }
+        })
+        .collect()
+}
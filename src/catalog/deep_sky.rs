@@ -0,0 +1,300 @@
+use std::f64::consts::PI;
+use std::path::Path;
+
+use nalgebra as na;
+
+use crate::catalog::astrometry::Astrometry;
+use crate::configuration::load_yaml_files;
+
+/// Parallax in milliarcseconds of a source one parsec away
+const PARALLAX_AT_ONE_PARSEC: f64 = 1000.0;
+/// Lanczos approximation coefficients for the gamma function, with g = 7
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_81,
+    676.520_368_121_885,
+    -1_259.139_216_722_40,
+    771.323_428_777_653,
+    -176.615_029_162_141,
+    12.507_343_278_686_9,
+    -0.138_571_095_265_720,
+    9.984_369_578_019_57e-6,
+    1.505_632_735_149_31e-7,
+];
+
+/// Radial light profile of an extended object
+///
+/// Radii are in units of the object's `radius`, measured in its own plane.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum Profile {
+    /// Galaxy with a Sérsic profile, exponential disks having an `index` of 1 and elliptical
+    /// galaxies 4. The radius is the half-light radius.
+    Sersic { index: f32 },
+    /// Diffuse emission nebula fading as a Gaussian. The radius is the half-light radius.
+    Gaussian,
+    /// Planetary nebula or supernova remnant, a glowing shell between `inner_radius` and the
+    /// radius, which appears brightest at its rim.
+    Shell { inner_radius: f32 },
+}
+
+impl Profile {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Profile::Sersic { index } if !(0.3..=10.0).contains(index) => {
+                Err("Sersic index must be between 0.3 and 10.".into())
+            }
+            Profile::Shell { inner_radius } if !(0.0..1.0).contains(inner_radius) => {
+                Err("Shell inner_radius must be between 0 and 1.".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Surface brightness at `radius`, relative to the brightness at a radius of 1 for Sérsic
+    /// profiles and to the peak otherwise
+    pub fn intensity(&self, radius: f64) -> f64 {
+        match *self {
+            Profile::Sersic { index } => {
+                let index = index as f64;
+                (-sersic_b(index) * (radius.powf(1.0 / index) - 1.0)).exp()
+            }
+            Profile::Gaussian => 0.5f64.powf(radius * radius),
+            Profile::Shell { inner_radius } => {
+                let chord = |outer: f64| (outer * outer - radius * radius).max(0.0).sqrt();
+                chord(1.0) - chord(inner_radius as f64)
+            }
+        }
+    }
+
+    /// Integral of [`Profile::intensity`] over the object's plane
+    pub fn total_intensity(&self) -> f64 {
+        match *self {
+            Profile::Sersic { index } => {
+                let index = index as f64;
+                let b = sersic_b(index);
+                2.0 * PI * index * b.exp() * b.powf(-2.0 * index) * gamma(2.0 * index)
+            }
+            Profile::Gaussian => PI / 2f64.ln(),
+            Profile::Shell { inner_radius } => {
+                2.0 / 3.0 * PI * (1.0 - (inner_radius as f64).powi(3))
+            }
+        }
+    }
+
+    /// Integral of [`Profile::intensity`] over the object's plane out to [`Profile::extent`]
+    pub fn truncated_intensity(&self) -> f64 {
+        let steps = 10_000;
+        let step = self.extent() / steps as f64;
+        (0..steps)
+            .map(|i| {
+                let radius = (i as f64 + 0.5) * step;
+                2.0 * PI * radius * self.intensity(radius) * step
+            })
+            .sum()
+    }
+
+    /// Fraction of the light inside a radius of 1
+    pub fn enclosed_fraction(&self) -> f64 {
+        match self {
+            Profile::Sersic { .. } | Profile::Gaussian => 0.5,
+            Profile::Shell { .. } => 1.0,
+        }
+    }
+
+    /// Radius beyond which the light is neglected
+    pub fn extent(&self) -> f64 {
+        match self {
+            Profile::Sersic { .. } => 8.0,
+            Profile::Gaussian => 3.0,
+            Profile::Shell { .. } => 1.0,
+        }
+    }
+}
+
+/// Constant making the Sérsic radius of 1 enclose half of the light, as approximated by
+/// Ciotti and Bertin (1999)
+fn sersic_b(index: f64) -> f64 {
+    2.0 * index - 1.0 / 3.0 + 4.0 / (405.0 * index) + 46.0 / (25_515.0 * index * index)
+}
+
+/// Gamma function for arguments of at least 0.5, by the Lanczos approximation
+fn gamma(x: f64) -> f64 {
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = LANCZOS_COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS_COEFFICIENTS[0], |sum, (i, coefficient)| {
+            sum + coefficient / (x + i as f64 + 1.0)
+        });
+    (2.0 * PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * series
+}
+
+/// Spectral line adding to the continuum of an object
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct EmissionLine {
+    /// Rest wavelength in micrometres
+    pub wavelength: f32,
+    /// Width of continuum, in micrometres, carrying as much light as the line
+    pub equivalent_width: f32,
+}
+
+/// Deep-sky object as listed in the catalog, with sizes seen from the Sun
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+struct CatalogEntry {
+    /// Right ascension in degrees
+    ra: f64,
+    /// Declination in degrees
+    dec: f64,
+    /// Distance in parsecs
+    distance: f64,
+    profile: Profile,
+    /// Angular radius in arcminutes, see [`Profile`]
+    radius: f64,
+    /// Angle of the major axis from north through east, in degrees
+    #[serde(default)]
+    position_angle: f64,
+    /// Angle between the object's plane and the sky, in degrees, 0 when seen face-on
+    #[serde(default)]
+    inclination: f64,
+    /// Ratio of the object's thickness to its diameter, 1 for a sphere
+    #[serde(default = "default_thickness")]
+    thickness: f32,
+    /// Mean face-on surface brightness of the continuum within the radius, in V magnitudes per
+    /// square arcsecond
+    surface_brightness: f32,
+    #[serde(default)]
+    emission_lines: Vec<EmissionLine>,
+}
+
+fn default_thickness() -> f32 {
+    1.0
+}
+
+/// Galaxy or nebula with extended emission
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeepSkyObject {
+    pub name: String,
+    /// Position of the centre in parsecs
+    pub position: na::Vector3<f32>,
+    pub profile: Profile,
+    /// Radius in parsecs, see [`Profile`]
+    pub radius: f32,
+    /// Unit normal to the object's plane
+    pub normal: na::Vector3<f32>,
+    /// Ratio of the object's thickness to its diameter, 1 for a sphere
+    pub thickness: f32,
+    /// Mean face-on surface brightness of the continuum within the radius, in magnitudes per
+    /// square arcsecond
+    pub surface_brightness: f32,
+    pub emission_lines: Vec<EmissionLine>,
+}
+
+impl DeepSkyObject {
+    fn from_entry(name: &str, entry: &CatalogEntry) -> Result<Self, String> {
+        if !entry.distance.is_finite() || entry.distance <= 0.0 {
+            return Err("distance must be positive.".into());
+        }
+        if entry.radius.is_nan() || entry.radius <= 0.0 {
+            return Err("radius must be positive.".into());
+        }
+        if !(0.0..=90.0).contains(&entry.inclination) {
+            return Err("inclination must be between 0 and 90 degrees.".into());
+        }
+        if entry.thickness.is_nan() || entry.thickness <= 0.0 || entry.thickness > 1.0 {
+            return Err("thickness must be positive and at most 1.".into());
+        }
+        if !entry.surface_brightness.is_finite() {
+            return Err("surface_brightness must be a finite number.".into());
+        }
+        if entry.emission_lines.iter().any(|line| {
+            line.wavelength.is_nan()
+                || line.wavelength <= 0.0
+                || line.equivalent_width.is_nan()
+                || line.equivalent_width < 0.0
+        }) {
+            return Err("emission lines need positive wavelengths and equivalent widths.".into());
+        }
+        entry.profile.validate()?;
+
+        let astrometry = Astrometry {
+            ra: entry.ra,
+            dec: entry.dec,
+            parallax: PARALLAX_AT_ONE_PARSEC / entry.distance,
+            pm_ra: 0.0,
+            pm_dec: 0.0,
+            radial_velocity: 0.0,
+        };
+        let (position, _) = astrometry
+            .position_and_velocity()
+            .expect("Distances are validated to be positive and finite");
+        // Directions on the sky at the object, from the catalog's equatorial ones
+        let north = Astrometry {
+            dec: entry.dec + 90.0,
+            ..astrometry
+        }
+        .position_and_velocity()
+        .expect("Distances are validated to be positive and finite")
+        .0
+        .normalize();
+        let line_of_sight = position.normalize();
+        let east = north.cross(&line_of_sight);
+        let minor_axis_angle = (entry.position_angle + 90.0).to_radians() as f32;
+        let minor_axis = minor_axis_angle.cos() * north + minor_axis_angle.sin() * east;
+        let (sin_inclination, cos_inclination) = (entry.inclination.to_radians() as f32).sin_cos();
+
+        Ok(Self {
+            name: name.to_string(),
+            position,
+            profile: entry.profile,
+            radius: (entry.distance * (entry.radius / 60.0).to_radians()) as f32,
+            normal: (-cos_inclination * line_of_sight + sin_inclination * minor_axis).normalize(),
+            thickness: entry.thickness,
+            surface_brightness: entry.surface_brightness,
+            emission_lines: entry.emission_lines.clone(),
+        })
+    }
+
+    /// Ratio of the apparent minor to major axes when seen along `line_of_sight`
+    ///
+    /// Hubble's formula for an oblate spheroid of the object's thickness.
+    pub fn axis_ratio(&self, line_of_sight: &na::Vector3<f32>) -> f32 {
+        let cos_inclination = self.normal.dot(&line_of_sight.normalize()).abs().min(1.0);
+        (cos_inclination.powi(2) + self.thickness.powi(2) * (1.0 - cos_inclination.powi(2))).sqrt()
+    }
+
+    /// Continuum flux of the whole object, relative to a zero magnitude source
+    ///
+    /// Surface brightness does not change with distance, so this is the flux at an apparent
+    /// radius of `angular_radius` radians.
+    pub fn continuum_flux(&self, angular_radius: f64) -> f64 {
+        let square_arcseconds = PI * (angular_radius.to_degrees() * 3600.0).powi(2);
+        10f64.powf(-0.4 * self.surface_brightness as f64) * square_arcseconds
+            / self.profile.enclosed_fraction()
+    }
+}
+
+/// Every deep-sky object known to the application
+#[derive(Debug, Default)]
+pub struct DeepSkyCatalog {
+    pub objects: Vec<DeepSkyObject>,
+}
+
+impl DeepSkyCatalog {
+    /// Load every object in `directory`, each named after its YAML file
+    pub fn load(directory: &Path) -> Result<Self, config::ConfigError> {
+        let objects = load_yaml_files::<CatalogEntry>(directory)?
+            .into_iter()
+            .map(|(name, entry)| {
+                DeepSkyObject::from_entry(&name, &entry).map_err(|e| {
+                    config::ConfigError::Message(format!("Invalid deep-sky object {}: {}", name, e))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { objects })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DeepSkyObject> {
+        self.objects.iter().find(|object| object.name == name)
+    }
+}
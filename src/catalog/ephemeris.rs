@@ -0,0 +1,312 @@
+use nalgebra as na;
+
+use crate::catalog::astrometry::icrs_to_galactic;
+
+/// Julian date of the J2000.0 epoch
+pub const J2000: f64 = 2_451_545.0;
+/// Astronomical unit in parsecs
+pub const ASTRONOMICAL_UNIT: f64 = 4.848_136_811e-6;
+/// Astronomical unit in kilometres
+const ASTRONOMICAL_UNIT_KM: f64 = 149_597_870.7;
+/// Obliquity of the ecliptic at J2000.0 in degrees
+const J2000_OBLIQUITY: f64 = 23.439_279;
+/// General precession in longitude, in degrees per Julian century
+const PRECESSION_IN_LONGITUDE: f64 = 1.396_971;
+/// Ratio of the Earth's mass to the Moon's
+const EARTH_MOON_MASS_RATIO: f64 = 81.300_57;
+/// Julian years over which the planets' orbital elements were fitted
+pub const EPHEMERIS_YEARS: std::ops::RangeInclusive<f64> = 1800.0..=2050.0;
+
+/// Julian date of a Julian year
+pub fn julian_date(julian_year: f64) -> f64 {
+    J2000 + (julian_year - 2000.0) * 365.25
+}
+
+/// Julian year of a Julian date
+pub fn julian_year(julian_date: f64) -> f64 {
+    2000.0 + (julian_date - J2000) / 365.25
+}
+
+/// Obliquity of the ecliptic of date in degrees
+pub fn obliquity(julian_date: f64) -> f64 {
+    23.439 - 4e-7 * (julian_date - J2000)
+}
+
+/// Unit vector in the equatorial frame of date for ecliptic coordinates of date, in degrees
+pub fn ecliptic_to_equatorial(julian_date: f64, longitude: f64, latitude: f64) -> na::Vector3<f64> {
+    rotate_ecliptic(
+        &spherical(longitude, latitude),
+        obliquity(julian_date).to_radians(),
+    )
+}
+
+/// Unit vector for a longitude and latitude in degrees
+fn spherical(longitude: f64, latitude: f64) -> na::Vector3<f64> {
+    let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    na::Vector3::new(cos_lat * cos_lon, cos_lat * sin_lon, sin_lat)
+}
+
+/// Rotate an ecliptic vector onto the equator, `obliquity` radians away
+fn rotate_ecliptic(ecliptic: &na::Vector3<f64>, obliquity: f64) -> na::Vector3<f64> {
+    let (sin_obl, cos_obl) = obliquity.sin_cos();
+    na::Vector3::new(
+        ecliptic.x,
+        cos_obl * ecliptic.y - sin_obl * ecliptic.z,
+        sin_obl * ecliptic.y + cos_obl * ecliptic.z,
+    )
+}
+
+/// Geocentric direction of the Sun in the equatorial frame of date
+///
+/// The Astronomical Almanac's low precision formulae, good to about 0.01° this century.
+pub fn sun(julian_date: f64) -> na::Vector3<f64> {
+    let days = julian_date - J2000;
+    let mean_longitude = 280.460 + 0.985_647_4 * days;
+    let mean_anomaly = (357.528 + 0.985_600_3 * days).to_radians();
+    let longitude =
+        mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin();
+    ecliptic_to_equatorial(julian_date, longitude, 0.0)
+}
+
+/// Geocentric direction of the Moon in the equatorial frame of date
+pub fn moon(julian_date: f64) -> na::Vector3<f64> {
+    let (longitude, latitude, _) = lunar_theory(julian_date);
+    ecliptic_to_equatorial(julian_date, longitude, latitude)
+}
+
+/// Geocentric ecliptic longitude and latitude of date of the Moon in degrees, and its distance
+/// in kilometres
+///
+/// The main terms of the lunar theory as truncated by the Astronomical Almanac's low precision
+/// formulae, good to about 0.3° and 0.2% in distance.
+fn lunar_theory(julian_date: f64) -> (f64, f64, f64) {
+    let t = (julian_date - J2000) / 36_525.0;
+    let sin = |degrees: f64| degrees.to_radians().sin();
+    let cos = |degrees: f64| degrees.to_radians().cos();
+    let longitude = 218.32 + 481_267.881 * t + 6.29 * sin(135.0 + 477_198.87 * t)
+        - 1.27 * sin(259.3 - 413_335.36 * t)
+        + 0.66 * sin(235.7 + 890_534.22 * t)
+        + 0.21 * sin(269.9 + 954_397.74 * t)
+        - 0.19 * sin(357.5 + 35_999.05 * t)
+        - 0.11 * sin(186.5 + 966_404.03 * t);
+    let latitude = 5.13 * sin(93.3 + 483_202.02 * t) + 0.28 * sin(228.2 + 960_400.89 * t)
+        - 0.28 * sin(318.3 + 6_003.15 * t)
+        - 0.17 * sin(217.6 - 407_332.21 * t);
+    let parallax = 0.9508
+        + 0.0518 * cos(134.9 + 477_198.85 * t)
+        + 0.0095 * cos(259.2 - 413_335.38 * t)
+        + 0.0078 * cos(235.7 + 890_534.23 * t)
+        + 0.0028 * cos(269.9 + 954_397.70 * t);
+    let distance = Body::Earth.radius() / parallax.to_radians().sin();
+    (longitude, latitude, distance)
+}
+
+/// Major bodies of the solar system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Body {
+    Sun,
+    Mercury,
+    Venus,
+    Earth,
+    Moon,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+}
+
+/// Keplerian elements at J2000.0 and their rates per Julian century
+///
+/// Semi-major axis in astronomical units, eccentricity, inclination, mean longitude, longitude
+/// of perihelion and longitude of the ascending node in degrees.
+struct OrbitalElements {
+    elements: [f64; 6],
+    rates: [f64; 6],
+}
+
+impl Body {
+    pub const ALL: [Body; 10] = [
+        Body::Sun,
+        Body::Mercury,
+        Body::Venus,
+        Body::Earth,
+        Body::Moon,
+        Body::Mars,
+        Body::Jupiter,
+        Body::Saturn,
+        Body::Uranus,
+        Body::Neptune,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Body::Sun => "sun",
+            Body::Mercury => "mercury",
+            Body::Venus => "venus",
+            Body::Earth => "earth",
+            Body::Moon => "moon",
+            Body::Mars => "mars",
+            Body::Jupiter => "jupiter",
+            Body::Saturn => "saturn",
+            Body::Uranus => "uranus",
+            Body::Neptune => "neptune",
+        }
+    }
+
+    /// Mean radius in kilometres
+    pub fn radius(&self) -> f64 {
+        match self {
+            Body::Sun => 695_700.0,
+            Body::Mercury => 2_439.7,
+            Body::Venus => 6_051.8,
+            Body::Earth => 6_371.0,
+            Body::Moon => 1_737.4,
+            Body::Mars => 3_389.5,
+            Body::Jupiter => 69_911.0,
+            Body::Saturn => 58_232.0,
+            Body::Uranus => 25_362.0,
+            Body::Neptune => 24_622.0,
+        }
+    }
+
+    /// Standish's elements fitted to JPL's DE405 ephemeris over 1800 to 2050, good to
+    /// arcminutes for the planets
+    ///
+    /// The Earth's elements are those of the Earth-Moon barycentre.
+    #[rustfmt::skip]
+    fn orbital_elements(&self) -> Option<OrbitalElements> {
+        let (elements, rates) = match self {
+            Body::Sun | Body::Moon => return None,
+            Body::Mercury => (
+                [0.387_099_27, 0.205_635_93, 7.004_979_02, 252.250_323_50, 77.457_796_28, 48.330_765_93],
+                [0.000_000_37, 0.000_019_06, -0.005_947_49, 149_472.674_111_75, 0.160_476_89, -0.125_340_81],
+            ),
+            Body::Venus => (
+                [0.723_335_66, 0.006_776_72, 3.394_676_05, 181.979_099_50, 131.602_467_18, 76.679_842_55],
+                [0.000_003_90, -0.000_041_07, -0.000_788_90, 58_517.815_387_29, 0.002_683_29, -0.277_694_18],
+            ),
+            Body::Earth => (
+                [1.000_002_61, 0.016_711_23, -0.000_015_31, 100.464_571_66, 102.937_681_93, 0.0],
+                [0.000_005_62, -0.000_043_92, -0.012_946_68, 35_999.372_449_81, 0.323_273_64, 0.0],
+            ),
+            Body::Mars => (
+                [1.523_710_34, 0.093_394_10, 1.849_691_42, -4.553_432_05, -23.943_629_59, 49.559_538_91],
+                [0.000_018_47, 0.000_078_82, -0.008_131_31, 19_140.302_684_99, 0.444_410_88, -0.292_573_43],
+            ),
+            Body::Jupiter => (
+                [5.202_887_00, 0.048_386_24, 1.304_396_95, 34.396_440_51, 14.728_479_83, 100.473_909_09],
+                [-0.000_116_07, -0.000_132_53, -0.001_837_14, 3_034.746_127_75, 0.212_526_68, 0.204_691_06],
+            ),
+            Body::Saturn => (
+                [9.536_675_94, 0.053_861_79, 2.485_991_87, 49.954_244_23, 92.598_878_31, 113.662_424_48],
+                [-0.001_250_60, -0.000_509_91, 0.001_936_09, 1_222.493_622_01, -0.418_972_16, -0.288_677_94],
+            ),
+            Body::Uranus => (
+                [19.189_164_64, 0.047_257_44, 0.772_637_83, 313.238_104_51, 170.954_276_30, 74.016_925_03],
+                [-0.001_961_76, -0.000_043_97, -0.002_429_39, 428.482_027_85, 0.408_052_81, 0.042_405_89],
+            ),
+            Body::Neptune => (
+                [30.069_922_76, 0.008_590_48, 1.770_043_47, -55.120_029_69, 44.964_762_27, 131.784_225_74],
+                [0.000_262_91, 0.000_051_05, 0.000_353_72, 218.459_453_25, -0.322_414_64, -0.005_086_64],
+            ),
+        };
+        Some(OrbitalElements { elements, rates })
+    }
+
+    /// Heliocentric position in astronomical units, on the ecliptic and equinox of J2000.0
+    pub fn heliocentric_ecliptic(&self, julian_date: f64) -> na::Vector3<f64> {
+        match self {
+            Body::Sun => na::Vector3::zeros(),
+            Body::Earth => {
+                let barycentre = kepler_position(&self.orbital_elements().unwrap(), julian_date);
+                barycentre - geocentric_moon(julian_date) / (1.0 + EARTH_MOON_MASS_RATIO)
+            }
+            Body::Moon => {
+                let barycentre =
+                    kepler_position(&Body::Earth.orbital_elements().unwrap(), julian_date);
+                barycentre
+                    + geocentric_moon(julian_date) * EARTH_MOON_MASS_RATIO
+                        / (1.0 + EARTH_MOON_MASS_RATIO)
+            }
+            _ => kepler_position(&self.orbital_elements().unwrap(), julian_date),
+        }
+    }
+
+    /// Position in catalog coordinates, in parsecs
+    pub fn position(&self, julian_date: f64) -> na::Vector3<f32> {
+        let equatorial = rotate_ecliptic(
+            &self.heliocentric_ecliptic(julian_date),
+            J2000_OBLIQUITY.to_radians(),
+        );
+        (icrs_to_galactic() * equatorial * ASTRONOMICAL_UNIT).cast()
+    }
+
+    /// Visual magnitude at `sun_distance` and `observer_distance` astronomical units, with
+    /// `phase_angle` degrees between the Sun and the observer seen from the body
+    ///
+    /// The Astronomical Almanac's phase laws for the planets, ignoring Saturn's rings, and
+    /// Krisciunas and Schaefer's for the Moon, which also stands in for the Earth.
+    pub fn magnitude(&self, sun_distance: f64, observer_distance: f64, phase_angle: f64) -> f32 {
+        let i = phase_angle.abs();
+        let (absolute, phase) = match self {
+            Body::Sun => return (-26.74 + 5.0 * observer_distance.log10()) as f32,
+            Body::Mercury => (
+                -0.42,
+                0.0380 * i - 0.000_273 * i.powi(2) + 0.000_002 * i.powi(3),
+            ),
+            Body::Venus => (
+                -4.40,
+                0.0009 * i + 0.000_239 * i.powi(2) - 0.000_000_65 * i.powi(3),
+            ),
+            Body::Earth => (-3.99, 0.026 * i + 4e-9 * i.powi(4)),
+            Body::Moon => (0.21, 0.026 * i + 4e-9 * i.powi(4)),
+            Body::Mars => (-1.52, 0.016 * i),
+            Body::Jupiter => (-9.40, 0.005 * i),
+            Body::Saturn => (-8.88, 0.0),
+            Body::Uranus => (-7.19, 0.0),
+            Body::Neptune => (-6.87, 0.0),
+        };
+        (absolute + 5.0 * (sun_distance * observer_distance).log10() + phase) as f32
+    }
+}
+
+/// Heliocentric position in astronomical units of a body following `orbit`
+fn kepler_position(orbit: &OrbitalElements, julian_date: f64) -> na::Vector3<f64> {
+    let t = (julian_date - J2000) / 36_525.0;
+    let [a, e, inclination, mean_longitude, perihelion, node] =
+        std::array::from_fn(|i| orbit.elements[i] + orbit.rates[i] * t);
+    let argument_of_perihelion = (perihelion - node).to_radians();
+    let mean_anomaly = (mean_longitude - perihelion).to_radians();
+    let (inclination, node) = (inclination.to_radians(), node.to_radians());
+
+    // Newton's method on Kepler's equation, E - e sin E = M
+    let mut eccentric_anomaly = mean_anomaly + e * mean_anomaly.sin();
+    for _ in 0..8 {
+        eccentric_anomaly -= (eccentric_anomaly - e * eccentric_anomaly.sin() - mean_anomaly)
+            / (1.0 - e * eccentric_anomaly.cos());
+    }
+    let x = a * (eccentric_anomaly.cos() - e);
+    let y = a * (1.0 - e * e).sqrt() * eccentric_anomaly.sin();
+
+    let (sin_w, cos_w) = argument_of_perihelion.sin_cos();
+    let (sin_node, cos_node) = node.sin_cos();
+    let (sin_i, cos_i) = inclination.sin_cos();
+    na::Vector3::new(
+        (cos_w * cos_node - sin_w * sin_node * cos_i) * x
+            + (-sin_w * cos_node - cos_w * sin_node * cos_i) * y,
+        (cos_w * sin_node + sin_w * cos_node * cos_i) * x
+            + (-sin_w * sin_node + cos_w * cos_node * cos_i) * y,
+        sin_w * sin_i * x + cos_w * sin_i * y,
+    )
+}
+
+/// Geocentric position of the Moon in astronomical units, on the ecliptic and equinox of J2000.0
+fn geocentric_moon(julian_date: f64) -> na::Vector3<f64> {
+    let (longitude, latitude, distance) = lunar_theory(julian_date);
+    let centuries = (julian_date - J2000) / 36_525.0;
+    // The lunar theory's longitudes are measured from the moving equinox of date
+    let longitude = longitude - PRECESSION_IN_LONGITUDE * centuries;
+    spherical(longitude, latitude) * distance / ASTRONOMICAL_UNIT_KM
+}
@@ -0,0 +1,94 @@
+use nalgebra as na;
+
+pub mod astrometry;
+pub mod deep_sky;
+pub mod ephemeris;
+pub mod octree;
+
+use crate::catalog::astrometry::Astrometry;
+
+/// Distance (in parsecs) below which stars are treated as if the observer were at their surface
+const MINIMUM_DISTANCE: f32 = 1e-8;
+/// Radius of the Sun in parsecs
+pub const SOLAR_RADIUS: f32 = 2.254_6e-8;
+/// Absolute visual magnitude of the Sun
+const SOLAR_ABSOLUTE_MAGNITUDE: f32 = 4.83;
+/// Bounds in solar radii of estimated stellar radii, from red dwarfs to supergiants
+const ESTIMATED_RADIUS_RANGE: std::ops::RangeInclusive<f32> = 0.1..=1500.0;
+
+/// A single star of the catalog
+///
+/// Positions are in parsecs, in the same coordinate system as a render job's `observer_position`.
+/// Velocities are in kilometres per second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Star {
+    pub id: u64,
+    pub position: na::Vector3<f32>,
+    pub velocity: na::Vector3<f32>,
+    pub absolute_magnitude: f32,
+    /// Radius in solar radii, estimated from the luminosity when the catalog has none
+    pub radius: Option<f32>,
+}
+
+impl Star {
+    /// Star with a survey's astrometry and apparent magnitude as seen from the Sun
+    ///
+    /// Returns `None` for stars without a usable parallax, see
+    /// [`Astrometry::position_and_velocity`].
+    pub fn from_astrometry(
+        id: u64,
+        astrometry: &Astrometry,
+        apparent_magnitude: f32,
+    ) -> Option<Self> {
+        let (position, velocity) = astrometry.position_and_velocity()?;
+        Some(Self {
+            id,
+            position,
+            velocity,
+            absolute_magnitude: apparent_magnitude - 5.0 * (position.norm() / 10.0).log10(),
+            radius: None,
+        })
+    }
+
+    /// Radius of the star in parsecs, see [`estimated_radius`]
+    pub fn physical_radius(&self) -> f32 {
+        self.radius
+            .unwrap_or_else(|| estimated_radius(self.absolute_magnitude))
+            * SOLAR_RADIUS
+    }
+
+    /// Magnitude of the star as seen from `observer_position`
+    pub fn apparent_magnitude(&self, observer_position: &na::Vector3<f32>) -> f32 {
+        apparent_magnitude(
+            self.absolute_magnitude,
+            (self.position - observer_position).norm(),
+        )
+    }
+}
+
+/// Apparent magnitude of a source with the given absolute magnitude at `distance` parsecs
+pub fn apparent_magnitude(absolute_magnitude: f32, distance: f32) -> f32 {
+    absolute_magnitude + 5.0 * (distance.max(MINIMUM_DISTANCE) / 10.0).log10()
+}
+
+/// Luminosity relative to a zero absolute magnitude source
+pub fn luminosity(absolute_magnitude: f32) -> f64 {
+    10f64.powf(-0.4 * absolute_magnitude as f64)
+}
+
+/// Absolute magnitude of a source with a luminosity relative to a zero absolute magnitude source
+pub fn absolute_magnitude(luminosity: f64) -> f32 {
+    (-2.5 * luminosity.log10()) as f32
+}
+
+/// Radius in solar radii of a main sequence star with the given absolute magnitude
+///
+/// Follows from the mass-luminosity relation L ∝ M^3.5 and the mass-radius relation R ∝ M^0.8,
+/// so giants are given the radii of equally bright dwarfs.
+pub fn estimated_radius(absolute_magnitude: f32) -> f32 {
+    let luminosity = 10f32.powf(0.4 * (SOLAR_ABSOLUTE_MAGNITUDE - absolute_magnitude));
+    luminosity.powf(0.8 / 3.5).clamp(
+        *ESTIMATED_RADIUS_RANGE.start(),
+        *ESTIMATED_RADIUS_RANGE.end(),
+    )
+}
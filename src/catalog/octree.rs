@@ -0,0 +1,334 @@
+use std::ops::Range;
+
+use nalgebra as na;
+
+use crate::catalog::astrometry::propagate;
+use crate::catalog::{absolute_magnitude, apparent_magnitude, luminosity, Star};
+
+/// Nodes holding this many stars or fewer are not subdivided any further
+const LEAF_CAPACITY: usize = 32;
+/// Guards against unbounded subdivision when many stars share a position
+const MAX_DEPTH: usize = 24;
+/// Largest angular size, in units of the query's angular resolution, of a node drawn as a single glow
+const MAX_GLOW_SIZE: f32 = 4.0;
+
+/// Infinite cone of directions seen from `apex`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cone {
+    pub apex: na::Vector3<f32>,
+    /// Unit vector along the cone's axis
+    pub axis: na::Vector3<f32>,
+    /// Angle between the axis and the cone's surface, in radians
+    pub half_angle: f32,
+}
+
+impl Cone {
+    pub fn contains(&self, point: &na::Vector3<f32>) -> bool {
+        let offset = point - self.apex;
+        let distance = offset.norm();
+        distance <= f32::EPSILON || angle_between(&offset, &self.axis, distance) <= self.half_angle
+    }
+
+    /// Conservative test for whether any part of a sphere lies inside the cone
+    fn intersects_sphere(&self, center: &na::Vector3<f32>, radius: f32) -> bool {
+        let offset = center - self.apex;
+        let distance = offset.norm();
+        if distance <= radius || self.half_angle >= std::f32::consts::PI {
+            return true;
+        }
+        let angular_radius = (radius / distance).asin();
+        angle_between(&offset, &self.axis, distance) - angular_radius <= self.half_angle
+    }
+}
+
+/// Angle between `vector` (of length `norm`) and the unit vector `axis`
+fn angle_between(vector: &na::Vector3<f32>, axis: &na::Vector3<f32>, norm: f32) -> f32 {
+    (vector.dot(axis) / norm).clamp(-1.0, 1.0).acos()
+}
+
+/// Axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: na::Vector3<f32>,
+    pub max: na::Vector3<f32>,
+}
+
+impl Aabb {
+    /// Smallest cube containing every position, or `None` if there are no positions
+    fn bounding_cube<'a>(positions: impl Iterator<Item = &'a na::Vector3<f32>>) -> Option<Self> {
+        let mut positions = positions.peekable();
+        let first = **positions.peek()?;
+        let (min, max) = positions.fold((first, first), |(min, max), position| {
+            (min.inf(position), max.sup(position))
+        });
+        let half_size = (max - min).max() / 2.0;
+        let center = (min + max) / 2.0;
+        let half_extent = na::Vector3::repeat(half_size);
+        Some(Self {
+            min: center - half_extent,
+            max: center + half_extent,
+        })
+    }
+
+    pub fn center(&self) -> na::Vector3<f32> {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Radius of the sphere circumscribing the box
+    pub fn radius(&self) -> f32 {
+        (self.max - self.min).norm() / 2.0
+    }
+
+    /// Distance from `point` to the closest point of the box, zero if the point is inside
+    pub fn distance_to(&self, point: &na::Vector3<f32>) -> f32 {
+        let below = self.min - point;
+        let above = point - self.max;
+        below.sup(&above).sup(&na::Vector3::zeros()).norm()
+    }
+
+    /// Index of the octant of the box containing `point`
+    fn octant(&self, point: &na::Vector3<f32>) -> usize {
+        let center = self.center();
+        (0..3)
+            .filter(|&axis| point[axis] >= center[axis])
+            .map(|axis| 1 << axis)
+            .sum()
+    }
+
+    fn child(&self, octant: usize) -> Self {
+        let center = self.center();
+        let mut min = self.min;
+        let mut max = center;
+        for axis in 0..3 {
+            if octant & (1 << axis) != 0 {
+                min[axis] = center[axis];
+                max[axis] = self.max[axis];
+            }
+        }
+        Self { min, max }
+    }
+}
+
+/// Integrated light of a group of stars that are individually too faint to be seen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glow {
+    /// Luminosity-weighted mean position of the stars
+    pub position: na::Vector3<f32>,
+    /// Absolute magnitude of the stars' combined light
+    pub absolute_magnitude: f32,
+    /// Luminosity-weighted RMS distance of the stars from `position`, in parsecs
+    pub radius: f32,
+}
+
+impl Glow {
+    /// Combined light of `stars`, or `None` if there are no stars
+    pub fn from_stars<'a>(stars: impl IntoIterator<Item = &'a Star>) -> Option<Self> {
+        let mut total_luminosity = 0.0;
+        let mut first_moment = na::Vector3::<f64>::zeros();
+        let mut second_moment = 0.0;
+        for star in stars {
+            let weight = luminosity(star.absolute_magnitude);
+            let position = star.position.cast::<f64>();
+            total_luminosity += weight;
+            first_moment += weight * position;
+            second_moment += weight * position.norm_squared();
+        }
+        if total_luminosity <= 0.0 {
+            return None;
+        }
+        let position = first_moment / total_luminosity;
+        let variance = second_moment / total_luminosity - position.norm_squared();
+        Some(Self {
+            position: position.cast(),
+            absolute_magnitude: absolute_magnitude(total_luminosity),
+            radius: variance.max(0.0).sqrt() as f32,
+        })
+    }
+
+    pub fn apparent_magnitude(&self, observer_position: &na::Vector3<f32>) -> f32 {
+        apparent_magnitude(
+            self.absolute_magnitude,
+            (self.position - observer_position).norm(),
+        )
+    }
+}
+
+/// Sources returned by a level of detail query
+#[derive(Debug, Default)]
+pub struct VisibleSources<'a> {
+    /// Stars bright enough to be seen individually
+    pub stars: Vec<&'a Star>,
+    /// Groups of stars too faint to be seen individually
+    pub glows: Vec<Glow>,
+}
+
+#[derive(Debug)]
+struct Node {
+    bounds: Aabb,
+    /// Smallest absolute magnitude of all the stars below this node
+    brightest_magnitude: f32,
+    /// Combined light of all the stars below this node
+    glow: Glow,
+    /// Stars below this node, as a contiguous range of `Octree::stars`
+    stars: Range<usize>,
+    children: Vec<usize>,
+}
+
+/// Octree over star positions, for fetching the stars visible inside a view cone
+#[derive(Debug)]
+pub struct Octree {
+    nodes: Vec<Node>,
+    stars: Vec<Star>,
+}
+
+impl Octree {
+    pub fn new(mut stars: Vec<Star>) -> Self {
+        let mut nodes = vec![];
+        if let Some(bounds) = Aabb::bounding_cube(stars.iter().map(|star| &star.position)) {
+            build(&mut nodes, &mut stars, 0, bounds, 0);
+        }
+        Self { nodes, stars }
+    }
+
+    /// Index over the stars as seen at `epoch`, see [`propagate`]
+    pub fn at_epoch(
+        &self,
+        epoch: f64,
+        observer_position: &na::Vector3<f32>,
+        light_travel_time: bool,
+    ) -> Self {
+        Self::new(propagate(
+            &self.stars,
+            epoch,
+            observer_position,
+            light_travel_time,
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.stars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stars.is_empty()
+    }
+
+    /// Every star inside `cone` that is at least as bright as `limiting_magnitude` from the cone's apex
+    ///
+    /// Whole subtrees are skipped when they lie outside the cone or when even their brightest star
+    /// would be too faint at the subtree's closest approach to the apex.
+    pub fn query(&self, cone: &Cone, limiting_magnitude: f32) -> Vec<&Star> {
+        let mut visible = vec![];
+        if self.nodes.is_empty() {
+            return visible;
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let closest_distance = node.bounds.distance_to(&cone.apex);
+            if apparent_magnitude(node.brightest_magnitude, closest_distance) > limiting_magnitude
+                || !cone.intersects_sphere(&node.bounds.center(), node.bounds.radius())
+            {
+                continue;
+            }
+            if node.children.is_empty() {
+                visible.extend(self.stars[node.stars.clone()].iter().filter(|star| {
+                    cone.contains(&star.position)
+                        && star.apparent_magnitude(&cone.apex) <= limiting_magnitude
+                }));
+            } else {
+                stack.extend(&node.children);
+            }
+        }
+        visible
+    }
+
+    /// Like [`Octree::query`], but faint stars are kept as diffuse glows instead of being discarded
+    ///
+    /// Subtrees whose stars are all fainter than `limiting_magnitude` are drawn as a single glow, once
+    /// they appear no larger than a few times `angular_resolution` (in radians) from the cone's apex.
+    pub fn query_with_glow(
+        &self,
+        cone: &Cone,
+        limiting_magnitude: f32,
+        angular_resolution: f32,
+    ) -> VisibleSources<'_> {
+        let mut visible = VisibleSources::default();
+        if self.nodes.is_empty() {
+            return visible;
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !cone.intersects_sphere(&node.bounds.center(), node.bounds.radius()) {
+                continue;
+            }
+            let closest_distance = node.bounds.distance_to(&cone.apex);
+            let all_faint =
+                apparent_magnitude(node.brightest_magnitude, closest_distance) > limiting_magnitude;
+            let angular_size = node.bounds.radius() / closest_distance;
+            if all_faint && angular_size <= MAX_GLOW_SIZE * angular_resolution {
+                visible.glows.push(node.glow);
+            } else if node.children.is_empty() {
+                let (bright, faint): (Vec<_>, Vec<_>) = self.stars[node.stars.clone()]
+                    .iter()
+                    .filter(|star| cone.contains(&star.position))
+                    .partition(|star| star.apparent_magnitude(&cone.apex) <= limiting_magnitude);
+                visible.stars.extend(bright);
+                visible.glows.extend(Glow::from_stars(faint));
+            } else {
+                stack.extend(&node.children);
+            }
+        }
+        visible
+    }
+}
+
+/// Recursively build the subtree holding `stars`, which start at `offset` in the final star order
+///
+/// Returns the index of the subtree's root node.
+fn build(
+    nodes: &mut Vec<Node>,
+    stars: &mut [Star],
+    offset: usize,
+    bounds: Aabb,
+    depth: usize,
+) -> usize {
+    let index = nodes.len();
+    nodes.push(Node {
+        bounds,
+        glow: Glow::from_stars(stars.iter()).expect("Octree nodes always hold stars"),
+        brightest_magnitude: stars
+            .iter()
+            .map(|star| star.absolute_magnitude)
+            .fold(f32::INFINITY, f32::min),
+        stars: offset..offset + stars.len(),
+        children: vec![],
+    });
+    if stars.len() <= LEAF_CAPACITY || depth >= MAX_DEPTH {
+        return index;
+    }
+
+    stars.sort_unstable_by_key(|star| bounds.octant(&star.position));
+    let mut children = vec![];
+    let mut start = 0;
+    for octant in 0..8 {
+        let end = start
+            + stars[start..]
+                .iter()
+                .take_while(|star| bounds.octant(&star.position) == octant)
+                .count();
+        if end > start {
+            children.push(build(
+                nodes,
+                &mut stars[start..end],
+                offset + start,
+                bounds.child(octant),
+                depth + 1,
+            ));
+        }
+        start = end;
+    }
+    nodes[index].children = children;
+    index
+}
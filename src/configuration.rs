@@ -1,5 +1,16 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use secrecy::{ExposeSecret, Secret};
 
+use crate::catalog::deep_sky::DeepSkyCatalog;
+use crate::render::extinction::DustGrid;
+use crate::render::instrument::InstrumentRegistry;
+use crate::retention::RetentionPolicy;
+use crate::storage::filesystem::FilesystemStorage;
+use crate::storage::s3::{S3Settings, S3Storage};
+use crate::storage::ArtifactStorage;
+
 /// Possible runtime environments
 pub enum Environment {
     Local,
@@ -33,12 +44,62 @@ impl TryFrom<String> for Environment {
 pub struct Settings {
     pub database: DatabaseSettings,
     pub application: ApplicationSettings,
+    #[serde(default)]
+    pub render: RenderSettings,
+    #[serde(default)]
+    pub storage: StorageSettings,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
 }
 
 #[derive(serde::Deserialize)]
 pub struct ApplicationSettings {
     pub host: String,
     pub port: u16,
+    /// Key of the HMAC signatures of download links
+    pub hmac_secret: Secret<String>,
+    /// Bearer token of the administration endpoints
+    pub admin_token: Secret<String>,
+}
+
+#[derive(serde::Deserialize, Default)]
+pub struct RenderSettings {
+    /// 3D dust map used by render jobs with `dust_map` extinction, relative to the working directory
+    pub dust_map: Option<PathBuf>,
+}
+
+impl RenderSettings {
+    pub fn dust_map(&self) -> std::io::Result<Option<DustGrid>> {
+        self.dust_map.as_deref().map(DustGrid::load).transpose()
+    }
+}
+
+/// Where render artifacts are kept
+#[derive(serde::Deserialize, Debug, Clone)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum StorageSettings {
+    /// Files below `root`, relative to the working directory
+    Filesystem {
+        root: PathBuf,
+    },
+    S3(S3Settings),
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        StorageSettings::Filesystem {
+            root: PathBuf::from("artifacts"),
+        }
+    }
+}
+
+impl StorageSettings {
+    pub fn storage(&self) -> Arc<dyn ArtifactStorage> {
+        match self {
+            StorageSettings::Filesystem { root } => Arc::new(FilesystemStorage::new(root.clone())),
+            StorageSettings::S3(settings) => Arc::new(S3Storage::new(settings.clone())),
+        }
+    }
 }
 
 #[derive(serde::Deserialize)]
@@ -91,5 +152,51 @@ pub fn get_configuration() -> Result<Settings, config::ConfigError> {
         config::File::from(configuration_directory.join(environment.as_str())).required(true),
     )?;
 
-    settings.try_into()
+    // Add in settings from environment variables, such as `APP_APPLICATION__HMAC_SECRET` for
+    // `application.hmac_secret`, which production secrets are read from
+    settings.merge(config::Environment::with_prefix("app").separator("__"))?;
+
+    let settings: Settings = settings.try_into()?;
+    settings
+        .retention
+        .validate()
+        .map_err(config::ConfigError::Message)?;
+    Ok(settings)
+}
+
+/// Parse every YAML file in `directory`, paired with its name without the extension
+///
+/// Other files, such as READMEs or editor backups, are skipped. Files are returned sorted by name.
+pub fn load_yaml_files<T: serde::de::DeserializeOwned>(
+    directory: &Path,
+) -> Result<Vec<(String, T)>, config::ConfigError> {
+    let mut files = vec![];
+    let entries =
+        std::fs::read_dir(directory).map_err(|e| config::ConfigError::Foreign(e.into()))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| config::ConfigError::Foreign(e.into()))?
+            .path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("yaml") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let mut settings = config::Config::default();
+        settings.merge(config::File::from(path.as_path()))?;
+        files.push((name.to_string(), settings.try_into()?));
+    }
+    files.sort_by(|a: &(String, T), b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+pub fn get_instruments() -> Result<InstrumentRegistry, config::ConfigError> {
+    let base_path = std::env::current_dir().expect("Failed to determine the current directory");
+    InstrumentRegistry::load(&base_path.join("configuration").join("instruments"))
+}
+
+pub fn get_deep_sky_catalog() -> Result<DeepSkyCatalog, config::ConfigError> {
+    let base_path = std::env::current_dir().expect("Failed to determine the current directory");
+    DeepSkyCatalog::load(&base_path.join("configuration").join("deep_sky"))
 }
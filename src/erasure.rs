@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::links::LinkSigner;
+use crate::retention::{artifact_shared, delete_files, image_keys, image_shared, PurgeError};
+use crate::storage::{survey_prefix, ArtifactStorage};
+
+/// Record of the erasure of everything tied to an email, signed by the server
+///
+/// The email itself is not kept, only its SHA-256, so whoever asked for the erasure can match the
+/// report to their address. Anyone holding the server's secret can check that the report is
+/// genuine and unaltered with [`ErasureReport::verify`].
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct ErasureReport {
+    pub id: Uuid,
+    pub erased_at: DateTime<Utc>,
+    /// Hex SHA-256 of the trimmed, lowercase email
+    pub email_sha256: String,
+    pub render_ids: Vec<Uuid>,
+    pub survey_ids: Vec<Uuid>,
+    /// Files deleted from storage, leaving those shared with other users' cached renders
+    pub files_deleted: i32,
+    /// Keys of the files, or prefixes of the surveys, that could not be deleted from storage
+    pub files_failed: Vec<String>,
+    /// Renders, surveys and API keys still tied to the email after the erasure, counted again
+    /// before committing it
+    pub remaining_references: i64,
+    /// HMAC of the other fields
+    pub signature: String,
+}
+
+impl ErasureReport {
+    /// Fields covered by the signature, in a fixed order
+    fn signed_content(&self) -> String {
+        let ids = |ids: &[Uuid]| {
+            ids.iter()
+                .map(Uuid::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        format!(
+            "erasure\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.id,
+            self.erased_at.to_rfc3339(),
+            self.email_sha256,
+            ids(&self.render_ids),
+            ids(&self.survey_ids),
+            self.files_deleted,
+            self.files_failed.join(","),
+            self.remaining_references
+        )
+    }
+
+    pub fn verify(&self, links: &LinkSigner) -> bool {
+        links.verify_report(&self.signed_content(), &self.signature)
+    }
+}
+
+/// Hex SHA-256 identifying an email in erasure reports
+pub fn email_digest(email: &str) -> String {
+    hex::encode(Sha256::digest(email.trim().to_lowercase().as_bytes()))
+}
+
+/// Delete every render and survey tied to `email`, with their stored files, and its API keys
+///
+/// Emails match regardless of case. Renders are deleted with their artifacts, except files that
+/// renders of other users reused from the cache still serve. Emails never reach the logs, as
+/// request bodies are left out of traces, so the database and storage hold every reference.
+/// Rows are erased in a single transaction, and files once it is committed, so that a failure
+/// leaves orphaned files rather than references to missing ones. Files that could not be deleted
+/// are listed in the report, which is then recorded in `erasures` in full, signature included.
+#[tracing::instrument(name = "Erasing the data of an email", skip_all)]
+pub async fn erase_email(
+    db_pool: &PgPool,
+    storage: &dyn ArtifactStorage,
+    links: &LinkSigner,
+    email: &str,
+    now: DateTime<Utc>,
+) -> Result<ErasureReport, PurgeError> {
+    let email = email.trim();
+    let mut transaction = db_pool.begin().await?;
+    let mut unused_keys = vec![];
+
+    let renders = sqlx::query!(
+        r#"
+        SELECT id, image_key, output, image_dimension_x, image_dimension_y
+        FROM renders
+        WHERE lower(email) = lower($1)
+        "#,
+        email
+    )
+    .fetch_all(&mut transaction)
+    .await?;
+    let render_ids: Vec<Uuid> = renders.iter().map(|render| render.id).collect();
+    let artifact_keys = sqlx::query!(
+        "DELETE FROM artifacts WHERE render_id = ANY($1) RETURNING storage_key",
+        &render_ids
+    )
+    .fetch_all(&mut transaction)
+    .await?;
+    sqlx::query!("DELETE FROM renders WHERE id = ANY($1)", &render_ids)
+        .execute(&mut transaction)
+        .await?;
+    for render in renders {
+        let Some(image_key) = render.image_key else {
+            continue;
+        };
+        if !image_shared(&mut transaction, &image_key).await? {
+            unused_keys.extend(image_keys(
+                &image_key,
+                &render.output,
+                render.image_dimension_x,
+                render.image_dimension_y,
+            ));
+        }
+    }
+    for artifact in artifact_keys {
+        if !artifact_shared(&mut transaction, &artifact.storage_key).await? {
+            unused_keys.push(artifact.storage_key);
+        }
+    }
+
+    let survey_ids: Vec<Uuid> = sqlx::query!(
+        "DELETE FROM surveys WHERE lower(email) = lower($1) RETURNING id",
+        email
+    )
+    .fetch_all(&mut transaction)
+    .await?
+    .into_iter()
+    .map(|survey| survey.id)
+    .collect();
+
+    sqlx::query!("DELETE FROM api_keys WHERE lower(owner) = lower($1)", email)
+        .execute(&mut transaction)
+        .await?;
+
+    let remaining_references = sqlx::query!(
+        r#"
+        SELECT
+            (SELECT count(*) FROM renders WHERE lower(email) = lower($1))
+            + (SELECT count(*) FROM surveys WHERE lower(email) = lower($1))
+            + (SELECT count(*) FROM api_keys WHERE lower(owner) = lower($1)) AS "remaining!"
+        "#,
+        email
+    )
+    .fetch_one(&mut transaction)
+    .await?
+    .remaining;
+    transaction.commit().await?;
+
+    let mut deletions = delete_files(storage, &unused_keys).await;
+    for survey_id in &survey_ids {
+        let prefix = survey_prefix(*survey_id);
+        match storage.delete_prefix(&prefix).await {
+            Ok(deleted) => deletions.deleted += deleted as i32,
+            Err(e) => {
+                tracing::warn!("Failed to delete {}: {}", prefix, e);
+                deletions.failed.push(prefix);
+            }
+        }
+    }
+
+    let mut report = ErasureReport {
+        id: Uuid::new_v4(),
+        erased_at: now,
+        email_sha256: email_digest(email),
+        render_ids,
+        survey_ids,
+        files_deleted: deletions.deleted,
+        files_failed: deletions.failed,
+        remaining_references,
+        signature: String::new(),
+    };
+    report.signature = links.sign_report(&report.signed_content());
+    sqlx::query!(
+        r#"
+        INSERT INTO erasures (
+            id,
+            erased_at,
+            email_sha256,
+            renders_deleted,
+            surveys_deleted,
+            files_deleted,
+            files_failed,
+            signature,
+            report
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        report.id,
+        report.erased_at,
+        report.email_sha256,
+        report.render_ids.len() as i32,
+        report.survey_ids.len() as i32,
+        report.files_deleted,
+        report.files_failed.len() as i32,
+        report.signature,
+        serde_json::to_string(&report).expect("Reports serialize to JSON"),
+    )
+    .execute(db_pool)
+    .await?;
+    tracing::info!(
+        "Erased {} renders and {} surveys",
+        report.render_ids.len(),
+        report.survey_ids.len()
+    );
+    Ok(report)
+}
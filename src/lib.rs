@@ -1,4 +1,14 @@
+pub mod api_keys;
+pub mod artifacts;
+pub mod cache;
+pub mod catalog;
 pub mod configuration;
+pub mod erasure;
+pub mod links;
+pub mod metrics;
+pub mod render;
+pub mod retention;
 pub mod routes;
 pub mod startup;
+pub mod storage;
 pub mod telemetry;
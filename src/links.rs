@@ -0,0 +1,165 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, Secret};
+use uuid::Uuid;
+
+use crate::render::derivatives::DerivativeKind;
+
+/// How long a download link stays valid after it is issued
+pub const LINK_LIFETIME_HOURS: i64 = 7 * 24;
+
+/// A download link to a render artifact, valid until `expires_at`
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+pub struct SignedLink {
+    /// Path and query of the link, relative to the API's address
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LinkError {
+    /// The link has no signature, or one that was not issued for it
+    InvalidSignature,
+    Expired,
+}
+
+impl std::fmt::Display for LinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkError::InvalidSignature => write!(f, "The link's signature is invalid."),
+            LinkError::Expired => write!(f, "The link has expired."),
+        }
+    }
+}
+
+/// Path of the status of a job
+pub fn status_path(render_id: Uuid) -> String {
+    format!("/renders/{}", render_id)
+}
+
+/// Path below which a survey's HiPS are served, one directory per filter
+pub fn survey_path(survey_id: Uuid) -> String {
+    format!("/surveys/{}", survey_id)
+}
+
+/// Path of the rendered image of a job, as signed in its download links
+pub fn image_path(render_id: Uuid) -> String {
+    format!("/renders/{}/image", render_id)
+}
+
+/// Path of the tile pyramid of a job, whose links sign every tile below it
+pub fn tiles_path(render_id: Uuid) -> String {
+    format!("/renders/{}/tiles", render_id)
+}
+
+/// Path of an artifact of a job, as signed in its download links
+pub fn artifact_path(render_id: Uuid, kind: DerivativeKind) -> String {
+    format!("/renders/{}/artifacts/{}", render_id, kind.as_str())
+}
+
+/// Issues and checks links that cannot be forged or extended without the application's secret
+///
+/// Links sign their path together with their expiry, so query parameters chosen by the client,
+/// such as the image format, are left out of the signature. Owners of render jobs prove their
+/// ownership with a token derived from the same secret, handed out when they submit the job.
+#[derive(Clone)]
+pub struct LinkSigner {
+    secret: Secret<String>,
+}
+
+impl LinkSigner {
+    pub fn new(secret: Secret<String>) -> Self {
+        Self { secret }
+    }
+
+    /// Link to the rendered image of a job, valid for [`LINK_LIFETIME_HOURS`] from `now`
+    pub fn image_link(&self, render_id: Uuid, now: DateTime<Utc>) -> SignedLink {
+        self.link(&image_path(render_id), now)
+    }
+
+    /// Link to `path`, valid for [`LINK_LIFETIME_HOURS`] from `now`
+    pub fn link(&self, path: &str, now: DateTime<Utc>) -> SignedLink {
+        // Links carry whole seconds
+        let expires = (now + Duration::hours(LINK_LIFETIME_HOURS)).timestamp();
+        SignedLink {
+            url: format!(
+                "{}?expires={}&signature={}",
+                path,
+                expires,
+                self.sign(path, expires)
+            ),
+            expires_at: Utc.timestamp_opt(expires, 0).unwrap(),
+        }
+    }
+
+    /// Signature of the link to `path` expiring at the Unix time `expires`
+    pub fn sign(&self, path: &str, expires: i64) -> String {
+        hex::encode(
+            self.mac(format!("link\n{}\n{}", path, expires).as_bytes())
+                .finalize()
+                .into_bytes(),
+        )
+    }
+
+    /// Check the signature and expiry of the link to `path`
+    pub fn verify(
+        &self,
+        path: &str,
+        expires: Option<i64>,
+        signature: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), LinkError> {
+        let (Some(expires), Some(signature)) = (expires, signature) else {
+            return Err(LinkError::InvalidSignature);
+        };
+        let signature = hex::decode(signature).map_err(|_| LinkError::InvalidSignature)?;
+        self.mac(format!("link\n{}\n{}", path, expires).as_bytes())
+            .verify_slice(&signature)
+            .map_err(|_| LinkError::InvalidSignature)?;
+        if expires <= now.timestamp() {
+            return Err(LinkError::Expired);
+        }
+        Ok(())
+    }
+
+    /// Token proving ownership of a render job
+    pub fn owner_token(&self, render_id: Uuid) -> String {
+        hex::encode(
+            self.mac(format!("owner\n{}", render_id).as_bytes())
+                .finalize()
+                .into_bytes(),
+        )
+    }
+
+    pub fn verify_owner_token(&self, render_id: Uuid, token: &str) -> bool {
+        hex::decode(token).map_or(false, |token| {
+            self.mac(format!("owner\n{}", render_id).as_bytes())
+                .verify_slice(&token)
+                .is_ok()
+        })
+    }
+
+    /// Signature of a report, such as an erasure report, proving that this server issued it
+    pub fn sign_report(&self, report: &str) -> String {
+        hex::encode(
+            self.mac(format!("report\n{}", report).as_bytes())
+                .finalize()
+                .into_bytes(),
+        )
+    }
+
+    pub fn verify_report(&self, report: &str, signature: &str) -> bool {
+        hex::decode(signature).map_or(false, |signature| {
+            self.mac(format!("report\n{}", report).as_bytes())
+                .verify_slice(&signature)
+                .is_ok()
+        })
+    }
+
+    fn mac(&self, message: &[u8]) -> Hmac<sha2::Sha256> {
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(self.secret.expose_secret().as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(message);
+        mac
+    }
+}
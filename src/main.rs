@@ -3,30 +3,81 @@ use std::net::TcpListener;
 use secrecy::ExposeSecret;
 use sqlx::PgPool;
 
-use space_telescope::configuration::get_configuration;
-use space_telescope::startup::run;
+use space_telescope::configuration::{get_configuration, get_deep_sky_catalog, get_instruments};
+use space_telescope::erasure::erase_email;
+use space_telescope::links::LinkSigner;
+use space_telescope::retention::schedule_purges;
+use space_telescope::startup::{run, RenderResources};
 use space_telescope::telemetry::{get_subscriber, init_subscriber};
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
+    // `space-telescope erase <email>` erases a user's data and prints the signed report
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let erased_email = match arguments.as_slice() {
+        [] => None,
+        [command, email] if command == "erase" => Some(email.clone()),
+        _ => {
+            eprintln!("Usage: space-telescope [erase <email>]");
+            std::process::exit(2);
+        }
+    };
+
     // Setup tracing
     let subscriber = get_subscriber("space-telescope", "info", std::io::stdout);
     init_subscriber(subscriber);
 
     let configuration = get_configuration().expect("Failed to read configuration");
+    let resources = RenderResources {
+        instruments: get_instruments().expect("Failed to read instrument profiles"),
+        dust_map: configuration
+            .render
+            .dust_map()
+            .expect("Failed to read dust map"),
+        deep_sky: get_deep_sky_catalog().expect("Failed to read deep-sky catalog"),
+    };
 
     let db_pool = PgPool::connect_lazy(
-        &configuration
+        configuration
             .database
             .connection_string_db()
             .expose_secret(),
     )
     .expect("Failed to create Postgres connection pool.");
 
+    let storage = configuration.storage.storage();
+    let links = LinkSigner::new(configuration.application.hmac_secret);
+
+    if let Some(email) = erased_email {
+        let report = erase_email(
+            &db_pool,
+            storage.as_ref(),
+            &links,
+            &email,
+            chrono::Utc::now(),
+        )
+        .await
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Reports serialize to JSON")
+        );
+        return Ok(());
+    }
+
     let address = format!(
         "{}:{}",
         configuration.application.host, configuration.application.port
     );
     let listener = TcpListener::bind(address)?;
-    run(listener, db_pool)?.await
+    schedule_purges(db_pool.clone(), storage.clone(), configuration.retention);
+    run(
+        listener,
+        db_pool,
+        resources,
+        storage,
+        links,
+        configuration.application.admin_token,
+    )?
+    .await
 }
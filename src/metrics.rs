@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters of the server's activity since it started, exposed at `/metrics`
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Render jobs completed on submission by reusing an identical finished render
+    pub render_cache_hits: AtomicU64,
+    /// Render jobs queued because no identical render was available
+    pub render_cache_misses: AtomicU64,
+}
+
+impl Metrics {
+    pub fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counters in the Prometheus text exposition format
+    pub fn exposition(&self) -> String {
+        [
+            (
+                "render_cache_hits_total",
+                "Render jobs completed by reusing an identical finished render.",
+                &self.render_cache_hits,
+            ),
+            (
+                "render_cache_misses_total",
+                "Render jobs queued without an identical finished render.",
+                &self.render_cache_misses,
+            ),
+        ]
+        .iter()
+        .map(|(name, help, counter)| {
+            format!(
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {}\n",
+                counter.load(Ordering::Relaxed)
+            )
+        })
+        .collect()
+    }
+}
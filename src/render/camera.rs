@@ -0,0 +1,248 @@
+use nalgebra as na;
+
+use crate::catalog::octree::{Cone, Octree, VisibleSources};
+use crate::catalog::Star;
+use crate::render::ground::{Atmosphere, MAX_REFRACTION};
+use crate::render::projection::Projection;
+use crate::render::relativity::Motion;
+use crate::render::sky::{CubemapFace, SkyFrame};
+
+/// Number of points sampled along each image edge when bounding the field of view
+const EDGE_SAMPLES: usize = 16;
+
+/// Viewpoint and optics of a framed render
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    pub position: na::Vector3<f32>,
+    pub right: na::Vector3<f32>,
+    pub up: na::Vector3<f32>,
+    pub forward: na::Vector3<f32>,
+    pub projection: Projection,
+    /// Field of view in degrees
+    pub fov: [f32; 2],
+    pub image_dimensions: [u32; 2],
+    /// Velocity of the camera, which aberrates the directions of stars
+    pub motion: Option<Motion>,
+    /// Atmosphere of a camera on the ground, which refracts stars and hides them below the horizon
+    pub atmosphere: Option<Atmosphere>,
+}
+
+impl Camera {
+    /// Camera at `position` looking towards a longitude and latitude (in degrees) of `frame`
+    ///
+    /// The image is oriented with the frame's pole upwards, so increasing longitude runs
+    /// from right to left as when looking at the sky from inside.
+    pub fn new(
+        position: na::Vector3<f32>,
+        frame: &SkyFrame,
+        longitude: f32,
+        latitude: f32,
+        projection: Projection,
+        fov: [f32; 2],
+        image_dimensions: [u32; 2],
+    ) -> Self {
+        let forward = frame.direction(longitude, latitude);
+        // Derivative of the viewing direction with respect to latitude, well defined at the poles
+        let up = frame.direction(longitude, latitude + 90.0);
+        let right = forward.cross(&up);
+        Self {
+            position,
+            right,
+            up,
+            forward,
+            projection,
+            fov,
+            image_dimensions,
+            motion: None,
+            atmosphere: None,
+        }
+    }
+
+    /// Camera at `position` seeing the whole sky as a 2:1 equirectangular panorama
+    ///
+    /// The panorama is centred on the frame's primary direction with its pole at the top edge.
+    pub fn panorama(
+        position: na::Vector3<f32>,
+        frame: &SkyFrame,
+        image_dimensions: [u32; 2],
+    ) -> Self {
+        Self::new(
+            position,
+            frame,
+            0.0,
+            0.0,
+            Projection::Equirectangular,
+            [360.0, 180.0],
+            image_dimensions,
+        )
+    }
+
+    /// Camera at `position` seeing one `face_size` pixels wide face of a skybox cubemap
+    pub fn cubemap_face(
+        position: na::Vector3<f32>,
+        frame: &SkyFrame,
+        face: CubemapFace,
+        face_size: u32,
+    ) -> Self {
+        let [right, up, forward] = face
+            .skybox_basis()
+            .map(|direction| frame.skybox_to_world(direction));
+        Self {
+            position,
+            right,
+            up,
+            forward,
+            projection: Projection::Gnomonic,
+            fov: [90.0, 90.0],
+            image_dimensions: [face_size, face_size],
+            motion: None,
+            atmosphere: None,
+        }
+    }
+
+    pub fn with_motion(self, motion: Motion) -> Self {
+        Self {
+            motion: Some(motion),
+            ..self
+        }
+    }
+
+    pub fn with_atmosphere(self, atmosphere: Atmosphere) -> Self {
+        Self {
+            atmosphere: Some(atmosphere),
+            ..self
+        }
+    }
+
+    /// Unit direction in which a world position appears, accounting for the camera's motion and
+    /// atmosphere
+    ///
+    /// Returns `None` for positions hidden below the horizon or at the camera itself.
+    pub fn apparent_direction(&self, position: &na::Vector3<f32>) -> Option<na::Vector3<f32>> {
+        // Normalized up front, as offsets within the solar system are tiny in parsecs
+        let direction = (position - self.position).try_normalize(0.0)?;
+        let direction = match &self.motion {
+            Some(motion) => motion.aberrate(&direction),
+            None => direction,
+        };
+        match &self.atmosphere {
+            Some(atmosphere) => atmosphere.refract(&direction),
+            None => Some(direction),
+        }
+    }
+
+    /// Express a world direction in the camera frame expected by [`Projection`]
+    pub fn to_camera_frame(&self, direction: &na::Vector3<f32>) -> na::Vector3<f32> {
+        na::Vector3::new(
+            direction.dot(&self.right),
+            direction.dot(&self.up),
+            direction.dot(&self.forward),
+        )
+    }
+
+    /// Express a camera frame direction in world coordinates
+    pub fn to_world_frame(&self, direction: &na::Vector3<f32>) -> na::Vector3<f32> {
+        direction.x * self.right + direction.y * self.up + direction.z * self.forward
+    }
+
+    /// Continuous pixel coordinates of a world position, with the origin at the image's top left corner
+    ///
+    /// Returns `None` for positions that the projection cannot represent or that are below the
+    /// horizon. Positions outside of the field of view are still returned.
+    pub fn pixel_coordinates(&self, position: &na::Vector3<f32>) -> Option<na::Point2<f32>> {
+        self.direction_pixel(&self.apparent_direction(position)?)
+    }
+
+    /// Continuous pixel coordinates of an apparent world direction
+    pub fn direction_pixel(&self, direction: &na::Vector3<f32>) -> Option<na::Point2<f32>> {
+        let point = self
+            .projection
+            .image_coordinates(&self.to_camera_frame(direction), self.fov)?;
+        Some(na::Point2::new(
+            (point.x + 1.0) / 2.0 * self.image_dimensions[0] as f32,
+            (1.0 - point.y) / 2.0 * self.image_dimensions[1] as f32,
+        ))
+    }
+
+    /// Apparent world direction through continuous pixel coordinates
+    pub fn pixel_direction(&self, pixel: &na::Point2<f32>) -> Option<na::Vector3<f32>> {
+        let point = na::Point2::new(
+            2.0 * pixel.x / self.image_dimensions[0] as f32 - 1.0,
+            1.0 - 2.0 * pixel.y / self.image_dimensions[1] as f32,
+        );
+        let direction = self.projection.image_direction(&point, self.fov)?;
+        Some(self.to_world_frame(&direction))
+    }
+
+    /// Smallest cone around the line of sight that contains the whole field of view
+    ///
+    /// For a moving camera, the cone contains the positions whose aberrated directions land in
+    /// the field of view. Under an atmosphere, it widens to cover stars refracted into view.
+    pub fn view_cone(&self) -> Cone {
+        let rest_frame = self.motion.map(|motion| motion.inverse());
+        let to_rest_frame = |direction: na::Vector3<f32>| match &rest_frame {
+            Some(rest_frame) => rest_frame.aberrate(&direction),
+            None => direction,
+        };
+        let axis = to_rest_frame(self.forward);
+        let edge = (0..=EDGE_SAMPLES).map(|i| 2.0 * i as f32 / EDGE_SAMPLES as f32 - 1.0);
+        let half_angle = edge
+            .flat_map(|t| {
+                [
+                    na::Point2::new(t, -1.0),
+                    na::Point2::new(t, 1.0),
+                    na::Point2::new(-1.0, t),
+                    na::Point2::new(1.0, t),
+                ]
+            })
+            .filter_map(|point| self.projection.image_direction(&point, self.fov))
+            .map(|direction| to_rest_frame(self.to_world_frame(&direction)))
+            .map(|direction| direction.dot(&axis).clamp(-1.0, 1.0).acos())
+            .fold(0.0, f32::max);
+        let refraction = self.atmosphere.map_or(0.0, |_| MAX_REFRACTION.to_radians());
+        Cone {
+            apex: self.position,
+            axis,
+            // Pad by a sampling interval's worth of angle to cover the gaps between samples
+            half_angle: half_angle * (1.0 + 2.0 / EDGE_SAMPLES as f32) + refraction,
+        }
+    }
+
+    /// Angle subtended by a pixel at the centre of the image, in radians
+    pub fn angular_resolution(&self) -> f32 {
+        (self.fov[0] / self.image_dimensions[0] as f32)
+            .min(self.fov[1] / self.image_dimensions[1] as f32)
+            .to_radians()
+    }
+
+    /// Stars of `catalog` that land inside the image and are at least as bright as `limiting_magnitude`
+    pub fn visible_stars<'a>(&self, catalog: &'a Octree, limiting_magnitude: f32) -> Vec<&'a Star> {
+        let mut stars = catalog.query(&self.view_cone(), limiting_magnitude);
+        stars.retain(|star| self.is_in_image(&star.position));
+        stars
+    }
+
+    /// Like [`Camera::visible_stars`], with the light of fainter stars kept as diffuse glows
+    pub fn visible_sources<'a>(
+        &self,
+        catalog: &'a Octree,
+        limiting_magnitude: f32,
+    ) -> VisibleSources<'a> {
+        let mut sources = catalog.query_with_glow(
+            &self.view_cone(),
+            limiting_magnitude,
+            self.angular_resolution(),
+        );
+        sources
+            .stars
+            .retain(|star| self.is_in_image(&star.position));
+        sources
+    }
+
+    fn is_in_image(&self, position: &na::Vector3<f32>) -> bool {
+        self.pixel_coordinates(position).map_or(false, |pixel| {
+            (0.0..=self.image_dimensions[0] as f32).contains(&pixel.x)
+                && (0.0..=self.image_dimensions[1] as f32).contains(&pixel.y)
+        })
+    }
+}
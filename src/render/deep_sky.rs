@@ -0,0 +1,151 @@
+use nalgebra as na;
+
+use crate::catalog::deep_sky::{DeepSkyCatalog, DeepSkyObject};
+use crate::render::camera::Camera;
+use crate::render::extinction::Extinction;
+use crate::render::filter::AstronomicalFilter;
+use crate::render::image::Image;
+use crate::render::optics::PsfKernel;
+use crate::render::{camera_magnitudes, flux};
+
+/// Apparent radius in pixels below which objects are drawn as blurred points
+const MIN_RESOLVED_RADIUS: f32 = 2.0;
+
+/// Flux of `object` through `filter`, before extinction, when it appears `angular_radius`
+/// radians wide
+///
+/// Emission lines inside the passband add their equivalent width's worth of continuum, spread
+/// over the filter's bandwidth, so narrowband filters isolate the regions bright in their line.
+pub fn filter_flux(
+    object: &DeepSkyObject,
+    filter: &AstronomicalFilter,
+    angular_radius: f64,
+) -> f64 {
+    let lines: f32 = object
+        .emission_lines
+        .iter()
+        .filter(|line| filter.transmits(line.wavelength))
+        .map(|line| line.equivalent_width)
+        .sum();
+    object.continuum_flux(angular_radius) * (1.0 + (lines / filter.bandwidth()) as f64)
+}
+
+/// Add the galaxies and nebulae of `catalog` seen through `camera` to `image`
+///
+/// Objects smaller than a few pixels are drawn as glows blurred by `kernel`, larger ones by
+/// sampling their profile, projected according to their orientation. Like stars, they are
+/// dimmed by `extinction` and the camera's atmosphere and motion, which should all be for light
+/// passing through `filter`.
+pub fn render_deep_sky(
+    image: &mut Image,
+    camera: &Camera,
+    catalog: &DeepSkyCatalog,
+    filter: &AstronomicalFilter,
+    kernel: &PsfKernel,
+    extinction: Option<&Extinction>,
+) {
+    let wavelength = filter.wavelength();
+    for object in &catalog.objects {
+        let offset = object.position - camera.position;
+        let distance = offset.norm();
+        if distance <= object.radius {
+            continue;
+        }
+        let Some(center) = camera.pixel_coordinates(&object.position) else {
+            continue;
+        };
+        let Some(direction) = camera.apparent_direction(&object.position) else {
+            continue;
+        };
+        let angular_radius = (object.radius / distance).atan();
+        let dimming = extinction.map_or(0.0, |extinction| {
+            extinction.magnitudes(&camera.position, &object.position)
+        }) + camera_magnitudes(camera, &object.position, wavelength);
+        let total_flux = filter_flux(object, filter, angular_radius as f64) * flux(dimming) as f64;
+
+        let pixel_radius = angular_radius / camera.angular_resolution();
+        if pixel_radius < MIN_RESOLVED_RADIUS {
+            let glow_kernel = PsfKernel::Gaussian {
+                sigma: (pixel_radius.powi(2) + kernel.sigma().powi(2)).sqrt(),
+            };
+            image.draw(&glow_kernel, &center, total_flux as f32);
+            continue;
+        }
+
+        let axis_ratio = object.axis_ratio(&offset);
+        // The major axis is the line of nodes where the object's plane crosses the sky
+        let major_axis = object
+            .normal
+            .cross(&direction)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(|| direction.cross(&camera.up).normalize());
+        let minor_axis = direction.cross(&major_axis);
+        let projection = Projection {
+            center: direction.cast(),
+            major_axis: major_axis.cast(),
+            minor_axis: minor_axis.cast(),
+            angular_radius: angular_radius as f64,
+            axis_ratio: axis_ratio as f64,
+        };
+        draw_profile(
+            image,
+            camera,
+            object,
+            &projection,
+            &center,
+            pixel_radius,
+            total_flux,
+        );
+    }
+}
+
+/// Apparent geometry of an extended object
+struct Projection {
+    /// Apparent direction of the centre
+    center: na::Vector3<f64>,
+    major_axis: na::Vector3<f64>,
+    minor_axis: na::Vector3<f64>,
+    angular_radius: f64,
+    axis_ratio: f64,
+}
+
+impl Projection {
+    /// Radius, in units of the object's radius, of the ellipse through apparent `direction`
+    fn elliptical_radius(&self, direction: &na::Vector3<f64>) -> Option<f64> {
+        let direction = direction.normalize();
+        if direction.dot(&self.center) <= 0.0 {
+            return None;
+        }
+        let x = direction.dot(&self.major_axis) / self.angular_radius;
+        let y = direction.dot(&self.minor_axis) / (self.angular_radius * self.axis_ratio);
+        Some((x * x + y * y).sqrt())
+    }
+}
+
+/// Spread `total_flux` over the profile of `object` around pixel `center`
+///
+/// The flux is normalized over the profile out to its extent, so objects cut by the edges of the
+/// image lose the light of their hidden part.
+fn draw_profile(
+    image: &mut Image,
+    camera: &Camera,
+    object: &DeepSkyObject,
+    projection: &Projection,
+    center: &na::Point2<f32>,
+    pixel_radius: f32,
+    total_flux: f64,
+) {
+    let profile = &object.profile;
+    let pixel_solid_angle = (camera.angular_resolution() as f64).powi(2);
+    let scale = total_flux * pixel_solid_angle
+        / (profile.truncated_intensity()
+            * projection.angular_radius.powi(2)
+            * projection.axis_ratio);
+    let extent = profile.extent() as f32 * pixel_radius;
+    image.draw_extended(camera, center, extent, scale, |direction| {
+        projection
+            .elliptical_radius(direction)
+            .filter(|&radius| radius <= profile.extent())
+            .map_or(0.0, |radius| profile.intensity(radius))
+    });
+}
@@ -0,0 +1,192 @@
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ColorType, DynamicImage, GrayImage, ImageBuffer, ImageEncoder, Luma};
+
+use crate::render::image::Image;
+
+/// Width and height of thumbnails, which are cropped to a square
+pub const THUMBNAIL_SIZE: u32 = 256;
+/// Longest side of previews, which are never enlarged
+pub const PREVIEW_SIZE: u32 = 1600;
+const JPEG_QUALITY: u8 = 85;
+/// Flux, relative to the brightest pixel, at which the display stretch turns logarithmic
+const STRETCH_SOFTENING: f32 = 1e-3;
+
+/// Images derived from a render for galleries and downloads
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivativeKind {
+    /// Square JPEG of [`THUMBNAIL_SIZE`] pixels
+    Thumbnail,
+    /// JPEG of at most [`PREVIEW_SIZE`] pixels
+    PreviewJpeg,
+    /// Lossless WebP of at most [`PREVIEW_SIZE`] pixels
+    PreviewWebp,
+    /// Full resolution 16-bit grayscale PNG, linear in flux
+    Png16,
+}
+
+impl DerivativeKind {
+    pub const ALL: [DerivativeKind; 4] = [
+        DerivativeKind::Thumbnail,
+        DerivativeKind::PreviewJpeg,
+        DerivativeKind::PreviewWebp,
+        DerivativeKind::Png16,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DerivativeKind::Thumbnail => "thumbnail",
+            DerivativeKind::PreviewJpeg => "preview_jpeg",
+            DerivativeKind::PreviewWebp => "preview_webp",
+            DerivativeKind::Png16 => "png16",
+        }
+    }
+
+    /// Name of the artifact among those of its render
+    ///
+    /// The 16-bit PNG is the render's PNG image, served by the image endpoint.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            DerivativeKind::Thumbnail => "thumbnail.jpg",
+            DerivativeKind::PreviewJpeg => "preview.jpg",
+            DerivativeKind::PreviewWebp => "preview.webp",
+            DerivativeKind::Png16 => "image.png",
+        }
+    }
+}
+
+impl TryFrom<&str> for DerivativeKind {
+    type Error = String;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        DerivativeKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("{} is not a kind of derivative.", s))
+    }
+}
+
+/// Encoded derivative of a render
+#[derive(Debug, Clone)]
+pub struct Derivative {
+    pub kind: DerivativeKind,
+    pub width: u32,
+    pub height: u32,
+    pub body: Vec<u8>,
+}
+
+/// Encode every [`DerivativeKind`] of `image`
+///
+/// The 16-bit PNG keeps the flux linear from the darkest to the brightest pixel, while the 8-bit
+/// derivatives are stretched with an inverse hyperbolic sine so that faint sources remain visible
+/// next to bright stars.
+pub fn derivatives(image: &Image) -> Result<Vec<Derivative>, String> {
+    let display = DynamicImage::ImageLuma8(display_stretch(image));
+    let thumbnail = square_crop(&display).thumbnail_exact(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    let preview = if image.width.max(image.height) > PREVIEW_SIZE {
+        display.thumbnail(PREVIEW_SIZE, PREVIEW_SIZE)
+    } else {
+        display
+    };
+
+    let mut derivatives = vec![
+        jpeg(DerivativeKind::Thumbnail, &thumbnail)?,
+        jpeg(DerivativeKind::PreviewJpeg, &preview)?,
+    ];
+    let mut body = vec![];
+    WebPEncoder::new_lossless(&mut body)
+        .encode(
+            preview.to_rgb8().as_raw(),
+            preview.width(),
+            preview.height(),
+            ColorType::Rgb8,
+        )
+        .map_err(|e| e.to_string())?;
+    derivatives.push(Derivative {
+        kind: DerivativeKind::PreviewWebp,
+        width: preview.width(),
+        height: preview.height(),
+        body,
+    });
+
+    let linear = linear(image);
+    let samples: Vec<u8> = linear
+        .as_raw()
+        .iter()
+        .flat_map(|sample| sample.to_ne_bytes())
+        .collect();
+    let mut body = vec![];
+    PngEncoder::new(&mut body)
+        .write_image(&samples, image.width, image.height, ColorType::L16)
+        .map_err(|e| e.to_string())?;
+    derivatives.push(Derivative {
+        kind: DerivativeKind::Png16,
+        width: image.width,
+        height: image.height,
+        body,
+    });
+    Ok(derivatives)
+}
+
+/// Darkest pixel and range of the flux of `image`, one for a blank image
+fn flux_range(image: &Image) -> (f32, f32) {
+    let (min, max) = image
+        .pixels
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &pixel| {
+            (min.min(pixel), max.max(pixel))
+        });
+    if max > min {
+        (min, max - min)
+    } else {
+        (0.0, 1.0)
+    }
+}
+
+fn linear(image: &Image) -> ImageBuffer<Luma<u16>, Vec<u16>> {
+    let (min, range) = flux_range(image);
+    ImageBuffer::from_fn(image.width, image.height, |x, y| {
+        Luma([((image.get(x, y) - min) / range * u16::MAX as f32).round() as u16])
+    })
+}
+
+/// 8-bit version of `image` for display, stretched with an inverse hyperbolic sine
+pub fn display_stretch(image: &Image) -> GrayImage {
+    let (min, range) = flux_range(image);
+    let scale = (1.0 / STRETCH_SOFTENING).asinh();
+    GrayImage::from_fn(image.width, image.height, |x, y| {
+        let flux = (image.get(x, y) - min) / range;
+        Luma([((flux / STRETCH_SOFTENING).asinh() / scale * u8::MAX as f32).round() as u8])
+    })
+}
+
+/// Largest centered square of `image`
+fn square_crop(image: &DynamicImage) -> DynamicImage {
+    let side = image.width().min(image.height());
+    image.crop_imm(
+        (image.width() - side) / 2,
+        (image.height() - side) / 2,
+        side,
+        side,
+    )
+}
+
+fn jpeg(kind: DerivativeKind, image: &DynamicImage) -> Result<Derivative, String> {
+    Ok(Derivative {
+        kind,
+        width: image.width(),
+        height: image.height(),
+        body: encode_jpeg(image)?,
+    })
+}
+
+pub(crate) fn encode_jpeg(image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let mut body = Cursor::new(vec![]);
+    JpegEncoder::new_with_quality(&mut body, JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| e.to_string())?;
+    Ok(body.into_inner())
+}
@@ -0,0 +1,138 @@
+use rand::Rng;
+use rand_distr::{Distribution, Poisson, StandardNormal};
+
+use crate::render::image::Image;
+
+/// Characteristics of an image sensor
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Detector {
+    /// Fraction of incident photons converted into photoelectrons
+    pub quantum_efficiency: f32,
+    /// RMS noise added by each readout, in electrons
+    pub read_noise: f32,
+    /// Thermal electrons accumulated per pixel per second
+    pub dark_current: f32,
+    /// Electrons per analog-to-digital unit
+    pub gain: f32,
+    /// Electrons a pixel can hold before saturating
+    pub full_well: f32,
+    /// Resolution of the analog-to-digital converter
+    pub bit_depth: u8,
+}
+
+impl Detector {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.quantum_efficiency.is_finite()
+            || self.quantum_efficiency <= 0.0
+            || self.quantum_efficiency > 1.0
+        {
+            return Err("Detector quantum_efficiency must be between 0 and 1.".into());
+        }
+        // Values overflowing an f32 are deserialized as infinities
+        if !self.read_noise.is_finite() || self.read_noise < 0.0 {
+            return Err("Detector read_noise must be finite and not negative.".into());
+        }
+        if !self.dark_current.is_finite() || self.dark_current < 0.0 {
+            return Err("Detector dark_current must be finite and not negative.".into());
+        }
+        if !self.gain.is_finite() || self.gain <= 0.0 {
+            return Err("Detector gain must be finite and positive.".into());
+        }
+        if !self.full_well.is_finite() || self.full_well <= 0.0 {
+            return Err("Detector full_well must be finite and positive.".into());
+        }
+        if !(1..=32).contains(&self.bit_depth) {
+            return Err("Detector bit_depth must be between 1 and 32.".into());
+        }
+        Ok(())
+    }
+}
+
+/// Exposure settings of a render job
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Exposure {
+    /// Integration time in seconds
+    pub time: f32,
+    /// Surface brightness of the sky background, in magnitudes per square arcsecond
+    pub sky_brightness: Option<f32>,
+    /// Seed of the noise, derived from the render's parameters when omitted
+    pub seed: Option<u64>,
+}
+
+impl Exposure {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.time.is_finite() || self.time <= 0.0 {
+            return Err("exposure time must be finite and positive.".into());
+        }
+        if let Some(sky_brightness) = self.sky_brightness {
+            if !sky_brightness.is_finite() {
+                return Err("exposure sky_brightness must be a finite number.".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Digitized image read out of a detector, in analog-to-digital units
+#[derive(Debug, Clone, PartialEq)]
+pub struct Readout {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub pixels: Vec<u32>,
+}
+
+impl Readout {
+    pub fn get(&self, x: u32, y: u32) -> u32 {
+        self.pixels[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Largest value the analog-to-digital converter can output
+    pub fn max_value(&self) -> u32 {
+        max_value(self.bit_depth)
+    }
+}
+
+fn max_value(bit_depth: u8) -> u32 {
+    (((1u64) << bit_depth) - 1) as u32
+}
+
+impl Detector {
+    /// Read out an exposure of `time` seconds of `photons`
+    ///
+    /// `photons` holds the rate at which each pixel receives photons, per second. Photon and dark
+    /// current shot noise are drawn from Poisson distributions and read noise from a normal
+    /// distribution, before the electrons are clipped to the full well and digitized.
+    pub fn read_out<R: Rng>(&self, photons: &Image, time: f32, rng: &mut R) -> Readout {
+        let max_value = max_value(self.bit_depth) as f64;
+        let pixels = photons
+            .pixels
+            .iter()
+            .map(|&rate| {
+                let expected =
+                    (self.quantum_efficiency * rate + self.dark_current) as f64 * time as f64;
+                let electrons = poisson(expected, rng).min(self.full_well as f64);
+                let read_noise: f64 = StandardNormal.sample(rng);
+                let signal = (electrons + self.read_noise as f64 * read_noise) / self.gain as f64;
+                signal.round().clamp(0.0, max_value) as u32
+            })
+            .collect();
+        Readout {
+            width: photons.width,
+            height: photons.height,
+            bit_depth: self.bit_depth,
+            pixels,
+        }
+    }
+}
+
+/// Draw from a Poisson distribution, which `rand_distr` rejects for non-positive means
+fn poisson<R: Rng>(mean: f64, rng: &mut R) -> f64 {
+    if mean > 0.0 {
+        Poisson::new(mean)
+            .map(|poisson| poisson.sample(rng))
+            .unwrap_or(mean)
+    } else {
+        0.0
+    }
+}
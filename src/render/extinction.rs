@@ -0,0 +1,313 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use nalgebra as na;
+
+/// Ratio of total to selective extinction of the diffuse interstellar medium
+const R_V: f32 = 3.1;
+/// Most samples taken along a single line of sight
+const MAX_STEPS: usize = 1024;
+/// First bytes of a dust map file
+const DUST_MAP_MAGIC: &[u8; 4] = b"DUST";
+
+/// Sources of interstellar extinction a render job can select
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtinctionModel {
+    /// Smooth model of the Milky Way's dust, see [`DustDisk`]
+    ExponentialDisk,
+    /// The dust map configured on the server
+    DustMap,
+}
+
+impl ExtinctionModel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExtinctionModel::ExponentialDisk => "exponential_disk",
+            ExtinctionModel::DustMap => "dust_map",
+        }
+    }
+}
+
+/// Distribution of interstellar dust
+///
+/// Densities are given as V band extinction per parsec, in magnitudes.
+pub trait DustModel: Send + Sync {
+    fn density(&self, position: &na::Vector3<f32>) -> f32;
+
+    /// Length over which the density varies appreciably, in parsecs
+    fn resolution(&self) -> f32;
+
+    /// V band extinction accumulated between two points, in magnitudes
+    fn column(&self, from: &na::Vector3<f32>, to: &na::Vector3<f32>) -> f32 {
+        let distance = (to - from).norm();
+        let steps = ((distance / self.resolution()).ceil() as usize).clamp(1, MAX_STEPS);
+        let step = (to - from) / steps as f32;
+        (0..steps)
+            .map(|i| self.density(&(from + (i as f32 + 0.5) * step)))
+            .sum::<f32>()
+            * distance
+            / steps as f32
+    }
+}
+
+/// Dust concentrated in the galactic plane, thinning exponentially with height and radius
+///
+/// Positions are heliocentric galactic coordinates, with x towards the galactic centre and z
+/// towards the north galactic pole.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DustDisk {
+    /// Density in the plane at the Sun's distance from the galactic centre
+    pub local_density: f32,
+    /// Distance from the Sun to the galactic centre in parsecs
+    pub galactocentric_distance: f32,
+    pub scale_height: f32,
+    pub scale_length: f32,
+}
+
+impl Default for DustDisk {
+    /// About a magnitude of extinction per kiloparsec in the solar neighbourhood
+    fn default() -> Self {
+        Self {
+            local_density: 1e-3,
+            galactocentric_distance: 8200.0,
+            scale_height: 125.0,
+            scale_length: 3000.0,
+        }
+    }
+}
+
+impl DustModel for DustDisk {
+    fn density(&self, position: &na::Vector3<f32>) -> f32 {
+        let radius = (position.x - self.galactocentric_distance).hypot(position.y);
+        self.local_density
+            * (-(radius - self.galactocentric_distance) / self.scale_length).exp()
+            * (-position.z.abs() / self.scale_height).exp()
+    }
+
+    fn resolution(&self) -> f32 {
+        self.scale_height / 4.0
+    }
+}
+
+/// Dust densities sampled on a regular 3D grid, interpolated trilinearly between voxel centres
+///
+/// Space outside of the grid is free of dust.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DustGrid {
+    /// Corner of the first voxel
+    pub origin: na::Vector3<f32>,
+    /// Edge length of the voxels in parsecs
+    pub voxel_size: f32,
+    pub dimensions: [usize; 3],
+    /// Densities with x varying fastest, then y, then z
+    pub densities: Vec<f32>,
+}
+
+impl DustGrid {
+    pub fn new(
+        origin: na::Vector3<f32>,
+        voxel_size: f32,
+        dimensions: [usize; 3],
+        densities: Vec<f32>,
+    ) -> Result<Self, String> {
+        if voxel_size.is_nan() || voxel_size <= 0.0 {
+            return Err("Dust map voxel size must be positive.".into());
+        }
+        if dimensions.iter().product::<usize>() != densities.len() {
+            return Err(format!(
+                "Dust map of {:?} voxels has {} densities.",
+                dimensions,
+                densities.len()
+            ));
+        }
+        if densities
+            .iter()
+            .any(|density| !density.is_finite() || *density < 0.0)
+        {
+            return Err("Dust map densities must be finite and not negative.".into());
+        }
+        Ok(Self {
+            origin,
+            voxel_size,
+            dimensions,
+            densities,
+        })
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        Self::read(&mut std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+
+    /// Read a dust map file
+    ///
+    /// Files hold, in little endian order: the bytes `DUST`, the dimensions as three `u32`, the
+    /// origin as three `f32`, the voxel size as an `f32`, then every density as an `f32`.
+    pub fn read<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let invalid =
+            |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != DUST_MAP_MAGIC {
+            return Err(invalid("Not a dust map file.".into()));
+        }
+        let mut dimensions = [0; 3];
+        for dimension in &mut dimensions {
+            *dimension = read_u32(reader)? as usize;
+        }
+        let origin = na::Vector3::new(read_f32(reader)?, read_f32(reader)?, read_f32(reader)?);
+        let voxel_size = read_f32(reader)?;
+        let size = dimensions
+            .iter()
+            .try_fold(4usize, |size, dimension| size.checked_mul(*dimension))
+            .ok_or_else(|| invalid(format!("Dust map of {:?} voxels is too large.", dimensions)))?;
+        // Buffer only the bytes that are there, however large the header claims the grid to be
+        let mut bytes = vec![];
+        reader.take(size as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != size {
+            return Err(invalid(format!(
+                "Dust map of {:?} voxels is truncated.",
+                dimensions
+            )));
+        }
+        let densities = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().expect("Chunks are four bytes")))
+            .collect();
+        Self::new(origin, voxel_size, dimensions, densities).map_err(invalid)
+    }
+
+    /// Write the grid in the format expected by [`DustGrid::read`]
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(DUST_MAP_MAGIC)?;
+        for dimension in self.dimensions {
+            writer.write_all(&(dimension as u32).to_le_bytes())?;
+        }
+        for value in self.origin.iter().chain([&self.voxel_size]) {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        for density in &self.densities {
+            writer.write_all(&density.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn voxel(&self, index: [usize; 3]) -> f32 {
+        let [nx, ny, _] = self.dimensions;
+        self.densities[(index[2] * ny + index[1]) * nx + index[0]]
+    }
+}
+
+impl DustModel for DustGrid {
+    fn density(&self, position: &na::Vector3<f32>) -> f32 {
+        // Continuous index relative to the voxel centres
+        let grid_position = (position - self.origin) / self.voxel_size;
+        let mut lower = [0; 3];
+        let mut upper = [0; 3];
+        let mut fraction = [0.0; 3];
+        for axis in 0..3 {
+            let size = self.dimensions[axis];
+            let coordinate = grid_position[axis];
+            if size == 0 || !(0.0..=size as f32).contains(&coordinate) {
+                return 0.0;
+            }
+            let coordinate = (coordinate - 0.5).clamp(0.0, (size - 1) as f32);
+            lower[axis] = coordinate.floor() as usize;
+            upper[axis] = (lower[axis] + 1).min(size - 1);
+            fraction[axis] = coordinate - lower[axis] as f32;
+        }
+
+        let mut density = 0.0;
+        for corner in 0..8 {
+            let mut index = [0; 3];
+            let mut weight = 1.0;
+            for axis in 0..3 {
+                if corner >> axis & 1 == 1 {
+                    index[axis] = upper[axis];
+                    weight *= fraction[axis];
+                } else {
+                    index[axis] = lower[axis];
+                    weight *= 1.0 - fraction[axis];
+                }
+            }
+            density += weight * self.voxel(index);
+        }
+        density
+    }
+
+    fn resolution(&self) -> f32 {
+        self.voxel_size / 2.0
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f32<R: Read>(reader: &mut R) -> std::io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+/// Extinction at `wavelength` micrometres relative to the V band
+///
+/// The Cardelli, Clayton & Mathis (1989) law, clamped to its range of 0.125 to 3.3 micrometres.
+/// Blue light is absorbed more strongly than red light, which reddens extincted stars.
+pub fn extinction_ratio(wavelength: f32) -> f32 {
+    let x = (1.0 / wavelength).clamp(0.3, 8.0);
+    let (a, b) = if x < 1.1 {
+        (0.574 * x.powf(1.61), -0.527 * x.powf(1.61))
+    } else if x < 3.3 {
+        let y = x - 1.82;
+        (
+            1.0 + y
+                * (0.17699
+                    + y * (-0.50447
+                        + y * (-0.02427
+                            + y * (0.72085 + y * (0.01979 + y * (-0.77530 + y * 0.32999)))))),
+            y * (1.41338
+                + y * (2.28305
+                    + y * (1.07233
+                        + y * (-5.38434 + y * (-0.62251 + y * (5.30260 - y * 2.09002)))))),
+        )
+    } else {
+        let (fa, fb) = if x > 5.9 {
+            let z = x - 5.9;
+            (
+                -0.04473 * z.powi(2) - 0.009779 * z.powi(3),
+                0.2130 * z.powi(2) + 0.1207 * z.powi(3),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        (
+            1.752 - 0.316 * x - 0.104 / ((x - 4.67).powi(2) + 0.341) + fa,
+            -3.090 + 1.825 * x + 1.206 / ((x - 4.62).powi(2) + 0.263) + fb,
+        )
+    };
+    a + b / R_V
+}
+
+/// Extinction through a dust model at a single wavelength
+pub struct Extinction<'a> {
+    dust: &'a dyn DustModel,
+    ratio: f32,
+}
+
+impl<'a> Extinction<'a> {
+    /// Extinction of light of `wavelength` micrometres by `dust`
+    pub fn new(dust: &'a dyn DustModel, wavelength: f32) -> Self {
+        Self {
+            dust,
+            ratio: extinction_ratio(wavelength),
+        }
+    }
+
+    /// Dimming of a source at `source` seen from `observer`, in magnitudes
+    pub fn magnitudes(&self, observer: &na::Vector3<f32>, source: &na::Vector3<f32>) -> f32 {
+        self.ratio * self.dust.column(observer, source)
+    }
+}
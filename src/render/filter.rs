@@ -0,0 +1,95 @@
+/// Full width of narrowband filters in micrometres, narrow enough to split H-alpha from [N II]
+pub const NARROWBAND_WIDTH: f32 = 0.003;
+
+// TODO fill this out
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum BroadBandFilter {
+    SDSS_U,
+    SDSS_G,
+    SDSS_R,
+}
+
+impl BroadBandFilter {
+    /// Effective wavelength of the filter's passband in micrometres
+    pub fn effective_wavelength(&self) -> f32 {
+        match self {
+            BroadBandFilter::SDSS_U => 0.3551,
+            BroadBandFilter::SDSS_G => 0.4686,
+            BroadBandFilter::SDSS_R => 0.6166,
+        }
+    }
+
+    /// Full width at half maximum of the filter's passband in micrometres
+    pub fn bandwidth(&self) -> f32 {
+        match self {
+            BroadBandFilter::SDSS_U => 0.0599,
+            BroadBandFilter::SDSS_G => 0.1379,
+            BroadBandFilter::SDSS_R => 0.1382,
+        }
+    }
+}
+
+impl std::fmt::Display for BroadBandFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Passband of a single rendered image
+///
+/// Narrowband filters are given by their central wavelength in micrometres.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum AstronomicalFilter {
+    NarrowBand(f32),
+    BroadBand(BroadBandFilter),
+}
+
+impl AstronomicalFilter {
+    /// Representative wavelength of the filter in micrometres
+    pub fn wavelength(&self) -> f32 {
+        match self {
+            AstronomicalFilter::NarrowBand(wavelength) => *wavelength,
+            AstronomicalFilter::BroadBand(filter) => filter.effective_wavelength(),
+        }
+    }
+
+    /// Width of the filter's passband in micrometres
+    pub fn bandwidth(&self) -> f32 {
+        match self {
+            AstronomicalFilter::NarrowBand(_) => NARROWBAND_WIDTH,
+            AstronomicalFilter::BroadBand(filter) => filter.bandwidth(),
+        }
+    }
+
+    /// Whether light of `wavelength` micrometres falls inside the passband, taken as a top hat
+    pub fn transmits(&self, wavelength: f32) -> bool {
+        (wavelength - self.wavelength()).abs() <= self.bandwidth() / 2.0
+    }
+}
+
+/// Check that the narrowband filters among `filters` have positive wavelengths
+pub fn validate_filters(filters: &[AstronomicalFilter]) -> Result<(), String> {
+    if filters.iter().any(|filter| {
+        matches!(filter, AstronomicalFilter::NarrowBand(wavelength)
+            if !wavelength.is_finite() || *wavelength <= 0.0)
+    }) {
+        return Err("Narrowband wavelengths must be positive.".into());
+    }
+    Ok(())
+}
+
+/// Wavelengths of the narrowband filters and names of the broadband filters among `filters`, as
+/// stored in the database
+pub fn split_filters(filters: &[AstronomicalFilter]) -> (Vec<f32>, Vec<String>) {
+    let mut narrowband = vec![];
+    let mut broadband = vec![];
+    for filter in filters {
+        match filter {
+            AstronomicalFilter::NarrowBand(wavelength) => narrowband.push(*wavelength),
+            AstronomicalFilter::BroadBand(filter) => broadband.push(filter.to_string()),
+        }
+    }
+    (narrowband, broadband)
+}
@@ -0,0 +1,48 @@
+use crate::render::image::Image;
+
+/// Size of FITS header and data blocks in bytes
+pub const BLOCK_SIZE: usize = 2880;
+/// Size of a header card in bytes
+const CARD_SIZE: usize = 80;
+
+/// Header card assigning `value` to `keyword`
+fn card(keyword: &str, value: &str) -> String {
+    // Values end in column 30, after the keyword and value indicator
+    format!("{:<8}= {:>20}", keyword, value)
+}
+
+/// Pad `bytes` with `fill` to a whole number of blocks
+fn pad_to_block(bytes: &mut Vec<u8>, fill: u8) {
+    let padded = (bytes.len() + BLOCK_SIZE - 1) / BLOCK_SIZE * BLOCK_SIZE;
+    bytes.resize(padded, fill);
+}
+
+/// Encode `image` as a FITS file of 32-bit floats, keeping the flux of every pixel
+///
+/// FITS images start from the bottom row, so rows are written in the reverse order of `image`.
+pub fn encode(image: &Image) -> Vec<u8> {
+    let cards = [
+        card("SIMPLE", "T"),
+        card("BITPIX", "-32"),
+        card("NAXIS", "2"),
+        card("NAXIS1", &image.width.to_string()),
+        card("NAXIS2", &image.height.to_string()),
+        "END".to_string(),
+    ];
+    let mut bytes: Vec<u8> = cards
+        .iter()
+        .flat_map(|card| format!("{:<1$}", card, CARD_SIZE).into_bytes())
+        .collect();
+    pad_to_block(&mut bytes, b' ');
+
+    let width = image.width as usize;
+    if width > 0 {
+        for row in image.pixels.chunks_exact(width).rev() {
+            for pixel in row {
+                bytes.extend_from_slice(&pixel.to_be_bytes());
+            }
+        }
+    }
+    pad_to_block(&mut bytes, 0);
+    bytes
+}
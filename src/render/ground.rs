@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use nalgebra as na;
+
+use crate::catalog::astrometry::icrs_to_galactic;
+use crate::catalog::ephemeris::J2000;
+use crate::render::sky::SkyFrame;
+
+/// Julian date of the Unix epoch
+const UNIX_EPOCH_JULIAN_DATE: f64 = 2_440_587.5;
+/// Scale height of the atmosphere's pressure, in metres
+const PRESSURE_SCALE_HEIGHT: f32 = 8434.0;
+/// True altitude, in degrees, below which sources are not refracted above the horizon
+const LOWEST_REFRACTED_ALTITUDE: f32 = -1.0;
+/// Upper bound on the refraction of a source visible above the horizon, in degrees
+pub const MAX_REFRACTION: f32 = 1.0;
+
+/// Place and time of an observer on the Earth's surface
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Site {
+    /// Geographic latitude in degrees, positive north
+    pub latitude: f32,
+    /// Geographic longitude in degrees, positive east
+    pub longitude: f32,
+    /// Height above sea level in metres
+    #[serde(default)]
+    pub elevation: f32,
+    pub time: DateTime<Utc>,
+}
+
+impl Site {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err("site latitude must be between -90 and 90 degrees.".into());
+        }
+        if !self.longitude.is_finite() {
+            return Err("site longitude must be a finite number.".into());
+        }
+        // From the shores of the Dead Sea to well above any observatory
+        if !(-500.0..=20_000.0).contains(&self.elevation) {
+            return Err("site elevation must be between -500 and 20000 metres.".into());
+        }
+        Ok(())
+    }
+
+    pub fn julian_date(&self) -> f64 {
+        let seconds =
+            self.time.timestamp() as f64 + self.time.timestamp_subsec_nanos() as f64 * 1e-9;
+        UNIX_EPOCH_JULIAN_DATE + seconds / 86_400.0
+    }
+
+    /// Local mean sidereal time in degrees, treating UTC as UT1
+    pub fn local_sidereal_time(&self) -> f64 {
+        let days = self.julian_date() - J2000;
+        let centuries = days / 36_525.0;
+        let greenwich =
+            280.460_618_37 + 360.985_647_366_29 * days + 0.000_387_933 * centuries.powi(2)
+                - centuries.powi(3) / 38_710_000.0;
+        (greenwich + self.longitude as f64).rem_euclid(360.0)
+    }
+
+    /// Horizon frame in catalog coordinates, with azimuth 0 towards north and the pole at zenith
+    ///
+    /// The frame's longitude increases towards the west, so an azimuth measured from north
+    /// through east is the negated longitude.
+    pub fn horizon_frame(&self) -> SkyFrame {
+        let (sin_lst, cos_lst) = self.local_sidereal_time().to_radians().sin_cos();
+        let (sin_lat, cos_lat) = (self.latitude as f64).to_radians().sin_cos();
+        // Directions in the equatorial frame of date
+        let zenith = na::Vector3::new(cos_lat * cos_lst, cos_lat * sin_lst, sin_lat);
+        let north = na::Vector3::new(-sin_lat * cos_lst, -sin_lat * sin_lst, cos_lat);
+        let west = na::Vector3::new(sin_lst, -cos_lst, 0.0);
+
+        SkyFrame {
+            primary: self.to_catalog_frame(&north),
+            secondary: self.to_catalog_frame(&west),
+            pole: self.to_catalog_frame(&zenith),
+        }
+    }
+
+    /// Express a direction in the equatorial frame of date in catalog coordinates
+    pub fn to_catalog_frame(&self, direction: &na::Vector3<f64>) -> na::Vector3<f32> {
+        (icrs_to_galactic() * precession(self.julian_date()).transpose() * direction).cast()
+    }
+
+    pub fn atmosphere(&self) -> Atmosphere {
+        Atmosphere {
+            zenith: self.horizon_frame().pole,
+            pressure: (-self.elevation / PRESSURE_SCALE_HEIGHT).exp(),
+        }
+    }
+}
+
+/// Precession from the J2000.0 mean equator to the mean equator of `julian_date` (IAU 1976)
+fn precession(julian_date: f64) -> na::Matrix3<f64> {
+    let t = (julian_date - J2000) / 36_525.0;
+    let arcseconds = |x: f64| (x / 3600.0).to_radians();
+    let zeta = arcseconds(2306.2181 * t + 0.30188 * t.powi(2) + 0.017998 * t.powi(3));
+    let z = arcseconds(2306.2181 * t + 1.09468 * t.powi(2) + 0.018203 * t.powi(3));
+    let theta = arcseconds(2004.3109 * t - 0.42665 * t.powi(2) - 0.041833 * t.powi(3));
+    let (sin_zeta, cos_zeta) = zeta.sin_cos();
+    let (sin_z, cos_z) = z.sin_cos();
+    let (sin_theta, cos_theta) = theta.sin_cos();
+    na::Matrix3::new(
+        cos_zeta * cos_theta * cos_z - sin_zeta * sin_z,
+        -sin_zeta * cos_theta * cos_z - cos_zeta * sin_z,
+        -sin_theta * cos_z,
+        cos_zeta * cos_theta * sin_z + sin_zeta * cos_z,
+        -sin_zeta * cos_theta * sin_z + cos_zeta * cos_z,
+        -sin_theta * sin_z,
+        cos_zeta * sin_theta,
+        -sin_zeta * sin_theta,
+        cos_theta,
+    )
+}
+
+/// Earth's atmosphere above a site
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Atmosphere {
+    /// Direction of the zenith in catalog coordinates
+    pub zenith: na::Vector3<f32>,
+    /// Surface pressure relative to sea level
+    pub pressure: f32,
+}
+
+impl Atmosphere {
+    /// Altitude of a direction above the horizon, in degrees
+    pub fn altitude(&self, direction: &na::Vector3<f32>) -> f32 {
+        direction
+            .normalize()
+            .dot(&self.zenith)
+            .clamp(-1.0, 1.0)
+            .asin()
+            .to_degrees()
+    }
+
+    /// Refraction of a source at a true altitude in degrees, also in degrees
+    ///
+    /// Saemundsson's formula for a temperature of 10°C, scaled with pressure.
+    pub fn refraction(&self, altitude: f32) -> f32 {
+        let altitude = altitude.max(LOWEST_REFRACTED_ALTITUDE);
+        let arcminutes = 1.02 / (altitude + 10.3 / (altitude + 5.11)).to_radians().tan();
+        (self.pressure * arcminutes / 60.0).max(0.0)
+    }
+
+    /// Apparent unit direction of a source, or `None` if it appears below the horizon
+    pub fn refract(&self, direction: &na::Vector3<f32>) -> Option<na::Vector3<f32>> {
+        let altitude = self.altitude(direction);
+        if altitude < LOWEST_REFRACTED_ALTITUDE {
+            return None;
+        }
+        let apparent_altitude = altitude + self.refraction(altitude);
+        if apparent_altitude < 0.0 {
+            return None;
+        }
+        let direction = direction.normalize();
+        let Some(horizontal) =
+            (direction - direction.dot(&self.zenith) * self.zenith).try_normalize(f32::EPSILON)
+        else {
+            return Some(direction);
+        };
+        let (sin_altitude, cos_altitude) = apparent_altitude.min(90.0).to_radians().sin_cos();
+        Some(cos_altitude * horizontal + sin_altitude * self.zenith)
+    }
+
+    /// Relative path length through the atmosphere at an apparent altitude in degrees
+    ///
+    /// Kasten and Young's formula, which stays finite at the horizon.
+    pub fn airmass(altitude: f32) -> f32 {
+        let altitude = altitude.max(0.0);
+        1.0 / (altitude.to_radians().sin() + 0.505_72 * (altitude + 6.079_95).powf(-1.636_4))
+    }
+
+    /// Extinction per airmass at `wavelength` micrometres, in magnitudes
+    ///
+    /// Rayleigh scattering, which thins out with the pressure, plus a typical aerosol load.
+    pub fn extinction_coefficient(&self, wavelength: f32) -> f32 {
+        let rayleigh = 0.008_8 * wavelength.powf(-4.05) * self.pressure;
+        let aerosol = 0.05 * (wavelength / 0.55).powf(-1.3);
+        rayleigh + aerosol
+    }
+
+    /// Dimming of a source seen in `apparent_direction` at `wavelength` micrometres, in magnitudes
+    pub fn magnitudes(&self, apparent_direction: &na::Vector3<f32>, wavelength: f32) -> f32 {
+        self.extinction_coefficient(wavelength) * Self::airmass(self.altitude(apparent_direction))
+    }
+}
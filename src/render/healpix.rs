@@ -0,0 +1,86 @@
+use std::f64::consts::FRAC_PI_4;
+
+use nalgebra as na;
+
+/// Ring of the southernmost corner of each base pixel, in units of `nside`
+const JRLL: [i64; 12] = [2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4];
+/// Longitude of the centre of each base pixel, in units of π/4
+const JPLL: [i64; 12] = [1, 3, 5, 7, 0, 2, 4, 6, 1, 3, 5, 7];
+
+/// Number of pixels of the whole sphere at `order`
+pub fn pixel_count(order: u32) -> u64 {
+    12 << (2 * order)
+}
+
+/// Solid angle of every pixel at `order`, in steradians
+pub fn pixel_area(order: u32) -> f64 {
+    4.0 * std::f64::consts::PI / pixel_count(order) as f64
+}
+
+/// Base pixel and coordinates within it of the pixel `npix` at `order`, in the nested scheme
+///
+/// `x` runs from the base pixel's southern corner towards its eastern one and `y` towards its
+/// western one, and the nested index interleaves their bits, those of `x` first.
+pub fn nest_to_xyf(order: u32, npix: u64) -> (u32, u32, usize) {
+    let face = (npix >> (2 * order)) as usize;
+    let within = npix & ((1 << (2 * order)) - 1);
+    (compact_bits(within), compact_bits(within >> 1), face)
+}
+
+/// Nested index of the pixel at `x` and `y` within the base pixel `face`, at `order`
+pub fn xyf_to_nest(order: u32, x: u32, y: u32, face: usize) -> u64 {
+    ((face as u64) << (2 * order)) | spread_bits(x) | (spread_bits(y) << 1)
+}
+
+/// Unit vector through the centre of the pixel `npix` at `order`, in the nested scheme
+///
+/// The z axis points towards the north pole and the x axis towards longitude 0.
+pub fn pixel_direction(order: u32, npix: u64) -> na::Vector3<f32> {
+    let (x, y, face) = nest_to_xyf(order, npix);
+    let nside = 1i64 << order;
+    let (x, y) = (x as i64, y as i64);
+    let ring = JRLL[face] * nside - x - y - 1;
+    let (ring_size, z) = if ring < nside {
+        (
+            ring,
+            1.0 - (ring * ring) as f64 / (3 * nside * nside) as f64,
+        )
+    } else if ring > 3 * nside {
+        let ring_size = 4 * nside - ring;
+        (
+            ring_size,
+            (ring_size * ring_size) as f64 / (3 * nside * nside) as f64 - 1.0,
+        )
+    } else {
+        (nside, (2 * nside - ring) as f64 * 2.0 / (3 * nside) as f64)
+    };
+    let mut phi = JPLL[face] * ring_size + x - y;
+    if phi < 0 {
+        phi += 8 * ring_size;
+    }
+    let phi = FRAC_PI_4 * phi as f64 / ring_size as f64;
+    let sin_theta = (1.0 - z * z).max(0.0).sqrt();
+    na::Vector3::new(
+        (sin_theta * phi.cos()) as f32,
+        (sin_theta * phi.sin()) as f32,
+        z as f32,
+    )
+}
+
+/// Keep the even bits of `value`, packed together
+fn compact_bits(value: u64) -> u32 {
+    let mut result = 0;
+    for bit in 0..32 {
+        result |= (((value >> (2 * bit)) & 1) as u32) << bit;
+    }
+    result
+}
+
+/// Spread the bits of `value` onto the even bits of the result
+fn spread_bits(value: u32) -> u64 {
+    let mut result = 0;
+    for bit in 0..32 {
+        result |= (((value >> bit) & 1) as u64) << (2 * bit);
+    }
+    result
+}
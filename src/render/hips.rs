@@ -0,0 +1,325 @@
+use chrono::{DateTime, Utc};
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
+use nalgebra as na;
+
+use crate::catalog::octree::Octree;
+use crate::render::camera::Camera;
+use crate::render::filter::AstronomicalFilter;
+use crate::render::healpix::{nest_to_xyf, pixel_area, pixel_count, pixel_direction, xyf_to_nest};
+use crate::render::image::Image;
+use crate::render::optics::PsfKernel;
+use crate::render::projection::Projection;
+use crate::render::render_flux;
+use crate::render::sky::SkyFrame;
+
+/// Width of HiPS tiles unless a survey asks for another one
+pub const DEFAULT_TILE_WIDTH: u32 = 512;
+/// Deepest order of surveys unless they ask for another one
+pub const DEFAULT_MAX_ORDER: u32 = 3;
+/// Deepest order that surveys may reach, about 13" per pixel with the default tile width
+pub const MAX_ORDER: u32 = 5;
+/// Stars at least as bright as this are drawn individually, fainter ones as glows
+pub const SURVEY_LIMITING_MAGNITUDE: f32 = 15.0;
+/// Camera pixels across each HEALPix pixel when rendering a tile
+const OVERSAMPLING: f32 = 2.0;
+/// Surface brightnesses (in magnitudes per square arcsecond) shown as black and white in tiles
+const DISPLAY_RANGE: [f32; 2] = [24.0, 12.0];
+const ARCSECONDS_PER_RADIAN: f64 = 206_264.806;
+
+/// Hierarchical Progressive Survey of the whole sky as seen from `observer_position`
+///
+/// Tiles at order `k` are the HEALPix pixels of order `k`, in the nested scheme and the
+/// catalog's galactic frame, each holding `tile_width`² HEALPix pixels of a deeper order. Tiles of
+/// [`HipsSurvey::max_order`] are rendered, while those of lower orders average their four children,
+/// as HiPS viewers expect all orders down to 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HipsSurvey {
+    pub observer_position: na::Vector3<f32>,
+    pub max_order: u32,
+    /// Power of two
+    pub tile_width: u32,
+}
+
+/// Encoded tile of a survey
+#[derive(Debug, Clone)]
+pub struct HipsTile {
+    pub order: u32,
+    pub npix: u64,
+    pub body: Vec<u8>,
+}
+
+impl HipsTile {
+    /// Path of the tile in the HiPS layout, relative to the survey's root
+    pub fn path(&self) -> String {
+        tile_path(self.order, self.npix)
+    }
+}
+
+/// Path of a tile in the HiPS layout, with directories of ten thousand tiles
+pub fn tile_path(order: u32, npix: u64) -> String {
+    format!(
+        "Norder{}/Dir{}/Npix{}.png",
+        order,
+        npix / 10_000 * 10_000,
+        npix
+    )
+}
+
+/// Name of the directory of a filter's survey, such as `SDSS_G` or `0.6563um`
+pub fn filter_label(filter: &AstronomicalFilter) -> String {
+    match filter {
+        AstronomicalFilter::BroadBand(filter) => filter.to_string(),
+        AstronomicalFilter::NarrowBand(wavelength) => format!("{}um", wavelength),
+    }
+}
+
+impl HipsSurvey {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_order > MAX_ORDER {
+            return Err(format!("max_order must be at most {}.", MAX_ORDER));
+        }
+        if !self.tile_width.is_power_of_two()
+            || !(64..=DEFAULT_TILE_WIDTH).contains(&self.tile_width)
+        {
+            return Err(format!(
+                "tile_width must be a power of two between 64 and {}.",
+                DEFAULT_TILE_WIDTH
+            ));
+        }
+        if self.observer_position.iter().any(|x| !x.is_finite()) {
+            return Err("observer_position values must be finite.".into());
+        }
+        Ok(())
+    }
+
+    /// Pixels rendered per filter, those of the tiles of [`HipsSurvey::max_order`]
+    pub fn pixels(&self) -> i64 {
+        pixel_count(self.max_order) as i64 * (self.tile_width as i64).pow(2)
+    }
+
+    /// Order of the HEALPix pixels within tiles, relative to the tiles' own
+    fn width_order(&self) -> u32 {
+        self.tile_width.trailing_zeros()
+    }
+
+    /// Every tile below the base pixel `face`, down to [`HipsSurvey::max_order`]
+    pub fn face_tiles(
+        &self,
+        catalog: &Octree,
+        filter: &AstronomicalFilter,
+        face: u64,
+    ) -> Result<Vec<HipsTile>, String> {
+        let mut tiles = vec![];
+        self.build_tile(catalog, filter, 0, face, &mut tiles)?;
+        Ok(tiles)
+    }
+
+    /// Surface brightness of the tile `npix` at `order`, encoding it and its descendants into `tiles`
+    fn build_tile(
+        &self,
+        catalog: &Octree,
+        filter: &AstronomicalFilter,
+        order: u32,
+        npix: u64,
+        tiles: &mut Vec<HipsTile>,
+    ) -> Result<Image, String> {
+        let image = if order == self.max_order {
+            self.render_tile(catalog, filter, order, npix)
+        } else {
+            let half = self.tile_width / 2;
+            let mut image = Image::new(self.tile_width, self.tile_width);
+            for child in 0..4 {
+                let child_image =
+                    self.build_tile(catalog, filter, order + 1, 4 * npix + child, tiles)?;
+                // Children split the tile's x and y in halves, x by the lowest bit
+                let (x_offset, y_offset) = ((child as u32 & 1) * half, (child as u32 >> 1) * half);
+                for y in 0..half {
+                    for x in 0..half {
+                        let mean = (0..4)
+                            .map(|i| child_pixel(&child_image, 2 * x + (i & 1), 2 * y + (i >> 1)))
+                            .sum::<f32>()
+                            / 4.0;
+                        set_pixel(&mut image, x_offset + x, y_offset + y, mean);
+                    }
+                }
+            }
+            image
+        };
+        tiles.push(HipsTile {
+            order,
+            npix,
+            body: encode(&image)?,
+        });
+        Ok(image)
+    }
+
+    /// Surface brightness, in flux per square arcsecond, of the HEALPix pixels of a tile
+    ///
+    /// The tile is rendered by a gnomonic camera centred on it, at twice its resolution, which is
+    /// then sampled at the centre of each HEALPix pixel.
+    pub fn render_tile(
+        &self,
+        catalog: &Octree,
+        filter: &AstronomicalFilter,
+        order: u32,
+        npix: u64,
+    ) -> Image {
+        let pixel_order = order + self.width_order();
+        let (tile_x, tile_y, face) = nest_to_xyf(order, npix);
+        let pixel = |x: u32, y: u32| {
+            pixel_direction(
+                pixel_order,
+                xyf_to_nest(
+                    pixel_order,
+                    (tile_x << self.width_order()) | x,
+                    (tile_y << self.width_order()) | y,
+                    face,
+                ),
+            )
+        };
+
+        let center = pixel_direction(order, npix);
+        let pixel_size = pixel_area(pixel_order).sqrt() as f32;
+        let last = self.tile_width - 1;
+        let half_angle = [(0, 0), (last, 0), (0, last), (last, last)]
+            .into_iter()
+            .map(|(x, y)| pixel(x, y).dot(&center).clamp(-1.0, 1.0).acos())
+            .fold(0.0, f32::max)
+            + pixel_size;
+        let fov = (2.0 * half_angle).to_degrees() * 1.05;
+        let size = (fov.to_radians() / pixel_size * OVERSAMPLING).ceil() as u32;
+        let frame = SkyFrame::from_basis(&[na::Vector3::x(), na::Vector3::y()])
+            .expect("The world axes are orthogonal");
+        let camera = Camera::new(
+            self.observer_position,
+            &frame,
+            center.y.atan2(center.x).to_degrees(),
+            center.z.clamp(-1.0, 1.0).asin().to_degrees(),
+            Projection::Gnomonic,
+            [fov, fov],
+            [size, size],
+        );
+        let flux = render_flux(
+            &camera,
+            catalog,
+            &PsfKernel::default(),
+            filter.wavelength(),
+            SURVEY_LIMITING_MAGNITUDE,
+            None,
+        );
+
+        let mut image = Image::new(self.tile_width, self.tile_width);
+        for y in 0..self.tile_width {
+            for x in 0..self.tile_width {
+                let direction = pixel(x, y);
+                let Some(point) = camera.direction_pixel(&direction) else {
+                    continue;
+                };
+                let surface_brightness = solid_angle(&camera, &point)
+                    .map_or(0.0, |solid_angle| bilinear(&flux, &point) / solid_angle);
+                set_pixel(&mut image, x, y, surface_brightness);
+            }
+        }
+        image
+    }
+
+    /// HiPS `properties` file of the survey in `filter`
+    pub fn properties(
+        &self,
+        survey_id: uuid::Uuid,
+        filter: &AstronomicalFilter,
+        released_at: DateTime<Utc>,
+    ) -> String {
+        let label = filter_label(filter);
+        // HiPS gives wavelengths in metres
+        let em_min = (filter.wavelength() - filter.bandwidth() / 2.0) as f64 * 1e-6;
+        let em_max = (filter.wavelength() + filter.bandwidth() / 2.0) as f64 * 1e-6;
+        let [x, y, z] = [
+            self.observer_position.x,
+            self.observer_position.y,
+            self.observer_position.z,
+        ];
+        [
+            (
+                "creator_did",
+                format!("ivo://space-telescope/surveys/{}/{}", survey_id, label),
+            ),
+            (
+                "obs_title",
+                format!("Sky from ({}, {}, {}) pc in {}", x, y, z, label),
+            ),
+            ("dataproduct_type", "image".into()),
+            ("hips_version", "1.4".into()),
+            (
+                "hips_release_date",
+                released_at.format("%Y-%m-%dT%H:%MZ").to_string(),
+            ),
+            ("hips_status", "public master unclonable".into()),
+            ("hips_tile_format", "png".into()),
+            ("hips_tile_width", self.tile_width.to_string()),
+            ("hips_order", self.max_order.to_string()),
+            ("hips_order_min", "0".into()),
+            ("hips_frame", "galactic".into()),
+            ("em_min", format!("{:e}", em_min)),
+            ("em_max", format!("{:e}", em_max)),
+        ]
+        .iter()
+        .map(|(key, value)| format!("{:<20} = {}\n", key, value))
+        .collect()
+    }
+}
+
+/// Tiles store their rows from the top, while `y` counts from the bottom
+fn set_pixel(image: &mut Image, x: u32, y: u32, value: f32) {
+    let row = image.height - 1 - y;
+    image.pixels[(row * image.width + x) as usize] = value;
+}
+
+fn child_pixel(image: &Image, x: u32, y: u32) -> f32 {
+    image.get(x, image.height - 1 - y)
+}
+
+/// Solid angle of the camera pixel around `point`, in square arcseconds
+fn solid_angle(camera: &Camera, point: &na::Point2<f32>) -> Option<f32> {
+    let direction =
+        |dx: f32, dy: f32| camera.pixel_direction(&na::Point2::new(point.x + dx, point.y + dy));
+    let along_x = direction(0.5, 0.0)? - direction(-0.5, 0.0)?;
+    let along_y = direction(0.0, 0.5)? - direction(0.0, -0.5)?;
+    let steradians = along_x.cross(&along_y).norm() as f64;
+    Some((steradians * ARCSECONDS_PER_RADIAN.powi(2)) as f32)
+}
+
+/// Value of `image` at continuous pixel coordinates, interpolated between pixel centres
+fn bilinear(image: &Image, point: &na::Point2<f32>) -> f32 {
+    let u = (point.x - 0.5).clamp(0.0, (image.width - 1) as f32);
+    let v = (point.y - 0.5).clamp(0.0, (image.height - 1) as f32);
+    let (x, y) = (u.floor() as u32, v.floor() as u32);
+    let (x1, y1) = ((x + 1).min(image.width - 1), (y + 1).min(image.height - 1));
+    let (fx, fy) = (u - x as f32, v - y as f32);
+    (image.get(x, y) * (1.0 - fx) + image.get(x1, y) * fx) * (1.0 - fy)
+        + (image.get(x, y1) * (1.0 - fx) + image.get(x1, y1) * fx) * fy
+}
+
+/// 8-bit PNG of a tile, with surface brightness mapped logarithmically over [`DISPLAY_RANGE`]
+fn encode(image: &Image) -> Result<Vec<u8>, String> {
+    let [faint, bright] = DISPLAY_RANGE;
+    let samples: Vec<u8> = image
+        .pixels
+        .iter()
+        .map(|&surface_brightness| {
+            if surface_brightness <= 0.0 {
+                0
+            } else {
+                let magnitude = -2.5 * surface_brightness.log10();
+                (((faint - magnitude) / (faint - bright)).clamp(0.0, 1.0) * u8::MAX as f32).round()
+                    as u8
+            }
+        })
+        .collect();
+    let mut body = vec![];
+    PngEncoder::new(&mut body)
+        .write_image(&samples, image.width, image.height, ColorType::L8)
+        .map_err(|e| e.to_string())?;
+    Ok(body)
+}
@@ -0,0 +1,152 @@
+use nalgebra as na;
+
+use crate::render::camera::Camera;
+use crate::render::optics::PsfKernel;
+
+/// Samples per pixel along each axis when integrating a PSF over pixels
+const SUBSAMPLES: usize = 5;
+/// Samples per pixel along each axis when drawing an extended source
+const EXTENDED_SUBSAMPLES: usize = 4;
+/// Distance from the centre, in units of the PSF's sigma, beyond which smooth profiles are
+/// sampled once per pixel
+const SUBSAMPLED_SIGMAS: f32 = 4.0;
+
+/// Single channel image of accumulated flux
+///
+/// Pixels are stored row by row from the top left corner. Pixel `(x, y)` covers the continuous
+/// coordinates `[x, x + 1) × [y, y + 1)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<f32>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0.0; width as usize * height as usize],
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> f32 {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn total_flux(&self) -> f64 {
+        self.pixels.iter().map(|&pixel| pixel as f64).sum()
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Spread `flux` over the pixels around `center` following `kernel`
+    ///
+    /// The PSF is integrated over each pixel and normalized over its whole stamp, so the image
+    /// gains exactly `flux` unless part of the stamp falls outside of it.
+    pub fn draw(&mut self, kernel: &PsfKernel, center: &na::Point2<f32>, flux: f32) {
+        let radius = kernel.stamp_radius();
+        let x_range = (center.x - radius).floor() as i64..=(center.x + radius).floor() as i64;
+        let y_range = (center.y - radius).floor() as i64..=(center.y + radius).floor() as i64;
+        if *x_range.end() < 0
+            || *y_range.end() < 0
+            || *x_range.start() >= self.width as i64
+            || *y_range.start() >= self.height as i64
+        {
+            return;
+        }
+
+        let mut weights = Vec::with_capacity(
+            (x_range.end() - x_range.start() + 1) as usize
+                * (y_range.end() - y_range.start() + 1) as usize,
+        );
+        for y in y_range.clone() {
+            for x in x_range.clone() {
+                weights.push(((x, y), pixel_weight(kernel, center, x, y)));
+            }
+        }
+        let total_weight: f32 = weights.iter().map(|(_, weight)| weight).sum();
+        if total_weight <= 0.0 {
+            // PSF narrower than the subsampling, all the light lands in one pixel
+            self.add(center.x.floor() as i64, center.y.floor() as i64, flux);
+            return;
+        }
+        for ((x, y), weight) in weights {
+            self.add(x, y, flux * weight / total_weight);
+        }
+    }
+
+    /// Add the light of an extended source around pixel `center`, `radius` pixels wide
+    ///
+    /// `brightness` gives the source's brightness along unit apparent directions. Each pixel
+    /// gains its mean brightness over a grid of samples, times `scale`, so a source of uniform
+    /// brightness 1 gains about `scale` times its area in pixels.
+    pub fn draw_extended(
+        &mut self,
+        camera: &Camera,
+        center: &na::Point2<f32>,
+        radius: f32,
+        scale: f64,
+        brightness: impl Fn(&na::Vector3<f64>) -> f64,
+    ) {
+        let samples_per_pixel = (EXTENDED_SUBSAMPLES * EXTENDED_SUBSAMPLES) as f64;
+        // Pad the bounding box to cover distortion by the projection
+        let extent = 1.5 * radius + 1.0;
+        let x_range = (center.x - extent).floor().max(0.0) as i64
+            ..=(center.x + extent).floor().min(self.width as f32 - 1.0) as i64;
+        let y_range = (center.y - extent).floor().max(0.0) as i64
+            ..=(center.y + extent).floor().min(self.height as f32 - 1.0) as i64;
+        for y in y_range {
+            for x in x_range.clone() {
+                let mut total = 0.0;
+                for i in 0..EXTENDED_SUBSAMPLES {
+                    for j in 0..EXTENDED_SUBSAMPLES {
+                        let sample = na::Point2::new(
+                            x as f32 + (i as f32 + 0.5) / EXTENDED_SUBSAMPLES as f32,
+                            y as f32 + (j as f32 + 0.5) / EXTENDED_SUBSAMPLES as f32,
+                        );
+                        if let Some(direction) = camera.pixel_direction(&sample) {
+                            total += brightness(&direction.cast::<f64>().normalize());
+                        }
+                    }
+                }
+                if total > 0.0 {
+                    self.add(x, y, (total / samples_per_pixel * scale) as f32);
+                }
+            }
+        }
+    }
+
+    /// Add `flux` to a pixel, ignoring pixels outside of the image
+    pub fn add(&mut self, x: i64, y: i64, flux: f32) {
+        if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+            let index = self.index(x as u32, y as u32);
+            self.pixels[index] += flux;
+        }
+    }
+}
+
+/// Integral of the PSF over pixel `(x, y)`, up to normalization
+fn pixel_weight(kernel: &PsfKernel, center: &na::Point2<f32>, x: i64, y: i64) -> f32 {
+    // The rings of Airy patterns need every sample, even far from the core
+    let distance = (na::Point2::new(x as f32 + 0.5, y as f32 + 0.5) - center).norm();
+    if !matches!(kernel, PsfKernel::Airy { .. })
+        && distance > SUBSAMPLED_SIGMAS * kernel.sigma() + 1.0
+    {
+        return kernel.profile(distance) * (SUBSAMPLES * SUBSAMPLES) as f32;
+    }
+    let mut weight = 0.0;
+    for i in 0..SUBSAMPLES {
+        for j in 0..SUBSAMPLES {
+            let sample = na::Point2::new(
+                x as f32 + (i as f32 + 0.5) / SUBSAMPLES as f32,
+                y as f32 + (j as f32 + 0.5) / SUBSAMPLES as f32,
+            );
+            weight += kernel.profile((sample - center).norm());
+        }
+    }
+    weight
+}
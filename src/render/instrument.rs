@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::configuration::load_yaml_files;
+use crate::render::detector::Detector;
+use crate::render::filter::AstronomicalFilter;
+use crate::render::optics::{Optics, Psf};
+
+/// Relative tolerance when checking a requested field of view against an instrument's
+const FOV_TOLERANCE: f32 = 1e-3;
+/// Arcseconds per radian
+const ARCSECONDS_PER_RADIAN: f32 = 206_264.8;
+
+/// A telescope and camera combination that render jobs can reference by name
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct Instrument {
+    /// Diameter of the entrance pupil in metres
+    pub aperture_diameter: f32,
+    /// Effective focal length in metres
+    pub focal_length: f32,
+    /// Physical size of a detector pixel in micrometres
+    pub pixel_size: f32,
+    /// Size of the detector in pixels
+    pub detector_dimensions: [u32; 2],
+    /// Filters that can be placed in front of the detector
+    pub filters: Vec<AstronomicalFilter>,
+    pub psf: Psf,
+    pub detector: Detector,
+}
+
+impl Instrument {
+    pub fn optics(&self) -> Optics {
+        Optics {
+            aperture_diameter: Some(self.aperture_diameter),
+            psf: self.psf,
+        }
+    }
+
+    /// Angle on the sky per millimetre of the focal plane, in arcseconds
+    pub fn plate_scale(&self) -> f32 {
+        ARCSECONDS_PER_RADIAN / (self.focal_length * 1e3)
+    }
+
+    /// Angle subtended by a pixel, in arcseconds
+    pub fn pixel_scale(&self) -> f32 {
+        self.plate_scale() * self.pixel_size * 1e-3
+    }
+
+    /// Field of view in degrees of a readout of `image_dimensions` pixels
+    pub fn fov(&self, image_dimensions: [i32; 2]) -> [f32; 2] {
+        let pixel_scale = self.pixel_scale();
+        image_dimensions.map(|dimension| dimension as f32 * pixel_scale / 3600.0)
+    }
+
+    pub fn supports_filter(&self, filter: &AstronomicalFilter) -> bool {
+        self.filters
+            .iter()
+            .any(|available| match (available, filter) {
+                (AstronomicalFilter::NarrowBand(a), AstronomicalFilter::NarrowBand(b)) => {
+                    (a - b).abs() <= 1e-4
+                }
+                _ => available == filter,
+            })
+    }
+
+    /// Check a render's geometry against the detector
+    ///
+    /// Images may be a subarray of the detector, but must keep its pixel scale.
+    pub fn validate_geometry(
+        &self,
+        fov: [f32; 2],
+        image_dimensions: [i32; 2],
+    ) -> Result<(), String> {
+        if image_dimensions.iter().any(|dimension| *dimension <= 0) {
+            return Err("image_dimensions values must be positive.".into());
+        }
+        for axis in 0..2 {
+            if image_dimensions[axis] as u32 > self.detector_dimensions[axis] {
+                return Err(format!(
+                    "image_dimensions of {:?} do not fit on the instrument's {:?} pixel detector.",
+                    image_dimensions, self.detector_dimensions
+                ));
+            }
+        }
+        let expected_fov = self.fov(image_dimensions);
+        for axis in 0..2 {
+            if (fov[axis] - expected_fov[axis]).abs() > FOV_TOLERANCE * expected_fov[axis] {
+                return Err(format!(
+                    "A fov of {:?} does not match the instrument's {:?} for {:?} pixels.",
+                    fov, expected_fov, image_dimensions
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.aperture_diameter.is_nan() || self.aperture_diameter <= 0.0 {
+            return Err("Instrument aperture_diameter must be positive.".into());
+        }
+        if self.focal_length.is_nan() || self.focal_length <= 0.0 {
+            return Err("Instrument focal_length must be positive.".into());
+        }
+        if self.pixel_size.is_nan() || self.pixel_size <= 0.0 {
+            return Err("Instrument pixel_size must be positive.".into());
+        }
+        if self.detector_dimensions.contains(&0) {
+            return Err("Instrument detector_dimensions must be positive.".into());
+        }
+        if self.filters.is_empty() {
+            return Err("Instrument must have at least one filter.".into());
+        }
+        self.optics().validate()?;
+        self.detector.validate()
+    }
+}
+
+/// Every instrument known to the application, by name
+#[derive(Debug, Default)]
+pub struct InstrumentRegistry {
+    instruments: HashMap<String, Instrument>,
+}
+
+impl InstrumentRegistry {
+    /// Load every instrument in `directory`, each named after its YAML file
+    pub fn load(directory: &Path) -> Result<Self, config::ConfigError> {
+        let mut instruments = HashMap::new();
+        for (name, instrument) in load_yaml_files::<Instrument>(directory)? {
+            instrument.validate().map_err(|e| {
+                config::ConfigError::Message(format!("Invalid instrument {}: {}", name, e))
+            })?;
+            instruments.insert(name, instrument);
+        }
+        Ok(Self { instruments })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Instrument> {
+        self.instruments.get(name)
+    }
+}
@@ -0,0 +1,187 @@
+use nalgebra as na;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::catalog::octree::Octree;
+use crate::render::camera::Camera;
+use crate::render::detector::{Detector, Exposure, Readout};
+use crate::render::extinction::Extinction;
+use crate::render::image::Image;
+use crate::render::optics::PsfKernel;
+use crate::render::relativity::SOLAR_TEMPERATURE;
+use crate::render::sky_brightness::SkyBrightness;
+use crate::render::stellar_disk::StellarDisk;
+
+pub mod camera;
+pub mod deep_sky;
+pub mod derivatives;
+pub mod detector;
+pub mod extinction;
+pub mod filter;
+pub mod fits;
+pub mod ground;
+pub mod healpix;
+pub mod hips;
+pub mod image;
+pub mod instrument;
+pub mod optics;
+pub mod projection;
+pub mod relativity;
+pub mod sky;
+pub mod sky_brightness;
+pub mod solar_system;
+pub mod stellar_disk;
+pub mod tiles;
+
+/// Photons per second per square metre of aperture received from a zero magnitude source
+pub const ZERO_POINT_PHOTON_RATE: f32 = 1e10;
+
+/// Flux of a source relative to a zero magnitude source
+pub fn flux(apparent_magnitude: f32) -> f32 {
+    10f32.powf(-0.4 * apparent_magnitude)
+}
+
+/// Accumulate the flux of every source of `catalog` seen through `camera`
+///
+/// Stars at least as bright as `limiting_magnitude` are drawn individually with `kernel`, while
+/// fainter stars are drawn as glows blurred by both their spatial extent and the PSF. Stars close
+/// enough to span a few pixels are drawn as limb darkened disks instead, hiding the sources
+/// behind them. Sources are dimmed by `extinction` along their line of sight and by the camera's
+/// atmosphere, and Doppler shifted and beamed by the camera's motion. `kernel` and `extinction`
+/// should both be for light of `wavelength` micrometres.
+pub fn render_flux(
+    camera: &Camera,
+    catalog: &Octree,
+    kernel: &PsfKernel,
+    wavelength: f32,
+    limiting_magnitude: f32,
+    extinction: Option<&Extinction>,
+) -> Image {
+    let mut image = Image::new(camera.image_dimensions[0], camera.image_dimensions[1]);
+    // Beaming can lift stars fainter than the limit above it
+    let query_limit = camera.motion.map_or(limiting_magnitude, |motion| {
+        limiting_magnitude + motion.max_brightening(wavelength, SOLAR_TEMPERATURE)
+    });
+    let sources = camera.visible_sources(catalog, query_limit);
+    let extinction = |position: &na::Vector3<f32>| {
+        let dust = extinction.map_or(0.0, |extinction| {
+            extinction.magnitudes(&camera.position, position)
+        });
+        dust + camera_magnitudes(camera, position, wavelength)
+    };
+
+    let disks: Vec<StellarDisk> = sources
+        .stars
+        .iter()
+        .filter_map(|star| StellarDisk::resolve(camera, star))
+        .collect();
+    let occulted =
+        |position: &na::Vector3<f32>| disks.iter().any(|disk| disk.occults(camera, position));
+
+    for star in sources.stars {
+        let Some(pixel) = camera.pixel_coordinates(&star.position) else {
+            continue;
+        };
+        let star_flux =
+            flux(star.apparent_magnitude(&camera.position) + extinction(&star.position));
+        if let Some(disk) = disks.iter().find(|disk| disk.id == star.id) {
+            disk.draw(&mut image, camera, &pixel, wavelength, star_flux);
+        } else if !occulted(&star.position) {
+            image.draw(kernel, &pixel, star_flux);
+        }
+    }
+
+    for glow in sources.glows {
+        let Some(pixel) = camera.pixel_coordinates(&glow.position) else {
+            continue;
+        };
+        if occulted(&glow.position) {
+            continue;
+        }
+        let distance = (glow.position - camera.position).norm();
+        let angular_radius = (glow.radius / distance).atan() / camera.angular_resolution();
+        let glow_kernel = PsfKernel::Gaussian {
+            sigma: (angular_radius.powi(2) + kernel.sigma().powi(2)).sqrt(),
+        };
+        image.draw(
+            &glow_kernel,
+            &pixel,
+            flux(glow.apparent_magnitude(&camera.position) + extinction(&glow.position)),
+        );
+    }
+
+    image
+}
+
+/// Change in magnitude of a source at `position` from the camera's motion and atmosphere, at
+/// `wavelength` micrometres
+fn camera_magnitudes(camera: &Camera, position: &na::Vector3<f32>, wavelength: f32) -> f32 {
+    let motion = camera.motion.map_or(0.0, |motion| {
+        motion.magnitude_shift(&(position - camera.position), wavelength, SOLAR_TEMPERATURE)
+    });
+    let atmosphere = camera.atmosphere.map_or(0.0, |atmosphere| {
+        camera
+            .apparent_direction(position)
+            .map_or(0.0, |direction| {
+                atmosphere.magnitudes(&direction, wavelength)
+            })
+    });
+    motion + atmosphere
+}
+
+/// Solid angle of a pixel of `camera` in square arcseconds
+fn pixel_area(camera: &Camera) -> f32 {
+    (camera.angular_resolution().to_degrees() * 3600.0).powi(2)
+}
+
+/// Flux of the sky background in each pixel of `camera`, seen from the ground
+///
+/// Pixels are sampled at their centres.
+pub fn render_sky(camera: &Camera, sky: &SkyBrightness) -> Image {
+    let [width, height] = camera.image_dimensions;
+    let pixel_area = pixel_area(camera);
+    let mut image = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = na::Point2::new(x as f32 + 0.5, y as f32 + 0.5);
+            if let Some(direction) = camera.pixel_direction(&pixel) {
+                image.pixels[y as usize * width as usize + x as usize] =
+                    flux(sky.surface_brightness(&direction)) * pixel_area;
+            }
+        }
+    }
+    image
+}
+
+/// Simulate an exposure of the accumulated flux of `image` through an aperture of
+/// `aperture_diameter` metres
+///
+/// The sky adds the exposure's uniform sky brightness and, from the ground, the `background`
+/// rendered by [`render_sky`]. The noise is drawn from a generator seeded with `seed`, so the
+/// same job always produces the same readout.
+pub fn expose(
+    image: &Image,
+    background: Option<&Image>,
+    camera: &Camera,
+    aperture_diameter: f32,
+    detector: &Detector,
+    exposure: &Exposure,
+    seed: u64,
+) -> Readout {
+    let collecting_area = std::f32::consts::PI * (aperture_diameter / 2.0).powi(2);
+    let photon_rate = ZERO_POINT_PHOTON_RATE * collecting_area;
+    let sky = exposure.sky_brightness.map_or(0.0, |sky_brightness| {
+        flux(sky_brightness) * pixel_area(camera)
+    });
+
+    let mut photons = image.clone();
+    for (i, pixel) in photons.pixels.iter_mut().enumerate() {
+        let background = background.map_or(0.0, |background| background.pixels[i]);
+        *pixel = (*pixel + sky + background) * photon_rate;
+    }
+    detector.read_out(
+        &photons,
+        exposure.time,
+        &mut ChaCha8Rng::seed_from_u64(seed),
+    )
+}
@@ -0,0 +1,209 @@
+use std::f32::consts::PI;
+
+/// Ratio between the full width at half maximum and the standard deviation of a Gaussian
+const FWHM_PER_SIGMA: f32 = 2.354_82;
+/// Radius of the first dark ring of an Airy pattern, in units of wavelength / aperture diameter
+const AIRY_FIRST_ZERO: f32 = 1.219_67;
+/// Width of the PSF used when a render job does not describe its optics, in pixels
+const DEFAULT_FWHM_PIXELS: f32 = 1.5;
+/// Largest PSF stamp, in pixels from its centre
+///
+/// The wings of wide PSFs and of the glare of resolved stars are cut at this radius, keeping
+/// their flux within the stamp, so a single source costs at most 257² pixels.
+pub const MAX_STAMP_RADIUS: f32 = 128.0;
+
+/// Point-spread function models
+///
+/// Widths are given in arcseconds.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum Psf {
+    Gaussian {
+        fwhm: f32,
+    },
+    /// Seeing-limited profile with extended wings. Smaller `beta` means stronger wings.
+    Moffat {
+        fwhm: f32,
+        beta: f32,
+    },
+    /// Diffraction pattern of the optics' circular aperture, which widens with wavelength.
+    Airy,
+}
+
+impl Psf {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Psf::Gaussian { .. } => "gaussian",
+            Psf::Moffat { .. } => "moffat",
+            Psf::Airy => "airy",
+        }
+    }
+
+    /// Full width at half maximum in arcseconds, if the model has a fixed width
+    pub fn fwhm(&self) -> Option<f32> {
+        match self {
+            Psf::Gaussian { fwhm } | Psf::Moffat { fwhm, .. } => Some(*fwhm),
+            Psf::Airy => None,
+        }
+    }
+
+    pub fn beta(&self) -> Option<f32> {
+        match self {
+            Psf::Moffat { beta, .. } => Some(*beta),
+            _ => None,
+        }
+    }
+}
+
+/// Telescope optics of a render job
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Optics {
+    /// Diameter of the entrance pupil in metres
+    pub aperture_diameter: Option<f32>,
+    pub psf: Psf,
+}
+
+impl Optics {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(aperture_diameter) = self.aperture_diameter {
+            if aperture_diameter.is_nan() || aperture_diameter <= 0.0 {
+                return Err("optics aperture_diameter must be positive.".into());
+            }
+        }
+        match self.psf {
+            Psf::Gaussian { fwhm } | Psf::Moffat { fwhm, .. } if fwhm.is_nan() || fwhm <= 0.0 => {
+                Err("PSF fwhm must be positive.".into())
+            }
+            // The profile's total flux diverges otherwise
+            Psf::Moffat { beta, .. } if beta.is_nan() || beta <= 1.0 => {
+                Err("Moffat PSF beta must be greater than 1.".into())
+            }
+            Psf::Airy if self.aperture_diameter.is_none() => {
+                Err("An Airy PSF requires the optics' aperture_diameter.".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The PSF sampled by pixels subtending `pixel_scale` radians, at `wavelength` micrometres
+    pub fn kernel(&self, wavelength: f32, pixel_scale: f32) -> PsfKernel {
+        let arcseconds_to_pixels = (1.0f32 / 3600.0).to_radians() / pixel_scale;
+        match self.psf {
+            Psf::Gaussian { fwhm } => PsfKernel::Gaussian {
+                sigma: fwhm * arcseconds_to_pixels / FWHM_PER_SIGMA,
+            },
+            Psf::Moffat { fwhm, beta } => PsfKernel::Moffat {
+                alpha: fwhm * arcseconds_to_pixels / (2.0 * (2f32.powf(1.0 / beta) - 1.0).sqrt()),
+                beta,
+            },
+            Psf::Airy => {
+                let aperture_diameter = self
+                    .aperture_diameter
+                    .expect("Airy PSFs are validated to have an aperture diameter");
+                PsfKernel::Airy {
+                    first_zero: AIRY_FIRST_ZERO * wavelength * 1e-6
+                        / aperture_diameter
+                        / pixel_scale,
+                }
+            }
+        }
+    }
+}
+
+/// Point-spread function with its widths in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PsfKernel {
+    Gaussian {
+        sigma: f32,
+    },
+    Moffat {
+        alpha: f32,
+        beta: f32,
+    },
+    /// `first_zero` is the radius of the first dark ring
+    Airy {
+        first_zero: f32,
+    },
+}
+
+impl Default for PsfKernel {
+    /// A barely resolved Gaussian, for render jobs without optics
+    fn default() -> Self {
+        PsfKernel::Gaussian {
+            sigma: DEFAULT_FWHM_PIXELS / FWHM_PER_SIGMA,
+        }
+    }
+}
+
+impl PsfKernel {
+    /// Unnormalized intensity at `radius` pixels from the centre
+    pub fn profile(&self, radius: f32) -> f32 {
+        match *self {
+            PsfKernel::Gaussian { sigma } => (-0.5 * (radius / sigma).powi(2)).exp(),
+            PsfKernel::Moffat { alpha, beta } => (1.0 + (radius / alpha).powi(2)).powf(-beta),
+            PsfKernel::Airy { first_zero } => {
+                let x = PI * AIRY_FIRST_ZERO * radius / first_zero;
+                if x.abs() < 1e-4 {
+                    1.0
+                } else {
+                    (2.0 * bessel_j1(x) / x).powi(2)
+                }
+            }
+        }
+    }
+
+    /// Standard deviation of the Gaussian that best matches the PSF's core, in pixels
+    pub fn sigma(&self) -> f32 {
+        match *self {
+            PsfKernel::Gaussian { sigma } => sigma,
+            PsfKernel::Moffat { alpha, beta } => {
+                2.0 * alpha * (2f32.powf(1.0 / beta) - 1.0).sqrt() / FWHM_PER_SIGMA
+            }
+            PsfKernel::Airy { first_zero } => 0.42 * first_zero / AIRY_FIRST_ZERO,
+        }
+    }
+
+    /// Distance from the centre beyond which the PSF's light is neglected, in pixels
+    pub fn stamp_radius(&self) -> f32 {
+        let radius = match *self {
+            PsfKernel::Gaussian { sigma } => 4.0 * sigma,
+            // Where the profile falls to a thousandth of its peak
+            PsfKernel::Moffat { alpha, beta } => alpha * (1000f32.powf(1.0 / beta) - 1.0).sqrt(),
+            PsfKernel::Airy { first_zero } => 8.0 * first_zero,
+        };
+        radius.clamp(1.0, MAX_STAMP_RADIUS)
+    }
+}
+
+/// Bessel function of the first kind of order one
+///
+/// Rational approximations from Numerical Recipes, accurate to about 1e-8.
+fn bessel_j1(x: f32) -> f32 {
+    let x = x as f64;
+    let ax = x.abs();
+    let value = if ax < 8.0 {
+        let y = x * x;
+        let numerator = x
+            * (72362614232.0
+                + y * (-7895059235.0
+                    + y * (242396853.1
+                        + y * (-2972611.439 + y * (15704.48260 + y * (-30.16036606))))));
+        let denominator = 144725228442.0
+            + y * (2300535178.0 + y * (18583304.74 + y * (99447.43394 + y * (376.9991397 + y))));
+        numerator / denominator
+    } else {
+        let z = 8.0 / ax;
+        let y = z * z;
+        let xx = ax - 2.356194491;
+        let p = 1.0
+            + y * (0.183105e-2
+                + y * (-0.3516396496e-4 + y * (0.2457520174e-5 + y * (-0.240337019e-6))));
+        let q = 0.04687499995
+            + y * (-0.2002690873e-3
+                + y * (0.8449199096e-5 + y * (-0.88228987e-6 + y * 0.105787412e-6)));
+        let magnitude =
+            (std::f64::consts::FRAC_2_PI / ax).sqrt() * (xx.cos() * p - z * xx.sin() * q);
+        magnitude.copysign(x)
+    };
+    value as f32
+}
@@ -0,0 +1,225 @@
+use std::f32::consts::{FRAC_PI_2, PI, SQRT_2};
+
+use nalgebra as na;
+
+/// Map projections used to flatten the celestial sphere onto the image plane
+///
+/// Directions are given in the camera frame: +x points right, +y points up
+/// and +z points along the line of sight.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Projection {
+    /// Pinhole camera. Straight lines stay straight, but the scale diverges towards 90° off-axis.
+    #[default]
+    Gnomonic,
+    /// Conformal azimuthal projection, only the antipode of the line of sight is lost.
+    Stereographic,
+    /// The sky hemisphere as seen from infinitely far away.
+    Orthographic,
+    /// Fisheye lens. Distance from the image centre is proportional to the angle off-axis.
+    AzimuthalEquidistant,
+    /// Plate carrée. Longitude and latitude around the line of sight map linearly onto x and y.
+    Equirectangular,
+    /// Equal-area all-sky projection.
+    HammerAitoff,
+}
+
+impl Projection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Projection::Gnomonic => "gnomonic",
+            Projection::Stereographic => "stereographic",
+            Projection::Orthographic => "orthographic",
+            Projection::AzimuthalEquidistant => "azimuthal_equidistant",
+            Projection::Equirectangular => "equirectangular",
+            Projection::HammerAitoff => "hammer_aitoff",
+        }
+    }
+
+    /// Largest field of view (in degrees) that can be represented along each image axis
+    ///
+    /// The second element of the tuple is `true` if the limit itself is attainable.
+    pub fn max_fov(&self) -> ([f32; 2], bool) {
+        match self {
+            Projection::Gnomonic => ([180.0, 180.0], false),
+            Projection::Stereographic => ([360.0, 360.0], false),
+            Projection::Orthographic => ([180.0, 180.0], true),
+            Projection::AzimuthalEquidistant => ([360.0, 360.0], true),
+            Projection::Equirectangular | Projection::HammerAitoff => ([360.0, 180.0], true),
+        }
+    }
+
+    /// Check that a field of view (in degrees) can be rendered with this projection
+    pub fn validate_fov(&self, fov: [f32; 2]) -> Result<(), String> {
+        let (max_fov, inclusive) = self.max_fov();
+        for (axis, (fov, max_fov)) in ["x", "y"].iter().zip(fov.iter().zip(max_fov)) {
+            let within_limit = if inclusive {
+                *fov <= max_fov
+            } else {
+                *fov < max_fov
+            };
+            if !within_limit {
+                return Err(format!(
+                    "fov_{} of {}° exceeds the {}° limit of the {} projection.",
+                    axis,
+                    fov,
+                    max_fov,
+                    self.as_str()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Project a camera frame direction onto the projection plane
+    ///
+    /// Returns `None` for directions that this projection cannot represent.
+    pub fn project(&self, direction: &na::Vector3<f32>) -> Option<na::Point2<f32>> {
+        let direction = direction.try_normalize(f32::EPSILON)?;
+        match self {
+            Projection::Equirectangular => {
+                let (longitude, latitude) = spherical(&direction);
+                Some(na::Point2::new(longitude, latitude))
+            }
+            Projection::HammerAitoff => {
+                let (longitude, latitude) = spherical(&direction);
+                Some(hammer_aitoff(longitude, latitude))
+            }
+            _ => {
+                // Azimuthal projections only depend on the angle off the line of sight
+                let theta = direction.z.clamp(-1.0, 1.0).acos();
+                let radius = self.radius(theta)?;
+                let azimuth = direction.y.atan2(direction.x);
+                Some(na::Point2::new(
+                    radius * azimuth.cos(),
+                    radius * azimuth.sin(),
+                ))
+            }
+        }
+    }
+
+    /// Project a camera frame direction into normalized image coordinates
+    ///
+    /// The field of view (in degrees) spans `[-1, 1]` along both axes, with +y pointing up.
+    /// Directions that fall outside of the field of view are still returned.
+    pub fn image_coordinates(
+        &self,
+        direction: &na::Vector3<f32>,
+        fov: [f32; 2],
+    ) -> Option<na::Point2<f32>> {
+        let point = self.project(direction)?;
+        let extent = self.half_extent(fov)?;
+        Some(na::Point2::new(point.x / extent.x, point.y / extent.y))
+    }
+
+    /// Camera frame unit direction of a point on the projection plane
+    ///
+    /// Inverse of [`Projection::project`]. Returns `None` for points outside of the projection's domain.
+    pub fn unproject(&self, point: &na::Point2<f32>) -> Option<na::Vector3<f32>> {
+        match self {
+            Projection::Equirectangular => {
+                if point.x.abs() > PI || point.y.abs() > FRAC_PI_2 {
+                    return None;
+                }
+                Some(from_spherical(point.x, point.y))
+            }
+            Projection::HammerAitoff => {
+                let z_squared = 1.0 - (point.x / 4.0).powi(2) - (point.y / 2.0).powi(2);
+                // Points beyond the projection's bounding ellipse, allowing for rounding on its edge
+                if z_squared < 0.5 - 1e-5 {
+                    return None;
+                }
+                let z_squared = z_squared.max(0.5);
+                let z = z_squared.sqrt();
+                let longitude = 2.0 * (z * point.x).atan2(2.0 * (2.0 * z_squared - 1.0));
+                let latitude = (z * point.y).clamp(-1.0, 1.0).asin();
+                Some(from_spherical(longitude, latitude))
+            }
+            _ => {
+                let theta = self.inverse_radius(point.coords.norm())?;
+                let azimuth = point.y.atan2(point.x);
+                Some(na::Vector3::new(
+                    theta.sin() * azimuth.cos(),
+                    theta.sin() * azimuth.sin(),
+                    theta.cos(),
+                ))
+            }
+        }
+    }
+
+    /// Camera frame unit direction of a point in normalized image coordinates
+    ///
+    /// Inverse of [`Projection::image_coordinates`].
+    pub fn image_direction(
+        &self,
+        point: &na::Point2<f32>,
+        fov: [f32; 2],
+    ) -> Option<na::Vector3<f32>> {
+        let extent = self.half_extent(fov)?;
+        self.unproject(&na::Point2::new(point.x * extent.x, point.y * extent.y))
+    }
+
+    /// Half-width and half-height of the projection plane covered by a field of view (in degrees)
+    fn half_extent(&self, fov: [f32; 2]) -> Option<na::Vector2<f32>> {
+        let half_fov = [fov[0].to_radians() / 2.0, fov[1].to_radians() / 2.0];
+        match self {
+            Projection::Equirectangular => Some(na::Vector2::new(half_fov[0], half_fov[1])),
+            Projection::HammerAitoff => Some(na::Vector2::new(
+                hammer_aitoff(half_fov[0], 0.0).x,
+                hammer_aitoff(0.0, half_fov[1]).y,
+            )),
+            _ => Some(na::Vector2::new(
+                self.radius(half_fov[0])?,
+                self.radius(half_fov[1])?,
+            )),
+        }
+    }
+
+    /// Radial distance from the centre of an azimuthal projection at `theta` radians off-axis
+    fn radius(&self, theta: f32) -> Option<f32> {
+        match self {
+            Projection::Gnomonic if theta < FRAC_PI_2 => Some(theta.tan()),
+            Projection::Stereographic if theta < PI => Some(2.0 * (theta / 2.0).tan()),
+            Projection::Orthographic if theta <= FRAC_PI_2 => Some(theta.sin()),
+            Projection::AzimuthalEquidistant => Some(theta),
+            _ => None,
+        }
+    }
+
+    /// Angle off-axis at `radius` from the centre of an azimuthal projection
+    fn inverse_radius(&self, radius: f32) -> Option<f32> {
+        match self {
+            Projection::Gnomonic => Some(radius.atan()),
+            Projection::Stereographic => Some(2.0 * (radius / 2.0).atan()),
+            Projection::Orthographic if radius <= 1.0 => Some(radius.asin()),
+            Projection::AzimuthalEquidistant if radius <= PI => Some(radius),
+            _ => None,
+        }
+    }
+}
+
+/// Longitude and latitude (in radians) of a unit camera frame direction
+///
+/// Longitude increases to the right of the line of sight and latitude increases upwards.
+fn spherical(direction: &na::Vector3<f32>) -> (f32, f32) {
+    let longitude = direction.x.atan2(direction.z);
+    let latitude = direction.y.clamp(-1.0, 1.0).asin();
+    (longitude, latitude)
+}
+
+/// Unit camera frame direction of a longitude and latitude (in radians)
+fn from_spherical(longitude: f32, latitude: f32) -> na::Vector3<f32> {
+    na::Vector3::new(
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+        latitude.cos() * longitude.cos(),
+    )
+}
+
+fn hammer_aitoff(longitude: f32, latitude: f32) -> na::Point2<f32> {
+    let denominator = (1.0 + latitude.cos() * (longitude / 2.0).cos()).sqrt();
+    na::Point2::new(
+        2.0 * SQRT_2 * latitude.cos() * (longitude / 2.0).sin() / denominator,
+        SQRT_2 * latitude.sin() / denominator,
+    )
+}
@@ -0,0 +1,109 @@
+use nalgebra as na;
+
+/// Effective temperature in kelvin of the blackbody standing in for stellar spectra, which the
+/// catalog does not record
+pub const SOLAR_TEMPERATURE: f64 = 5772.0;
+/// Second radiation constant `hc / k`, in micrometre kelvins
+const SECOND_RADIATION_CONSTANT: f64 = 14_387.77;
+/// Directions sampled between the front and back of a moving observer
+const BRIGHTENING_SAMPLES: usize = 64;
+
+/// Velocity of an observer relative to the catalog's rest frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    /// Velocity in units of the speed of light
+    beta: na::Vector3<f32>,
+    gamma: f32,
+}
+
+impl Motion {
+    /// Motion at `beta` times the speed of light, or `None` at or beyond the speed of light
+    pub fn new(beta: na::Vector3<f32>) -> Option<Self> {
+        let speed = beta.norm();
+        if !speed.is_finite() || speed >= 1.0 {
+            return None;
+        }
+        Some(Self {
+            beta,
+            gamma: 1.0 / (1.0 - speed * speed).sqrt(),
+        })
+    }
+
+    /// Motion of the catalog's rest frame as seen by the observer
+    pub fn inverse(&self) -> Self {
+        Self {
+            beta: -self.beta,
+            gamma: self.gamma,
+        }
+    }
+
+    /// Apparent unit direction of a source seen in `direction` from the rest frame
+    ///
+    /// Sources crowd towards the direction of motion.
+    pub fn aberrate(&self, direction: &na::Vector3<f32>) -> na::Vector3<f32> {
+        let direction = direction.normalize();
+        let speed = self.beta.norm();
+        if speed == 0.0 {
+            return direction;
+        }
+        let along = direction.dot(&self.beta) / speed;
+        let aberrated =
+            direction + ((self.gamma - 1.0) * along + self.gamma * speed) * self.beta / speed;
+        aberrated.normalize()
+    }
+
+    /// Ratio of observed to emitted frequency of light from a source in rest frame `direction`
+    pub fn doppler_factor(&self, direction: &na::Vector3<f32>) -> f32 {
+        self.gamma * (1.0 + self.beta.dot(&direction.normalize()))
+    }
+
+    /// Change in the magnitude of a source in rest frame `direction`, observed at `wavelength`
+    /// micrometres
+    ///
+    /// The source's spectrum is a blackbody at `temperature` kelvin. Light observed at
+    /// `wavelength` was emitted at a Doppler shifted wavelength, and beaming concentrates the
+    /// flux of sources ahead of the observer.
+    pub fn magnitude_shift(
+        &self,
+        direction: &na::Vector3<f32>,
+        wavelength: f32,
+        temperature: f64,
+    ) -> f32 {
+        let doppler_factor = self.doppler_factor(direction) as f64;
+        let x = SECOND_RADIATION_CONSTANT / (wavelength as f64 * temperature);
+        // Flux per unit wavelength scales as D^3 B(D λ) / B(λ) for a moving observer
+        let log_ratio = -2.0 * doppler_factor.ln() + ln_exp_m1(x) - ln_exp_m1(x / doppler_factor);
+        (-2.5 * log_ratio / std::f64::consts::LN_10) as f32
+    }
+
+    /// Largest decrease in magnitude of any source, see [`Motion::magnitude_shift`]
+    pub fn max_brightening(&self, wavelength: f32, temperature: f64) -> f32 {
+        let speed = self.beta.norm();
+        if speed == 0.0 {
+            return 0.0;
+        }
+        let forward = self.beta / speed;
+        let sideways = forward.cross(&na::Vector3::x());
+        let sideways = if sideways.norm() > 1e-3 {
+            sideways.normalize()
+        } else {
+            forward.cross(&na::Vector3::y()).normalize()
+        };
+        (0..=BRIGHTENING_SAMPLES)
+            .map(|i| {
+                let angle = std::f32::consts::PI * i as f32 / BRIGHTENING_SAMPLES as f32;
+                let direction = angle.cos() * forward + angle.sin() * sideways;
+                -self.magnitude_shift(&direction, wavelength, temperature)
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+/// `ln(e^x - 1)` without overflowing for large `x`
+fn ln_exp_m1(x: f64) -> f64 {
+    if x > 20.0 {
+        x + (-(-x).exp()).ln_1p()
+    } else {
+        x.exp_m1().ln()
+    }
+}
@@ -0,0 +1,188 @@
+use nalgebra as na;
+
+use crate::render::camera::Camera;
+
+/// Orthonormal reference frame built from a render job's fundamental plane
+///
+/// `primary` points towards longitude 0 on the fundamental plane, `secondary` towards
+/// longitude 90° and `pole` towards latitude +90°, forming a right-handed basis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyFrame {
+    pub primary: na::Vector3<f32>,
+    pub secondary: na::Vector3<f32>,
+    pub pole: na::Vector3<f32>,
+}
+
+impl SkyFrame {
+    /// Orthonormalize a pair of fundamental plane basis vectors
+    ///
+    /// The first vector is kept as the primary direction. Returns `None` if the vectors
+    /// are parallel or either of them is zero.
+    pub fn from_basis(basis: &[na::Vector3<f32>; 2]) -> Option<Self> {
+        let primary = basis[0].try_normalize(f32::EPSILON)?;
+        let pole = primary.cross(&basis[1]).try_normalize(f32::EPSILON)?;
+        let secondary = pole.cross(&primary);
+        Some(Self {
+            primary,
+            secondary,
+            pole,
+        })
+    }
+
+    /// Unit vector pointing towards a longitude and latitude (in degrees)
+    pub fn direction(&self, longitude: f32, latitude: f32) -> na::Vector3<f32> {
+        let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+        let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+        cos_lat * cos_lon * self.primary + cos_lat * sin_lon * self.secondary + sin_lat * self.pole
+    }
+
+    /// Axes of the skybox coordinate system used by game engines and cubemaps
+    ///
+    /// The fundamental plane's pole is +y (up) and the primary direction is -z (forward),
+    /// so longitude 90° ends up on the viewer's left, as when looking at the sky from inside.
+    /// Returned as the columns `[x, y, z]`.
+    pub fn skybox_axes(&self) -> [na::Vector3<f32>; 3] {
+        [-self.secondary, self.pole, -self.primary]
+    }
+
+    /// Convert a direction expressed in skybox coordinates back into world coordinates
+    pub fn skybox_to_world(&self, direction: na::Vector3<f32>) -> na::Vector3<f32> {
+        let [x, y, z] = self.skybox_axes();
+        (direction.x * x + direction.y * y + direction.z * z).normalize()
+    }
+}
+
+/// Faces of a skybox cubemap, in the conventional upload order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubemapFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubemapFace {
+    pub const ALL: [CubemapFace; 6] = [
+        CubemapFace::PositiveX,
+        CubemapFace::NegativeX,
+        CubemapFace::PositiveY,
+        CubemapFace::NegativeY,
+        CubemapFace::PositiveZ,
+        CubemapFace::NegativeZ,
+    ];
+
+    /// Right, up and forward directions of the face in skybox coordinates
+    ///
+    /// Faces follow the OpenGL cubemap convention, with pixel rows running from the top of the
+    /// face downwards.
+    pub fn skybox_basis(&self) -> [na::Vector3<f32>; 3] {
+        let (x, y, z) = (na::Vector3::x(), na::Vector3::y(), na::Vector3::z());
+        match self {
+            CubemapFace::PositiveX => [-z, y, x],
+            CubemapFace::NegativeX => [z, y, -x],
+            CubemapFace::PositiveY => [x, -z, y],
+            CubemapFace::NegativeY => [x, z, -y],
+            CubemapFace::PositiveZ => [x, y, z],
+            CubemapFace::NegativeZ => [-x, y, -z],
+        }
+    }
+
+    /// Suffix used when naming the face's image file
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CubemapFace::PositiveX => "px",
+            CubemapFace::NegativeX => "nx",
+            CubemapFace::PositiveY => "py",
+            CubemapFace::NegativeY => "ny",
+            CubemapFace::PositiveZ => "pz",
+            CubemapFace::NegativeZ => "nz",
+        }
+    }
+}
+
+/// Shape of the rendered output
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RenderMode {
+    /// A single view of `fov` centred on the job's longitude and latitude.
+    #[default]
+    Framed,
+    /// The whole sky as a 2:1 equirectangular panorama. `fov` is ignored.
+    Panorama,
+    /// The whole sky as six square skybox faces. `fov` is ignored.
+    Cubemap,
+}
+
+impl RenderMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RenderMode::Framed => "framed",
+            RenderMode::Panorama => "panorama",
+            RenderMode::Cubemap => "cubemap",
+        }
+    }
+
+    /// Field of view (in degrees) always covered by this render mode, or `None` if it is chosen freely
+    ///
+    /// For cubemaps, this is the field of view of each face.
+    pub fn coverage(&self) -> Option<[f32; 2]> {
+        match self {
+            RenderMode::Framed => None,
+            RenderMode::Panorama => Some([360.0, 180.0]),
+            RenderMode::Cubemap => Some([90.0, 90.0]),
+        }
+    }
+
+    /// Cameras rendering the images of this mode, with the cubemap face of each
+    ///
+    /// `framed` is the camera of the job as a framed render. Panoramas and cubemaps keep its
+    /// position, image dimensions, motion and atmosphere but are oriented by `frame` alone.
+    pub fn cameras(&self, framed: Camera, frame: &SkyFrame) -> Vec<(Option<CubemapFace>, Camera)> {
+        let whole_sky = |camera: Camera| Camera {
+            motion: framed.motion,
+            atmosphere: framed.atmosphere,
+            ..camera
+        };
+        match self {
+            RenderMode::Framed => vec![(None, framed)],
+            RenderMode::Panorama => vec![(
+                None,
+                whole_sky(Camera::panorama(
+                    framed.position,
+                    frame,
+                    framed.image_dimensions,
+                )),
+            )],
+            RenderMode::Cubemap => CubemapFace::ALL
+                .into_iter()
+                .map(|face| {
+                    let camera = Camera::cubemap_face(
+                        framed.position,
+                        frame,
+                        face,
+                        framed.image_dimensions[0],
+                    );
+                    (Some(face), whole_sky(camera))
+                })
+                .collect(),
+        }
+    }
+
+    /// Check that the image dimensions fit this render mode
+    ///
+    /// For cubemaps, `image_dimensions` is the size of each face.
+    pub fn validate_image_dimensions(&self, image_dimensions: [i32; 2]) -> Result<(), String> {
+        match self {
+            RenderMode::Framed => Ok(()),
+            RenderMode::Panorama if image_dimensions[0] != 2 * image_dimensions[1] => {
+                Err("Panorama image_dimensions must have a 2:1 aspect ratio.".into())
+            }
+            RenderMode::Cubemap if image_dimensions[0] != image_dimensions[1] => {
+                Err("Cubemap image_dimensions must be square.".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
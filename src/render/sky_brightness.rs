@@ -0,0 +1,161 @@
+use nalgebra as na;
+
+use crate::catalog::ephemeris;
+use crate::render::ground::{Atmosphere, Site};
+
+/// Effective wavelength of the V band in micrometres, where sky brightness is modelled
+const V_WAVELENGTH: f32 = 0.55;
+/// Moonless zenith brightness of each Bortle class, in V magnitudes per square arcsecond
+const BORTLE_ZENITH_BRIGHTNESS: [f32; 9] =
+    [21.85, 21.65, 21.45, 20.85, 19.75, 18.8, 18.25, 17.75, 17.25];
+/// Altitude of the Sun, in degrees, at which astronomical twilight ends
+const ASTRONOMICAL_TWILIGHT: f32 = -18.0;
+/// Twilight zenith brightness at the end of astronomical twilight, in V magnitudes per square
+/// arcsecond
+const TWILIGHT_ZENITH_BRIGHTNESS: f32 = 25.0;
+/// Brightening of the twilight zenith per degree of solar altitude, in magnitudes
+const TWILIGHT_GRADIENT: f32 = 1.0;
+/// Zenith brightness of the clear daytime sky, in V magnitudes per square arcsecond
+const DAYLIGHT_ZENITH_BRIGHTNESS: f32 = 4.0;
+
+/// Darkness of the night sky above a site
+///
+/// Either a Bortle class or a measured zenith brightness, as from a sky quality meter.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SkyConditions {
+    /// Bortle dark-sky class, from 1 for excellent to 9 for an inner-city sky
+    pub bortle_class: Option<u8>,
+    /// Moonless night sky brightness at the zenith, in V magnitudes per square arcsecond
+    pub zenith_brightness: Option<f32>,
+}
+
+impl SkyConditions {
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.bortle_class, self.zenith_brightness) {
+            (Some(bortle_class), None) => {
+                if !(1..=9).contains(&bortle_class) {
+                    return Err("sky bortle_class must be between 1 and 9.".into());
+                }
+            }
+            (None, Some(zenith_brightness)) => {
+                // From the glare of a city centre to a sky darker than any on the Earth
+                if !(14.0..=23.0).contains(&zenith_brightness) {
+                    return Err(
+                        "sky zenith_brightness must be between 14 and 23 magnitudes per square arcsecond."
+                            .into(),
+                    );
+                }
+            }
+            _ => {
+                return Err(
+                    "sky requires exactly one of bortle_class and zenith_brightness.".into(),
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// Moonless zenith brightness in V magnitudes per square arcsecond
+    pub fn dark_zenith_brightness(&self) -> f32 {
+        match (self.bortle_class, self.zenith_brightness) {
+            (_, Some(zenith_brightness)) => zenith_brightness,
+            (Some(bortle_class), None) => {
+                BORTLE_ZENITH_BRIGHTNESS[(bortle_class.clamp(1, 9) - 1) as usize]
+            }
+            (None, None) => BORTLE_ZENITH_BRIGHTNESS[0],
+        }
+    }
+
+    /// Sky above `site` at its time of observation, lit by the Sun and the Moon
+    pub fn at(&self, site: &Site) -> SkyBrightness {
+        let julian_date = site.julian_date();
+        let sun = site.to_catalog_frame(&ephemeris::sun(julian_date));
+        let moon = site.to_catalog_frame(&ephemeris::moon(julian_date));
+        let elongation = sun.dot(&moon).clamp(-1.0, 1.0).acos().to_degrees();
+        SkyBrightness {
+            dark_zenith_brightness: self.dark_zenith_brightness(),
+            atmosphere: site.atmosphere(),
+            sun,
+            moon,
+            moon_phase_angle: 180.0 - elongation,
+        }
+    }
+}
+
+/// Surface brightness of the sky seen from the ground
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyBrightness {
+    /// Moonless night sky brightness at the zenith, in V magnitudes per square arcsecond
+    pub dark_zenith_brightness: f32,
+    pub atmosphere: Atmosphere,
+    /// Direction of the Sun in catalog coordinates
+    pub sun: na::Vector3<f32>,
+    /// Direction of the Moon in catalog coordinates
+    pub moon: na::Vector3<f32>,
+    /// Angle between the Sun and the Earth seen from the Moon in degrees, 0 at full moon
+    pub moon_phase_angle: f32,
+}
+
+impl SkyBrightness {
+    /// Brightness of the sky in `direction`, in V magnitudes per square arcsecond
+    ///
+    /// Airglow and light pollution brighten towards the horizon as the path through the
+    /// atmosphere lengthens, twilight follows the Sun's altitude and moonlight is scattered
+    /// following Krisciunas and Schaefer (1991). Directions below the horizon see the sky just
+    /// above it.
+    pub fn surface_brightness(&self, direction: &na::Vector3<f32>) -> f32 {
+        let extinction = self.atmosphere.extinction_coefficient(V_WAVELENGTH);
+        let airmass = Atmosphere::airmass(self.atmosphere.altitude(direction));
+        let gradient = airmass * 10f32.powf(-0.4 * extinction * (airmass - 1.0));
+
+        let dark = nanolamberts(self.dark_zenith_brightness) * gradient;
+        let twilight = nanolamberts(self.twilight_zenith_brightness()) * gradient;
+        magnitudes(dark + twilight + self.moonlight(direction, extinction, airmass))
+    }
+
+    /// Brightness of the sky at the zenith lit by the Sun alone, in V magnitudes per square
+    /// arcsecond
+    ///
+    /// A linear fit to the zenith brightness through twilight, capped at daylight.
+    pub fn twilight_zenith_brightness(&self) -> f32 {
+        let sun_altitude = self.atmosphere.altitude(&self.sun);
+        let brightness =
+            TWILIGHT_ZENITH_BRIGHTNESS - TWILIGHT_GRADIENT * (sun_altitude - ASTRONOMICAL_TWILIGHT);
+        brightness.max(DAYLIGHT_ZENITH_BRIGHTNESS)
+    }
+
+    /// Moonlight scattered towards `direction`, in nanolamberts
+    fn moonlight(&self, direction: &na::Vector3<f32>, extinction: f32, airmass: f32) -> f32 {
+        let moon_altitude = self.atmosphere.altitude(&self.moon);
+        if moon_altitude < 0.0 {
+            return 0.0;
+        }
+        let phase_angle = self.moon_phase_angle.abs();
+        // Illuminance of the Moon outside the atmosphere, in foot-candles
+        let illuminance =
+            10f32.powf(-0.4 * (3.84 + 0.026 * phase_angle + 4e-9 * phase_angle.powi(4)));
+        let separation = direction
+            .normalize()
+            .dot(&self.moon)
+            .clamp(-1.0, 1.0)
+            .acos();
+        // Rayleigh and Mie scattering at the separation from the Moon
+        let scattering = 10f32.powf(5.36) * (1.06 + separation.cos().powi(2))
+            + 10f32.powf(6.15 - separation.to_degrees() / 40.0);
+        let moon_airmass = Atmosphere::airmass(moon_altitude);
+        scattering
+            * illuminance
+            * 10f32.powf(-0.4 * extinction * moon_airmass)
+            * (1.0 - 10f32.powf(-0.4 * extinction * airmass))
+    }
+}
+
+/// Convert V magnitudes per square arcsecond to nanolamberts
+fn nanolamberts(surface_brightness: f32) -> f32 {
+    34.08 * (20.7233 - 0.92104 * surface_brightness).exp()
+}
+
+/// Convert nanolamberts to V magnitudes per square arcsecond
+fn magnitudes(nanolamberts: f32) -> f32 {
+    (20.7233 - (nanolamberts / 34.08).ln()) / 0.92104
+}
@@ -0,0 +1,192 @@
+use nalgebra as na;
+
+use crate::catalog::ephemeris::{Body, ASTRONOMICAL_UNIT};
+use crate::render::camera::Camera;
+use crate::render::ground::Site;
+use crate::render::image::Image;
+use crate::render::optics::PsfKernel;
+use crate::render::stellar_disk::LimbDarkening;
+use crate::render::{camera_magnitudes, flux};
+
+/// Kilometres per parsec
+const PARSEC_KM: f64 = 3.085_677_581e13;
+/// Apparent radius in pixels above which bodies are drawn as disks rather than through the PSF
+const MIN_RESOLVED_RADIUS: f32 = 2.0;
+
+/// Positions of the solar system's major bodies at one instant
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolarSystem {
+    pub julian_date: f64,
+    /// Positions in catalog coordinates, in parsecs, in the order of [`Body::ALL`]
+    pub positions: Vec<na::Vector3<f32>>,
+}
+
+impl SolarSystem {
+    pub fn at(julian_date: f64) -> Self {
+        Self {
+            julian_date,
+            positions: Body::ALL
+                .iter()
+                .map(|body| body.position(julian_date))
+                .collect(),
+        }
+    }
+
+    pub fn position(&self, body: Body) -> na::Vector3<f32> {
+        let index = Body::ALL.iter().position(|&other| other == body).unwrap();
+        self.positions[index]
+    }
+
+    /// Position of an observer standing at `site`, in catalog coordinates
+    pub fn site_position(&self, site: &Site) -> na::Vector3<f32> {
+        let height = (Body::Earth.radius() + site.elevation as f64 / 1000.0) / PARSEC_KM;
+        let zenith = site.atmosphere().zenith.cast::<f64>();
+        (self.position(Body::Earth).cast::<f64>() + zenith * height).cast()
+    }
+
+    /// Angle between the Sun and `observer` seen from `body`, in degrees
+    pub fn phase_angle(&self, body: Body, observer: &na::Vector3<f32>) -> f64 {
+        let position = self.position(body).cast::<f64>();
+        let to_sun = self.position(Body::Sun).cast::<f64>() - position;
+        let to_observer = observer.cast::<f64>() - position;
+        to_sun.angle(&to_observer).to_degrees()
+    }
+
+    /// Visual magnitude of `body` seen from `observer`
+    pub fn apparent_magnitude(&self, body: Body, observer: &na::Vector3<f32>) -> f32 {
+        let position = self.position(body).cast::<f64>();
+        let sun_distance = (self.position(Body::Sun).cast::<f64>() - position).norm();
+        let observer_distance = (observer.cast::<f64>() - position).norm();
+        body.magnitude(
+            sun_distance / ASTRONOMICAL_UNIT,
+            observer_distance / ASTRONOMICAL_UNIT,
+            self.phase_angle(body, observer),
+        )
+    }
+}
+
+/// Add the bodies of `solar_system` seen through `camera` to `image`
+///
+/// Bodies are drawn with `kernel` while they are smaller than a few pixels, and otherwise as
+/// disks lit by the Sun, with the phase dependent magnitude of the whole body. The Sun itself is
+/// limb darkened. The body the camera stands on is left out. `kernel` should be for light of
+/// `wavelength` micrometres.
+pub fn render_solar_system(
+    image: &mut Image,
+    camera: &Camera,
+    solar_system: &SolarSystem,
+    kernel: &PsfKernel,
+    wavelength: f32,
+) {
+    for body in Body::ALL {
+        let position = solar_system.position(body);
+        let distance = (position - camera.position).cast::<f64>().norm();
+        let radius = body.radius() / PARSEC_KM;
+        if distance <= radius {
+            continue;
+        }
+        let Some(center) = camera.pixel_coordinates(&position) else {
+            continue;
+        };
+        let magnitude = solar_system.apparent_magnitude(body, &camera.position)
+            + camera_magnitudes(camera, &position, wavelength);
+        let angular_radius = (radius / distance).asin() as f32;
+        let pixel_radius = angular_radius / camera.angular_resolution();
+        if pixel_radius < MIN_RESOLVED_RADIUS {
+            image.draw(kernel, &center, flux(magnitude));
+            continue;
+        }
+        let Some(direction) = camera.apparent_direction(&position) else {
+            continue;
+        };
+        let sunlight = match body {
+            Body::Sun => None,
+            _ => Some(
+                (solar_system.position(Body::Sun) - position)
+                    .cast::<f64>()
+                    .normalize(),
+            ),
+        };
+        let disk = Disk {
+            center: direction.cast::<f64>().normalize(),
+            radius: radius / distance,
+            sunlight,
+            limb_darkening: LimbDarkening::solar(wavelength),
+        };
+        draw_disk(
+            image,
+            camera,
+            &disk,
+            &center,
+            pixel_radius,
+            solar_system.phase_angle(body, &camera.position),
+            flux(magnitude),
+        );
+    }
+}
+
+/// Sphere seen from the camera
+struct Disk {
+    /// Apparent direction of the centre
+    center: na::Vector3<f64>,
+    /// Radius of the sphere moved to unit distance, the sine of its angular radius
+    radius: f64,
+    /// Direction of the Sun from the sphere, `None` for a self-luminous sphere
+    sunlight: Option<na::Vector3<f64>>,
+    /// Darkening of a self-luminous sphere towards its limb
+    limb_darkening: LimbDarkening,
+}
+
+impl Disk {
+    /// Brightness of the sphere in apparent `direction`, 0 off the sphere or on its night side
+    fn brightness(&self, direction: &na::Vector3<f64>) -> f64 {
+        let direction = direction.normalize();
+        // Squared sine of the angle from the centre, accurate for tiny disks
+        let offset = direction.cross(&self.center).norm_squared();
+        let discriminant = self.radius.powi(2) - offset;
+        if discriminant < 0.0 || direction.dot(&self.center) <= 0.0 {
+            return 0.0;
+        }
+        let Some(sunlight) = self.sunlight else {
+            return self
+                .limb_darkening
+                .intensity((offset / self.radius.powi(2)).sqrt());
+        };
+        // Surface normal where the line of sight meets the unit distance sphere
+        let distance = direction.dot(&self.center) - discriminant.sqrt();
+        let normal = (distance * direction - self.center) / self.radius;
+        normal.dot(&sunlight).max(0.0)
+    }
+
+    /// Total brightness over the unit disk, for a Lambertian sphere at `phase_angle` degrees
+    fn total_brightness(&self, phase_angle: f64) -> f64 {
+        if self.sunlight.is_none() {
+            return self.limb_darkening.total_intensity();
+        }
+        let phase_angle = phase_angle.to_radians();
+        2.0 / 3.0 * (phase_angle.sin() + (std::f64::consts::PI - phase_angle) * phase_angle.cos())
+    }
+}
+
+/// Spread `flux` over the disk around pixel `center`, `pixel_radius` pixels wide
+///
+/// The flux is normalized over the whole disk, so disks cut by the edges of the image lose the
+/// light of their hidden part.
+fn draw_disk(
+    image: &mut Image,
+    camera: &Camera,
+    disk: &Disk,
+    center: &na::Point2<f32>,
+    pixel_radius: f32,
+    phase_angle: f64,
+    flux: f32,
+) {
+    let total_brightness = disk.total_brightness(phase_angle);
+    if total_brightness <= 0.0 {
+        return;
+    }
+    let scale = flux as f64 / (total_brightness * (pixel_radius as f64).powi(2));
+    image.draw_extended(camera, center, pixel_radius, scale, |direction| {
+        disk.brightness(direction)
+    });
+}
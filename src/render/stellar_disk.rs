@@ -0,0 +1,160 @@
+use std::f64::consts::PI;
+
+use nalgebra as na;
+
+use crate::catalog::Star;
+use crate::render::camera::Camera;
+use crate::render::image::Image;
+use crate::render::optics::PsfKernel;
+
+/// Apparent radius in pixels above which stars are drawn as disks rather than through the PSF
+const MIN_RESOLVED_RADIUS: f32 = 2.0;
+/// Fraction of a resolved star's light scattered by the optics into a glare halo
+const GLARE_FRACTION: f32 = 0.01;
+/// Slope of the glare halo, as the `beta` of a Moffat profile
+const GLARE_BETA: f32 = 2.5;
+/// Approximate linear limb darkening coefficients of the Sun, by wavelength in micrometres
+const SOLAR_LIMB_DARKENING: [(f32, f32); 7] = [
+    (0.35, 0.87),
+    (0.40, 0.82),
+    (0.45, 0.77),
+    (0.55, 0.64),
+    (0.70, 0.55),
+    (1.00, 0.44),
+    (2.00, 0.27),
+];
+
+/// Linear limb darkening law, `I(μ) = 1 - u (1 - μ)`
+///
+/// `μ` is the cosine of the angle between the line of sight and the stellar surface, so disks
+/// fade from their centre towards their limb.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimbDarkening {
+    /// The coefficient `u`, 0 for a uniform disk
+    pub coefficient: f32,
+}
+
+impl LimbDarkening {
+    /// Limb darkening of a Sun-like star at `wavelength` micrometres
+    pub fn solar(wavelength: f32) -> Self {
+        let (first, last) = (
+            SOLAR_LIMB_DARKENING[0],
+            SOLAR_LIMB_DARKENING[SOLAR_LIMB_DARKENING.len() - 1],
+        );
+        let coefficient = if wavelength <= first.0 {
+            first.1
+        } else if wavelength >= last.0 {
+            last.1
+        } else {
+            SOLAR_LIMB_DARKENING
+                .windows(2)
+                .find(|pair| wavelength <= pair[1].0)
+                .map(|pair| {
+                    let t = (wavelength - pair[0].0) / (pair[1].0 - pair[0].0);
+                    pair[0].1 + t * (pair[1].1 - pair[0].1)
+                })
+                .unwrap_or(last.1)
+        };
+        Self { coefficient }
+    }
+
+    /// Intensity at `radius`, in units of the disk's radius, relative to the centre of the disk
+    pub fn intensity(&self, radius: f64) -> f64 {
+        if radius > 1.0 {
+            return 0.0;
+        }
+        let mu = (1.0 - radius * radius).sqrt();
+        1.0 - self.coefficient as f64 * (1.0 - mu)
+    }
+
+    /// Integral of [`LimbDarkening::intensity`] over the unit disk
+    pub fn total_intensity(&self) -> f64 {
+        PI * (1.0 - self.coefficient as f64 / 3.0)
+    }
+}
+
+/// Star close enough to the camera to appear as a disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct StellarDisk {
+    /// Identifier of the star
+    pub id: u64,
+    /// Apparent direction of the centre
+    pub direction: na::Vector3<f64>,
+    /// Sine of the angular radius
+    pub radius: f64,
+    /// Distance from the camera in parsecs
+    pub distance: f32,
+    /// Angular radius in pixels
+    pub pixel_radius: f32,
+}
+
+impl StellarDisk {
+    /// Disk of `star` seen through `camera`, or `None` if it is smaller than a few pixels, out
+    /// of sight, or around the camera
+    pub fn resolve(camera: &Camera, star: &Star) -> Option<Self> {
+        let distance = (star.position - camera.position).norm();
+        let radius = star.physical_radius();
+        if distance <= radius {
+            return None;
+        }
+        let sine = radius as f64 / distance as f64;
+        let pixel_radius = sine.asin() as f32 / camera.angular_resolution();
+        if pixel_radius < MIN_RESOLVED_RADIUS {
+            return None;
+        }
+        let direction = camera.apparent_direction(&star.position)?.cast();
+        Some(Self {
+            id: star.id,
+            direction,
+            radius: sine,
+            distance,
+            pixel_radius,
+        })
+    }
+
+    /// Whether a source at `position` is hidden behind the disk
+    pub fn occults(&self, camera: &Camera, position: &na::Vector3<f32>) -> bool {
+        if (position - camera.position).norm() <= self.distance {
+            return false;
+        }
+        camera
+            .apparent_direction(position)
+            .map_or(false, |direction| self.contains(&direction.cast()))
+    }
+
+    fn contains(&self, direction: &na::Vector3<f64>) -> bool {
+        direction.dot(&self.direction) > 0.0
+            && direction.cross(&self.direction).norm_squared() <= self.radius.powi(2)
+    }
+
+    /// Spread `flux` of light at `wavelength` micrometres over the disk around pixel `center`
+    ///
+    /// The disk keeps all but a small fraction of the light, which the optics scatter into a
+    /// glare halo whose brightness follows the star's and whose width follows its disk. The flux
+    /// is normalized over the whole disk, so disks cut by the edges of the image lose the light
+    /// of their hidden part.
+    pub fn draw(
+        &self,
+        image: &mut Image,
+        camera: &Camera,
+        center: &na::Point2<f32>,
+        wavelength: f32,
+        flux: f32,
+    ) {
+        let limb_darkening = LimbDarkening::solar(wavelength);
+        let scale = (1.0 - GLARE_FRACTION) as f64 * flux as f64
+            / (limb_darkening.total_intensity() * (self.pixel_radius as f64).powi(2));
+        image.draw_extended(camera, center, self.pixel_radius, scale, |direction| {
+            if direction.dot(&self.direction) <= 0.0 {
+                return 0.0;
+            }
+            limb_darkening.intensity(direction.cross(&self.direction).norm() / self.radius)
+        });
+
+        let glare = PsfKernel::Moffat {
+            alpha: self.pixel_radius,
+            beta: GLARE_BETA,
+        };
+        image.draw(&glare, center, GLARE_FRACTION * flux);
+    }
+}
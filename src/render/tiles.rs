@@ -0,0 +1,180 @@
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage};
+use uuid::Uuid;
+
+use crate::render::derivatives::{display_stretch, encode_jpeg};
+use crate::render::image::Image;
+use crate::storage::render_key;
+
+/// Side of the square tiles of pyramids, in pixels
+pub const TILE_SIZE: u32 = 256;
+
+/// What a render job produces
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Output {
+    /// A single image at full resolution.
+    #[default]
+    Image,
+    /// A Deep Zoom tile pyramid, for viewers that load the part of a huge render on screen.
+    Tiles,
+}
+
+impl Output {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Output::Image => "image",
+            Output::Tiles => "tiles",
+        }
+    }
+}
+
+impl TryFrom<&str> for Output {
+    type Error = String;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "image" => Ok(Output::Image),
+            "tiles" => Ok(Output::Tiles),
+            other => Err(format!("{} is not a render output.", other)),
+        }
+    }
+}
+
+/// Layout of the Deep Zoom pyramid of an image
+///
+/// Level 0 is a single pixel and each level doubles the resolution of the previous one, up to the
+/// full resolution at [`TilePyramid::max_level`]. Levels are cut into [`TILE_SIZE`] tiles from
+/// their top left corner, without overlap, so the level, column and row of a tile are also its
+/// XYZ coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TilePyramid {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl TilePyramid {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn max_level(&self) -> u32 {
+        let side = self.width.max(self.height).max(1);
+        u32::BITS - (side - 1).leading_zeros()
+    }
+
+    /// Dimensions of the image at `level`
+    pub fn level_dimensions(&self, level: u32) -> (u32, u32) {
+        let scale = 1u64 << (self.max_level() - level.min(self.max_level()));
+        let scaled = |dimension: u32| ((dimension as u64 + scale - 1) / scale).max(1) as u32;
+        (scaled(self.width), scaled(self.height))
+    }
+
+    /// Columns and rows of tiles at `level`
+    pub fn tile_counts(&self, level: u32) -> (u32, u32) {
+        let (width, height) = self.level_dimensions(level);
+        (
+            (width + TILE_SIZE - 1) / TILE_SIZE,
+            (height + TILE_SIZE - 1) / TILE_SIZE,
+        )
+    }
+
+    /// Keys of the descriptor at `descriptor_key` and of every tile next to it
+    pub fn keys(&self, descriptor_key: &str) -> Vec<String> {
+        let mut keys = vec![descriptor_key.to_string()];
+        for level in 0..=self.max_level() {
+            let (columns, rows) = self.tile_counts(level);
+            for column in 0..columns {
+                for row in 0..rows {
+                    keys.push(tile_key(descriptor_key, level, column, row));
+                }
+            }
+        }
+        keys
+    }
+
+    /// Deep Zoom descriptor of the pyramid, read by viewers such as OpenSeadragon
+    pub fn descriptor(&self) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Image xmlns="http://schemas.microsoft.com/deepzoom/2008" Format="jpg" Overlap="0" TileSize="{}">
+  <Size Width="{}" Height="{}"/>
+</Image>
+"#,
+            TILE_SIZE, self.width, self.height
+        )
+    }
+}
+
+/// Key of the Deep Zoom descriptor of a render job
+pub fn descriptor_key(render_id: Uuid) -> String {
+    render_key(render_id, "image.dzi")
+}
+
+/// Key of a tile, following the Deep Zoom layout next to the descriptor at `descriptor_key`
+pub fn tile_key(descriptor_key: &str, level: u32, column: u32, row: u32) -> String {
+    format!(
+        "{}_files/{}/{}_{}.jpg",
+        descriptor_key
+            .strip_suffix(".dzi")
+            .unwrap_or(descriptor_key),
+        level,
+        column,
+        row
+    )
+}
+
+/// Encoded tile of a pyramid
+#[derive(Debug, Clone)]
+pub struct Tile {
+    pub level: u32,
+    pub column: u32,
+    pub row: u32,
+    pub body: Vec<u8>,
+}
+
+/// Levels of the pyramid of `image`, from the full resolution down to a single pixel
+///
+/// Each level is downsampled from the previous one, so a whole pyramid costs little more than
+/// its full resolution level. Images are stretched for display as their previews are.
+pub fn levels(image: &Image) -> impl Iterator<Item = (u32, GrayImage)> {
+    let pyramid = TilePyramid::new(image.width, image.height);
+    let mut current = Some(display_stretch(image));
+    (0..=pyramid.max_level()).rev().map_while(move |level| {
+        let level_image = current.take()?;
+        if level > 0 {
+            let (width, height) = pyramid.level_dimensions(level - 1);
+            current = Some(imageops::resize(
+                &level_image,
+                width,
+                height,
+                FilterType::Triangle,
+            ));
+        }
+        Some((level, level_image))
+    })
+}
+
+/// Cut `level_image`, the image at `level` of a pyramid, into JPEG tiles
+pub fn tiles(level: u32, level_image: &GrayImage) -> Result<Vec<Tile>, String> {
+    let mut tiles = vec![];
+    for row in 0..(level_image.height() + TILE_SIZE - 1) / TILE_SIZE {
+        for column in 0..(level_image.width() + TILE_SIZE - 1) / TILE_SIZE {
+            // Edge tiles are cropped to the image
+            let tile = imageops::crop_imm(
+                level_image,
+                column * TILE_SIZE,
+                row * TILE_SIZE,
+                TILE_SIZE,
+                TILE_SIZE,
+            )
+            .to_image();
+            tiles.push(Tile {
+                level,
+                column,
+                row,
+                body: encode_jpeg(&DynamicImage::ImageLuma8(tile))?,
+            });
+        }
+    }
+    Ok(tiles)
+}
@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::render::tiles::{Output, TilePyramid};
+use crate::routes::images::{image_format_key, ImageFormat};
+use crate::storage::{ArtifactStorage, StorageError};
+
+/// Key of the Postgres advisory lock held by the instance running the purge
+pub const PURGE_LOCK: i64 = 0x7075_7267_6500;
+
+/// How long renders are kept in each status, in hours
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Queued renders that no worker finished are deleted after this long
+    pub queued_hours: i64,
+    /// Finished renders lose their artifacts after this long, or when they expire if sooner,
+    /// leaving a tombstone
+    pub finished_hours: i64,
+    /// Tombstones of expired renders are deleted after this long
+    pub expired_hours: i64,
+    /// Time between two purges
+    pub purge_interval_minutes: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            queued_hours: 7 * 24,
+            finished_hours: 30 * 24,
+            expired_hours: 90 * 24,
+            purge_interval_minutes: 60,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.queued_hours <= 0 || self.finished_hours <= 0 || self.expired_hours <= 0 {
+            return Err("Retention periods must be positive.".into());
+        }
+        if self.purge_interval_minutes == 0 {
+            return Err("The purge interval must be positive.".into());
+        }
+        Ok(())
+    }
+}
+
+/// Counts of what a purge removed, as recorded in the `purges` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PurgeReport {
+    /// Queued renders deleted
+    pub queued_deleted: i32,
+    /// Finished renders whose artifacts were removed, leaving a tombstone
+    pub renders_expired: i32,
+    /// Files deleted from storage
+    pub files_deleted: i32,
+    /// Tombstones deleted
+    pub tombstones_deleted: i32,
+}
+
+#[derive(Debug)]
+pub enum PurgeError {
+    Database(sqlx::Error),
+}
+
+impl std::fmt::Display for PurgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurgeError::Database(e) => write!(f, "Failed to purge renders: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PurgeError {}
+
+impl From<sqlx::Error> for PurgeError {
+    fn from(e: sqlx::Error) -> Self {
+        PurgeError::Database(e)
+    }
+}
+
+/// Remove the renders and artifacts that outlived `policy` at `now`
+///
+/// Renders are purged in a single transaction holding an advisory lock, so only one instance
+/// purges at a time. Returns `None` without removing anything while another instance holds the
+/// lock. Their files are deleted once the transaction is committed, so a failure leaves orphaned
+/// files rather than renders pointing at missing ones. Files shared with renders reused from the
+/// cache are kept until none of them is left.
+#[tracing::instrument(name = "Purging old renders", skip(db_pool, storage))]
+pub async fn purge(
+    db_pool: &PgPool,
+    storage: &dyn ArtifactStorage,
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Result<Option<PurgeReport>, PurgeError> {
+    let mut transaction = db_pool.begin().await?;
+    let locked = sqlx::query!(
+        r#"SELECT pg_try_advisory_xact_lock($1) AS "locked!""#,
+        PURGE_LOCK
+    )
+    .fetch_one(&mut transaction)
+    .await?
+    .locked;
+    if !locked {
+        tracing::info!("Another instance is purging renders");
+        return Ok(None);
+    }
+
+    let mut report = PurgeReport::default();
+    let mut unused_keys = vec![];
+    let expiring = sqlx::query!(
+        r#"
+        SELECT id, image_key AS "image_key!", output, image_dimension_x, image_dimension_y
+        FROM renders
+        WHERE purged_at IS NULL
+            AND image_key IS NOT NULL
+            AND (expires_at <= $1 OR created_at <= $2)
+        "#,
+        now,
+        now - Duration::hours(policy.finished_hours),
+    )
+    .fetch_all(&mut transaction)
+    .await?;
+    for render in expiring {
+        let image_keys = image_keys(
+            &render.image_key,
+            &render.output,
+            render.image_dimension_x,
+            render.image_dimension_y,
+        );
+        let artifact_keys: Vec<String> = sqlx::query!(
+            "DELETE FROM artifacts WHERE render_id = $1 RETURNING storage_key",
+            render.id
+        )
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .map(|artifact| artifact.storage_key)
+        .collect();
+        sqlx::query!(
+            "UPDATE renders SET purged_at = $1, expires_at = LEAST(expires_at, $1) WHERE id = $2",
+            now,
+            render.id
+        )
+        .execute(&mut transaction)
+        .await?;
+        report.renders_expired += 1;
+
+        if !image_shared(&mut transaction, &render.image_key).await? {
+            unused_keys.extend(image_keys);
+        }
+        for key in artifact_keys {
+            if !artifact_shared(&mut transaction, &key).await? {
+                unused_keys.push(key);
+            }
+        }
+    }
+
+    report.queued_deleted = sqlx::query!(
+        "DELETE FROM renders WHERE image_key IS NULL AND created_at <= $1",
+        now - Duration::hours(policy.queued_hours),
+    )
+    .execute(&mut transaction)
+    .await?
+    .rows_affected() as i32;
+    report.tombstones_deleted = sqlx::query!(
+        "DELETE FROM renders WHERE purged_at <= $1",
+        now - Duration::hours(policy.expired_hours),
+    )
+    .execute(&mut transaction)
+    .await?
+    .rows_affected() as i32;
+    transaction.commit().await?;
+
+    report.files_deleted = delete_files(storage, &unused_keys).await.deleted;
+    sqlx::query!(
+        r#"
+        INSERT INTO purges (
+            id, ran_at, queued_deleted, renders_expired, files_deleted, tombstones_deleted
+        ) VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        Uuid::new_v4(),
+        now,
+        report.queued_deleted,
+        report.renders_expired,
+        report.files_deleted,
+        report.tombstones_deleted,
+    )
+    .execute(db_pool)
+    .await?;
+    tracing::info!("Purged renders: {:?}", report);
+    Ok(Some(report))
+}
+
+/// Keys of the files of a finished render, from its `image_key`, output and image dimensions
+pub(crate) fn image_keys(image_key: &str, output: &str, width: i32, height: i32) -> Vec<String> {
+    match Output::try_from(output) {
+        Ok(Output::Tiles) => TilePyramid::new(width as u32, height as u32).keys(image_key),
+        _ => vec![
+            image_format_key(image_key, ImageFormat::Png),
+            image_format_key(image_key, ImageFormat::Fits),
+        ],
+    }
+}
+
+/// Whether a render that is not purged still serves the image at `image_key`
+pub(crate) async fn image_shared(
+    transaction: &mut Transaction<'_, Postgres>,
+    image_key: &str,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query!(
+        r#"SELECT EXISTS(SELECT 1 FROM renders WHERE image_key = $1 AND purged_at IS NULL) AS "shared!""#,
+        image_key
+    )
+    .fetch_one(transaction)
+    .await
+    .map(|row| row.shared)
+}
+
+/// Whether another render still records an artifact stored at `key`
+pub(crate) async fn artifact_shared(
+    transaction: &mut Transaction<'_, Postgres>,
+    key: &str,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query!(
+        r#"SELECT EXISTS(SELECT 1 FROM artifacts WHERE storage_key = $1) AS "shared!""#,
+        key
+    )
+    .fetch_one(transaction)
+    .await
+    .map(|row| row.shared)
+}
+
+/// Outcome of deleting files from storage
+#[derive(Debug, Default)]
+pub(crate) struct Deletions {
+    /// Files that existed and were deleted
+    pub deleted: i32,
+    /// Keys of the files that could not be deleted
+    pub failed: Vec<String>,
+}
+
+/// Delete the files at `keys`, logging those that fail instead of stopping at them
+pub(crate) async fn delete_files(storage: &dyn ArtifactStorage, keys: &[String]) -> Deletions {
+    let mut deletions = Deletions::default();
+    for key in keys {
+        let deleted = match storage.metadata(key).await {
+            Ok(_) => storage.delete(key).await,
+            Err(StorageError::NotFound(_)) => continue,
+            Err(e) => Err(e),
+        };
+        match deleted {
+            Ok(()) => deletions.deleted += 1,
+            Err(e) => {
+                tracing::warn!("Failed to delete {}: {}", key, e);
+                deletions.failed.push(key.clone());
+            }
+        }
+    }
+    deletions
+}
+
+/// Purge old renders every [`RetentionPolicy::purge_interval_minutes`], until the server stops
+pub fn schedule_purges(
+    db_pool: PgPool,
+    storage: Arc<dyn ArtifactStorage>,
+    policy: RetentionPolicy,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            policy.purge_interval_minutes * 60,
+        ));
+        loop {
+            interval.tick().await;
+            if let Err(e) = purge(&db_pool, storage.as_ref(), &policy, Utc::now()).await {
+                tracing::error!("{}", e);
+            }
+        }
+    })
+}
@@ -0,0 +1,168 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::api_keys::{create_api_key, Quotas};
+use crate::erasure::erase_email;
+use crate::links::LinkSigner;
+use crate::routes::links::{bearer_token, unauthorized};
+use crate::storage::ArtifactStorage;
+
+/// Bearer token of the administration endpoints
+pub struct AdminToken(pub Secret<String>);
+
+impl AdminToken {
+    /// Whether the request carries the token
+    pub fn authorizes(&self, request: &HttpRequest) -> bool {
+        // Comparing digests keeps the time taken independent of the token
+        bearer_token(request).map_or(false, |token| {
+            Sha256::digest(token.as_bytes()) == Sha256::digest(self.0.expose_secret().as_bytes())
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct ErasureRequest {
+    email: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/erasures",
+    request_body = ErasureRequest,
+    responses(
+        (status = 200, description = "Signed report of the erased renders, surveys and files."),
+        (status = 400, description = "Erasure request body malformed."),
+        (status = 401, description = "Missing or invalid admin token.")
+    )
+)]
+#[tracing::instrument(
+    name = "Erasing the data of an email",
+    skip(request, body, db_pool, storage, links, admin_token)
+)]
+pub async fn erase_user_data(
+    request: HttpRequest,
+    body: web::Json<ErasureRequest>,
+    db_pool: web::Data<PgPool>,
+    storage: web::Data<dyn ArtifactStorage>,
+    links: web::Data<LinkSigner>,
+    admin_token: web::Data<AdminToken>,
+) -> HttpResponse {
+    if !admin_token.authorizes(&request) {
+        return unauthorized();
+    }
+    if body.email.trim().is_empty() {
+        return HttpResponse::BadRequest().body("email must not be empty.");
+    }
+    match erase_email(&db_pool, storage.get_ref(), &links, &body.email, Utc::now()).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => {
+            tracing::error!("{}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct ApiKeyRequest {
+    /// Email of the key's owner
+    owner: String,
+    #[serde(flatten)]
+    quotas: Quotas,
+}
+
+#[derive(serde::Serialize)]
+pub struct IssuedApiKey {
+    id: Uuid,
+    owner: String,
+    /// Shown once, as only its hash is stored
+    key: String,
+    #[serde(flatten)]
+    quotas: Quotas,
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/api-keys",
+    request_body = ApiKeyRequest,
+    responses(
+        (status = 201, description = "API key issued, returned only in this response."),
+        (status = 400, description = "API key request body malformed."),
+        (status = 401, description = "Missing or invalid admin token.")
+    )
+)]
+#[tracing::instrument(name = "Issuing an API key", skip(request, body, db_pool, admin_token))]
+pub async fn issue_api_key(
+    request: HttpRequest,
+    body: web::Json<ApiKeyRequest>,
+    db_pool: web::Data<PgPool>,
+    admin_token: web::Data<AdminToken>,
+) -> HttpResponse {
+    if !admin_token.authorizes(&request) {
+        return unauthorized();
+    }
+    if body.owner.trim().is_empty() {
+        return HttpResponse::BadRequest().body("owner must not be empty.");
+    }
+    if let Err(e) = body.quotas.validate() {
+        return HttpResponse::BadRequest().body(e);
+    }
+    match create_api_key(&db_pool, body.owner.trim(), body.quotas).await {
+        Ok((api_key, key)) => HttpResponse::Created().json(IssuedApiKey {
+            id: api_key.id,
+            owner: api_key.owner,
+            key,
+            quotas: api_key.quotas,
+        }),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/api-keys/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the API key")
+    ),
+    responses(
+        (status = 204, description = "API key revoked."),
+        (status = 401, description = "Missing or invalid admin token."),
+        (status = 404, description = "API key unknown or already revoked.")
+    )
+)]
+#[tracing::instrument(
+    name = "Revoking an API key",
+    skip(request, db_pool, admin_token),
+    fields(api_key_id = %api_key_id)
+)]
+pub async fn revoke_api_key(
+    request: HttpRequest,
+    api_key_id: web::Path<Uuid>,
+    db_pool: web::Data<PgPool>,
+    admin_token: web::Data<AdminToken>,
+) -> HttpResponse {
+    if !admin_token.authorizes(&request) {
+        return unauthorized();
+    }
+    match sqlx::query!(
+        "UPDATE api_keys SET revoked_at = $1 WHERE id = $2 AND revoked_at IS NULL",
+        Utc::now(),
+        *api_key_id
+    )
+    .execute(db_pool.get_ref())
+    .await
+    {
+        Ok(result) if result.rows_affected() == 0 => HttpResponse::NotFound().finish(),
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
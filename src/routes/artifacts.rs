@@ -0,0 +1,89 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::links::{artifact_path, LinkSigner};
+use crate::render::derivatives::DerivativeKind;
+use crate::routes::images::serve_artifact;
+use crate::storage::ArtifactStorage;
+
+#[derive(serde::Deserialize)]
+pub struct ArtifactQuery {
+    /// Unix time after which the link stops working
+    expires: Option<i64>,
+    signature: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/renders/{id}/artifacts/{kind}",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the render job"),
+        ("kind" = String, Path, description = "`thumbnail`, `preview_jpeg`, `preview_webp` or `png16`"),
+        ("expires" = i64, Query, description = "Unix time at which the download link expires"),
+        ("signature" = String, Query, description = "HMAC signature of the download link")
+    ),
+    responses(
+        (status = 200, description = "Derivative of the rendered image."),
+        (status = 206, description = "Requested byte range of the derivative."),
+        (status = 304, description = "Cached copy of the derivative is current."),
+        (status = 403, description = "Download link unsigned, forged or expired."),
+        (status = 404, description = "Render job unknown or without this derivative."),
+        (status = 410, description = "Derivative removed after its retention period."),
+        (status = 416, description = "Requested byte range lies outside of the derivative.")
+    )
+)]
+#[tracing::instrument(
+    name = "Serving a render artifact",
+    skip(request, path, query, db_pool, storage, links),
+    fields(render_id = %path.0, kind = %path.1)
+)]
+pub async fn get_render_artifact(
+    request: HttpRequest,
+    path: web::Path<(Uuid, String)>,
+    query: web::Query<ArtifactQuery>,
+    db_pool: web::Data<PgPool>,
+    storage: web::Data<dyn ArtifactStorage>,
+    links: web::Data<LinkSigner>,
+) -> HttpResponse {
+    let (render_id, kind) = path.into_inner();
+    let Ok(kind) = DerivativeKind::try_from(kind.as_str()) else {
+        return HttpResponse::NotFound().finish();
+    };
+    if let Err(e) = links.verify(
+        &artifact_path(render_id, kind),
+        query.expires,
+        query.signature.as_deref(),
+        Utc::now(),
+    ) {
+        return HttpResponse::Forbidden().body(e.to_string());
+    }
+    let artifact = match sqlx::query!(
+        r#"
+        SELECT artifacts.storage_key, renders.expires_at
+        FROM artifacts
+        JOIN renders ON renders.id = artifacts.render_id
+        WHERE artifacts.render_id = $1 AND artifacts.kind = $2
+        "#,
+        render_id,
+        kind.as_str()
+    )
+    .fetch_optional(db_pool.get_ref())
+    .await
+    {
+        Ok(Some(artifact)) => artifact,
+        Ok(None) => return HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    if artifact
+        .expires_at
+        .map_or(false, |expires_at| expires_at <= Utc::now())
+    {
+        return HttpResponse::Gone().finish();
+    }
+    serve_artifact(&request, storage.get_ref(), &artifact.storage_key).await
+}
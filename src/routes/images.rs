@@ -0,0 +1,284 @@
+use std::time::SystemTime;
+
+use actix_web::http::header::{
+    self, Accept, ByteRangeSpec, ContentRange, ContentRangeSpec, ContentType, ETag, EntityTag,
+    Header, HttpDate, IfModifiedSince, IfNoneMatch, LastModified, Quality, Range,
+};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::links::{image_path, LinkSigner};
+use crate::storage::{ArtifactMetadata, ArtifactStorage, StorageError};
+
+/// Formats in which rendered images are stored
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Png,
+    Fits,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Fits => "fits",
+        }
+    }
+
+    /// Media types under which the format is served
+    fn media_types(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            ImageFormat::Png => &[("image", "png")],
+            ImageFormat::Fits => &[("application", "fits"), ("image", "fits")],
+        }
+    }
+
+    /// Specificity with which `media_range` from an `Accept` header matches the format
+    fn matches(&self, media_range: &mime::Mime) -> Option<u8> {
+        let range = (media_range.type_().as_str(), media_range.subtype().as_str());
+        let media_types = self.media_types();
+        if media_types.contains(&range) {
+            Some(2)
+        } else if range.1 == "*" && media_types.iter().any(|(type_, _)| *type_ == range.0) {
+            Some(1)
+        } else if range == ("*", "*") {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct ImageQuery {
+    /// Takes precedence over the `Accept` header
+    format: Option<ImageFormat>,
+    /// Unix time after which the link stops working
+    expires: Option<i64>,
+    signature: Option<String>,
+}
+
+/// Key of the rendered image in `format`, next to the image stored at `image_key`
+pub fn image_format_key(image_key: &str, format: ImageFormat) -> String {
+    let stem = match image_key.rsplit_once('.') {
+        Some((stem, _)) if !stem.ends_with('/') => stem,
+        _ => image_key,
+    };
+    format!("{}.{}", stem, format.extension())
+}
+
+/// Format asked for by the client, PNG if it has no preference
+///
+/// Each format takes the quality of the most specific media range matching it, so an explicit
+/// `q=0` refuses a format even when a wildcard accepts it. Ties go to PNG.
+fn negotiate(request: &HttpRequest, query: &ImageQuery) -> Option<ImageFormat> {
+    if let Some(format) = query.format {
+        return Some(format);
+    }
+    let accept = match Accept::parse(request) {
+        Ok(accept) if !accept.is_empty() => accept,
+        _ => return Some(ImageFormat::Png),
+    };
+    let quality = |format: &ImageFormat| {
+        accept
+            .iter()
+            .filter_map(|range| Some((format.matches(&range.item)?, range.quality)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(Quality::ZERO, |(_, quality)| quality)
+    };
+    let (png, fits) = (quality(&ImageFormat::Png), quality(&ImageFormat::Fits));
+    if png > Quality::ZERO && png >= fits {
+        Some(ImageFormat::Png)
+    } else if fits > Quality::ZERO {
+        Some(ImageFormat::Fits)
+    } else {
+        None
+    }
+}
+
+/// Whether the client's cached copy of the artifact is still current
+fn not_modified(request: &HttpRequest, metadata: &ArtifactMetadata) -> bool {
+    // Validators take precedence over dates
+    if request.headers().contains_key(header::IF_NONE_MATCH) {
+        let etag = EntityTag::new_strong(metadata.etag.clone());
+        return match IfNoneMatch::parse(request) {
+            Ok(IfNoneMatch::Any) => true,
+            Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+            Err(_) => false,
+        };
+    }
+    // HTTP dates have a resolution of one second
+    IfModifiedSince::parse(request).map_or(false, |IfModifiedSince(since)| {
+        SystemTime::from(since)
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(false, |since| {
+                since.as_secs() as i64 >= metadata.last_modified.timestamp()
+            })
+    })
+}
+
+/// Inclusive bounds of the single byte range asked for by the client
+///
+/// `None` serves the whole artifact, as for requests with several ranges, and `Some(None)`
+/// rejects a range lying outside of the artifact.
+fn requested_range(request: &HttpRequest, size: u64) -> Option<Option<(u64, u64)>> {
+    match Range::parse(request) {
+        Ok(Range::Bytes(ranges)) if ranges.len() == 1 => {
+            Some(ByteRangeSpec::to_satisfiable_range(&ranges[0], size))
+        }
+        _ => None,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/renders/{id}/image",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the render job"),
+        ("format" = Option<String>, Query, description = "`png` or `fits`, overriding the Accept header"),
+        ("expires" = i64, Query, description = "Unix time at which the download link expires"),
+        ("signature" = String, Query, description = "HMAC signature of the download link")
+    ),
+    responses(
+        (status = 200, description = "Rendered image."),
+        (status = 206, description = "Requested byte range of the rendered image."),
+        (status = 304, description = "Cached copy of the image is current."),
+        (status = 403, description = "Download link unsigned, forged or expired."),
+        (status = 404, description = "Render job unknown, unfinished or without this format."),
+        (status = 406, description = "No acceptable image format."),
+        (status = 410, description = "Rendered image removed after its retention period."),
+        (status = 416, description = "Requested byte range lies outside of the image.")
+    )
+)]
+#[tracing::instrument(
+    name = "Serving a rendered image",
+    skip(request, query, db_pool, storage, links),
+    fields(render_id = %render_id)
+)]
+pub async fn get_render_image(
+    request: HttpRequest,
+    render_id: web::Path<Uuid>,
+    query: web::Query<ImageQuery>,
+    db_pool: web::Data<PgPool>,
+    storage: web::Data<dyn ArtifactStorage>,
+    links: web::Data<LinkSigner>,
+) -> HttpResponse {
+    if let Err(e) = links.verify(
+        &image_path(*render_id),
+        query.expires,
+        query.signature.as_deref(),
+        Utc::now(),
+    ) {
+        return HttpResponse::Forbidden().body(e.to_string());
+    }
+    let Some(format) = negotiate(&request, &query) else {
+        return HttpResponse::NotAcceptable().body("Images are served as PNG or FITS.");
+    };
+    let render = match sqlx::query!(
+        "SELECT image_key, expires_at FROM renders WHERE id = $1",
+        *render_id
+    )
+    .fetch_optional(db_pool.get_ref())
+    .await
+    {
+        Ok(Some(render)) => render,
+        Ok(None) => return HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    if render
+        .expires_at
+        .map_or(false, |expires_at| expires_at <= Utc::now())
+    {
+        return HttpResponse::Gone().finish();
+    }
+    let Some(image_key) = render.image_key else {
+        return HttpResponse::NotFound().body("The render has not finished.");
+    };
+
+    let key = image_format_key(&image_key, format);
+    let mut response = serve_artifact(&request, storage.get_ref(), &key).await;
+    if response.status() == StatusCode::NOT_FOUND {
+        return HttpResponse::NotFound().body(format!(
+            "The render has no {} image.",
+            format.extension().to_uppercase()
+        ));
+    }
+    response
+        .headers_mut()
+        .insert(header::VARY, header::HeaderValue::from_static("Accept"));
+    response
+}
+
+/// Respond with the artifact at `key`, honouring conditional and range requests
+pub(crate) async fn serve_artifact(
+    request: &HttpRequest,
+    storage: &dyn ArtifactStorage,
+    key: &str,
+) -> HttpResponse {
+    let metadata = match storage.metadata(key).await {
+        Ok(metadata) => metadata,
+        // Keys built from request paths may try to leave the storage's root
+        Err(StorageError::NotFound(_)) | Err(StorageError::InvalidKey(_)) => {
+            return HttpResponse::NotFound().finish()
+        }
+        Err(e) => {
+            tracing::error!("Failed to read artifact metadata: {}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header(ETag(EntityTag::new_strong(metadata.etag.clone())))
+        .insert_header(LastModified(HttpDate::from(SystemTime::from(
+            metadata.last_modified,
+        ))))
+        .insert_header((header::ACCEPT_RANGES, "bytes"));
+    if not_modified(request, &metadata) {
+        return response.status(StatusCode::NOT_MODIFIED).finish();
+    }
+
+    let range = match requested_range(request, metadata.size) {
+        Some(Some(range)) => Some(range),
+        Some(None) => {
+            return HttpResponse::RangeNotSatisfiable()
+                .insert_header(ContentRange(ContentRangeSpec::Bytes {
+                    range: None,
+                    instance_length: Some(metadata.size),
+                }))
+                .finish()
+        }
+        None => None,
+    };
+    let stream = match storage
+        .stream(key, range.map(|(first, last)| first..last + 1))
+        .await
+    {
+        Ok(stream) => stream,
+        Err(e) => {
+            tracing::error!("Failed to read artifact: {}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    response.insert_header(ContentType(metadata.content_type.parse().unwrap()));
+    let length = match range {
+        Some((first, last)) => {
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .insert_header(ContentRange(ContentRangeSpec::Bytes {
+                    range: Some((first, last)),
+                    instance_length: Some(metadata.size),
+                }));
+            last + 1 - first
+        }
+        None => metadata.size,
+    };
+    response.no_chunking(length).streaming(stream)
+}
@@ -0,0 +1,68 @@
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::links::LinkSigner;
+
+/// Token sent as `Authorization: Bearer <token>`
+pub(crate) fn bearer_token(request: &HttpRequest) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Whether the request carries the owner token of `render_id`
+pub(crate) fn is_owner(request: &HttpRequest, links: &LinkSigner, render_id: Uuid) -> bool {
+    bearer_token(request).map_or(false, |token| links.verify_owner_token(render_id, token))
+}
+
+/// Response to requests without the token of the resource they address
+pub(crate) fn unauthorized() -> HttpResponse {
+    HttpResponse::Unauthorized()
+        .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+        .finish()
+}
+
+#[utoipa::path(
+    post,
+    path = "/renders/{id}/links",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the render job")
+    ),
+    responses(
+        (status = 200, description = "Fresh signed download link to the rendered image."),
+        (status = 401, description = "Missing or invalid owner token."),
+        (status = 404, description = "Render job unknown.")
+    )
+)]
+#[tracing::instrument(
+    name = "Issuing a download link",
+    skip(request, db_pool, links),
+    fields(render_id = %render_id)
+)]
+pub async fn issue_download_link(
+    request: HttpRequest,
+    render_id: web::Path<Uuid>,
+    db_pool: web::Data<PgPool>,
+    links: web::Data<LinkSigner>,
+) -> impl Responder {
+    if !is_owner(&request, &links, *render_id) {
+        return unauthorized();
+    }
+    match sqlx::query!("SELECT id FROM renders WHERE id = $1", *render_id)
+        .fetch_optional(db_pool.get_ref())
+        .await
+    {
+        Ok(Some(_)) => HttpResponse::Ok().json(links.image_link(*render_id, Utc::now())),
+        Ok(None) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
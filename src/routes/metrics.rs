@@ -0,0 +1,15 @@
+use actix_web::http::header::ContentType;
+use actix_web::{web, HttpResponse};
+
+use crate::metrics::Metrics;
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses((status = 200, description = "Counters in the Prometheus text format.")),
+)]
+pub async fn get_metrics(metrics: web::Data<Metrics>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body(metrics.exposition())
+}
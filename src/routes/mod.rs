@@ -1,2 +1,10 @@
+pub mod admin;
+pub mod artifacts;
 pub mod health_check;
+pub mod images;
+pub mod links;
+pub mod metrics;
 pub mod renders;
+pub mod status;
+pub mod surveys;
+pub mod tiles;
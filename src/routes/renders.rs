@@ -1,30 +1,34 @@
+use actix_web::http::header;
 use actix_web::{web, HttpResponse, Responder};
 use chrono::Utc;
 use nalgebra as na;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
-// TODO fill this out
-#[derive(serde::Deserialize, Debug)]
-#[allow(non_camel_case_types)]
-enum BroadBandFilter {
-    SDSS_U,
-    SDSS_G,
-    SDSS_R,
-}
-
-impl std::fmt::Display for BroadBandFilter {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
+use crate::api_keys::{ApiKey, ApiKeyError};
+use crate::cache::{cache_key, default_seed, reuse_cached_render};
+use crate::catalog::astrometry::CATALOG_EPOCH;
+use crate::catalog::deep_sky::DeepSkyCatalog;
+use crate::catalog::ephemeris::{self, EPHEMERIS_YEARS};
+use crate::links::{status_path, LinkSigner};
+use crate::metrics::Metrics;
+use crate::render::detector::{Detector, Exposure};
+use crate::render::extinction::{DustGrid, ExtinctionModel};
+use crate::render::filter::{split_filters, validate_filters, AstronomicalFilter};
+use crate::render::ground::Site;
+use crate::render::instrument::InstrumentRegistry;
+use crate::render::optics::Optics;
+use crate::render::projection::Projection;
+use crate::render::relativity::Motion;
+use crate::render::sky::{RenderMode, SkyFrame};
+use crate::render::sky_brightness::SkyConditions;
+use crate::render::tiles::Output;
+use crate::startup::RenderResources;
 
-#[derive(serde::Deserialize)]
-#[serde(untagged)]
-enum AstronomicalFilter {
-    NarrowBand(f32),
-    BroadBand(BroadBandFilter),
-}
+/// Farthest a ground observer can be from the Sun, in parsecs
+const MAX_SITE_DISTANCE: f32 = 1e-3;
+/// Longest span of time, in years, over which stars are propagated from the catalog's epoch
+const MAX_EPOCH_OFFSET: f64 = 10_000_000.0;
 
 #[derive(serde::Deserialize)]
 struct FundamentalPlane {
@@ -32,6 +36,17 @@ struct FundamentalPlane {
 }
 
 impl FundamentalPlane {
+    fn from_frame(frame: &SkyFrame) -> Self {
+        Self {
+            basis: [frame.primary, frame.secondary],
+        }
+    }
+
+    /// Orthonormal frame spanned by the basis, or `None` if the basis vectors are parallel
+    pub fn frame(&self) -> Option<SkyFrame> {
+        SkyFrame::from_basis(&self.basis)
+    }
+
     pub fn basis_vec_1(&self) -> Vec<f32> {
         self.basis[0].data.as_slice().to_vec()
     }
@@ -45,34 +60,309 @@ impl FundamentalPlane {
 #[derive(serde::Deserialize)]
 pub struct RenderJob {
     email: String,
-    fov: [f32; 2],
-    image_dimensions: [i32; 2],
-    fundamental_plane: FundamentalPlane,
+    /// Derived from the instrument when omitted
+    fov: Option<[f32; 2]>,
+    /// Derived from the instrument when omitted
+    image_dimensions: Option<[i32; 2]>,
+    /// Derived from the site when observing from the ground
+    fundamental_plane: Option<FundamentalPlane>,
     observer_position: na::Vector3<f32>,
-    latitude: f32,
-    longitude: f32,
+    /// Velocity of the observer in units of the speed of light
+    observer_velocity: Option<na::Vector3<f32>>,
+    /// Derived from the altitude when observing from the ground
+    latitude: Option<f32>,
+    /// Derived from the azimuth when observing from the ground
+    longitude: Option<f32>,
+    /// Place and time on the Earth to observe from
+    site: Option<Site>,
+    /// Degrees above the horizon to point at from the site
+    altitude: Option<f32>,
+    /// Degrees from north through east to point at from the site
+    azimuth: Option<f32>,
+    /// Darkness of the site's sky, which adds a background to exposures from the ground
+    sky: Option<SkyConditions>,
     filters: Vec<AstronomicalFilter>,
+    #[serde(default)]
+    projection: Projection,
+    #[serde(default)]
+    mode: RenderMode,
+    #[serde(default)]
+    output: Output,
+    optics: Option<Optics>,
+    detector: Option<Detector>,
+    /// Renders without an exposure are noiseless maps of flux
+    exposure: Option<Exposure>,
+    /// Renders without an extinction model see through empty space
+    extinction: Option<ExtinctionModel>,
+    /// Julian year at which to observe the sky, the catalog's epoch when omitted
+    epoch: Option<f64>,
+    /// Show stars where they emitted the light reaching the observer, rather than where they are
+    #[serde(default)]
+    light_travel_time: bool,
+    /// Draw the Sun, the Moon and the planets at the site's time or the epoch
+    #[serde(default)]
+    solar_system: bool,
+    /// Draw the galaxies and nebulae of the deep-sky catalog
+    #[serde(default)]
+    deep_sky: bool,
+    /// Name of a preset telescope and camera combination
+    instrument: Option<String>,
 }
 
 impl RenderJob {
-    pub fn narrowband_filters(&self) -> Vec<f32> {
-        let mut output = vec![];
-        for filter in &self.filters {
-            if let AstronomicalFilter::NarrowBand(wavelength) = filter {
-                output.push(*wavelength);
+    /// Fill in the parameters implied by the render mode and instrument
+    ///
+    /// Parameters given explicitly must agree with the instrument, and the server must have the
+    /// resources the job refers to.
+    pub fn resolve(
+        &mut self,
+        instruments: &InstrumentRegistry,
+        dust_map: Option<&DustGrid>,
+        deep_sky: &DeepSkyCatalog,
+    ) -> Result<(), String> {
+        if self.extinction == Some(ExtinctionModel::DustMap) && dust_map.is_none() {
+            return Err("This server does not have a dust map.".into());
+        }
+        if self.deep_sky && deep_sky.objects.is_empty() {
+            return Err("This server does not have a deep-sky catalog.".into());
+        }
+        if let Some(site) = &self.site {
+            site.validate()?;
+            if self.fundamental_plane.is_some()
+                || self.latitude.is_some()
+                || self.longitude.is_some()
+            {
+                return Err(
+                    "A site is pointed with altitude and azimuth, not fundamental_plane, latitude and longitude."
+                        .into(),
+                );
+            }
+            if self.observer_velocity.is_some() {
+                return Err("observer_velocity cannot be given alongside a site.".into());
+            }
+            if self.observer_position.norm() > MAX_SITE_DISTANCE {
+                return Err("A site's observer_position must be the Sun, at the origin.".into());
             }
+            let (Some(altitude), Some(azimuth)) = (self.altitude, self.azimuth) else {
+                return Err("A site requires an altitude and azimuth to point at.".into());
+            };
+            // Longitude in the horizon frame increases towards the west
+            self.fundamental_plane = Some(FundamentalPlane::from_frame(&site.horizon_frame()));
+            self.latitude = Some(altitude);
+            self.longitude = Some(-azimuth);
+        } else if self.altitude.is_some() || self.azimuth.is_some() {
+            return Err("altitude and azimuth can only be given alongside a site.".into());
+        } else if self.sky.is_some() {
+            return Err("sky can only be given alongside a site.".into());
         }
-        output
+        // Panoramas and cubemaps always cover the whole sky
+        if let Some(coverage) = self.mode.coverage() {
+            self.fov = Some(coverage);
+        }
+        let Some(name) = &self.instrument else {
+            return Ok(());
+        };
+        let instrument = instruments
+            .get(name)
+            .ok_or_else(|| format!("{} is not a known instrument.", name))?;
+        if self.mode != RenderMode::Framed {
+            return Err("Instruments can only be used for framed renders.".into());
+        }
+        if self.optics.is_some() {
+            return Err("optics cannot be given alongside an instrument.".into());
+        }
+        if self.detector.is_some() {
+            return Err("detector cannot be given alongside an instrument.".into());
+        }
+        if let Some(filter) = self
+            .filters
+            .iter()
+            .find(|filter| !instrument.supports_filter(filter))
+        {
+            return Err(format!("{} does not have a {:?} filter.", name, filter));
+        }
+
+        let image_dimensions = *self.image_dimensions.get_or_insert(
+            instrument
+                .detector_dimensions
+                .map(|dimension| dimension as i32),
+        );
+        let fov = *self.fov.get_or_insert(instrument.fov(image_dimensions));
+        instrument.validate_geometry(fov, image_dimensions)?;
+        self.optics = Some(instrument.optics());
+        self.detector = Some(instrument.detector);
+        Ok(())
+    }
+
+    /// Pixels of the resolved job's image
+    pub fn pixels(&self) -> i64 {
+        self.image_dimensions
+            .map_or(0, |[width, height]| width as i64 * height as i64)
     }
 
-    pub fn broadband_filters(&self) -> Vec<String> {
-        let mut output = vec![];
-        for filter in &self.filters {
-            if let AstronomicalFilter::BroadBand(filter_name) = filter {
-                output.push(filter_name.to_string());
+    /// Content address of the resolved job, shared by every job rendering the same image
+    ///
+    /// The email and the instrument's name are left out, as the instrument is already resolved
+    /// into optics and a detector.
+    pub fn cache_key(&self) -> String {
+        cache_key(&[
+            ("fov", format!("{:?}", self.fov)),
+            ("image_dimensions", format!("{:?}", self.image_dimensions)),
+            (
+                "fundamental_plane",
+                format!(
+                    "{:?}",
+                    self.fundamental_plane
+                        .as_ref()
+                        .and_then(FundamentalPlane::frame)
+                ),
+            ),
+            ("observer_position", format!("{:?}", self.observer_position)),
+            ("observer_velocity", format!("{:?}", self.observer_velocity)),
+            ("latitude", format!("{:?}", self.latitude)),
+            (
+                "longitude",
+                format!(
+                    "{:?}",
+                    self.longitude.map(|longitude| longitude.rem_euclid(360.0))
+                ),
+            ),
+            ("site", format!("{:?}", self.site)),
+            ("sky", format!("{:?}", self.sky)),
+            ("filters", format!("{:?}", self.filters)),
+            ("projection", self.projection.as_str().into()),
+            ("mode", self.mode.as_str().into()),
+            ("output", self.output.as_str().into()),
+            ("optics", format!("{:?}", self.optics)),
+            ("detector", format!("{:?}", self.detector)),
+            ("exposure", format!("{:?}", self.exposure)),
+            ("extinction", format!("{:?}", self.extinction)),
+            ("epoch", format!("{:?}", self.epoch)),
+            ("light_travel_time", self.light_travel_time.to_string()),
+            ("solar_system", self.solar_system.to_string()),
+            ("deep_sky", self.deep_sky.to_string()),
+        ])
+    }
+
+    /// Check that the requested render is physically meaningful
+    pub fn validate(&self) -> Result<(), String> {
+        let (Some(fov), Some(image_dimensions)) = (self.fov, self.image_dimensions) else {
+            return Err("fov and image_dimensions are required without an instrument.".into());
+        };
+        if fov.iter().any(|fov| fov.is_nan() || *fov <= 0.0) {
+            return Err("fov values must be positive.".into());
+        }
+        if self.mode == RenderMode::Framed {
+            self.projection.validate_fov(fov)?;
+        }
+        if image_dimensions.iter().any(|dimension| *dimension <= 0) {
+            return Err("image_dimensions values must be positive.".into());
+        }
+        self.mode.validate_image_dimensions(image_dimensions)?;
+        validate_filters(&self.filters)?;
+        if self.observer_position.iter().any(|x| !x.is_finite()) {
+            return Err("observer_position values must be finite.".into());
+        }
+        if self.output == Output::Tiles && self.mode == RenderMode::Cubemap {
+            return Err("Cubemaps cannot be output as tiles.".into());
+        }
+        if let Some(optics) = &self.optics {
+            optics.validate()?;
+        }
+        if let Some(detector) = &self.detector {
+            detector.validate()?;
+        }
+        if let Some(exposure) = &self.exposure {
+            exposure.validate()?;
+            if self.detector.is_none() {
+                return Err("An exposure requires a detector or an instrument.".into());
+            }
+            if self
+                .optics
+                .and_then(|optics| optics.aperture_diameter)
+                .is_none()
+            {
+                return Err("An exposure requires the optics' aperture_diameter.".into());
+            }
+        }
+        if let Some(sky) = &self.sky {
+            sky.validate()?;
+            match &self.exposure {
+                None => return Err("sky requires an exposure.".into()),
+                Some(exposure) if exposure.sky_brightness.is_some() => {
+                    return Err("exposure sky_brightness cannot be given alongside a sky.".into())
+                }
+                Some(_) => {}
             }
         }
-        output
+        let (Some(fundamental_plane), Some(latitude), Some(longitude)) =
+            (&self.fundamental_plane, self.latitude, self.longitude)
+        else {
+            return Err(
+                "fundamental_plane, latitude and longitude are required without a site.".into(),
+            );
+        };
+        if fundamental_plane.frame().is_none() {
+            return Err("fundamental_plane basis vectors must not be parallel.".into());
+        }
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err("latitude must be between -90 and 90 degrees.".into());
+        }
+        if !longitude.is_finite() {
+            return Err("longitude must be a finite number.".into());
+        }
+        if let Some(velocity) = self.observer_velocity {
+            if Motion::new(velocity).is_none() {
+                return Err("observer_velocity must be slower than light.".into());
+            }
+        }
+        if let Some(epoch) = self.epoch {
+            // Stars stray too far from straight lines over longer spans
+            if !((CATALOG_EPOCH - MAX_EPOCH_OFFSET)..=(CATALOG_EPOCH + MAX_EPOCH_OFFSET))
+                .contains(&epoch)
+            {
+                return Err(format!(
+                    "epoch must be within {} years of the catalog's epoch.",
+                    MAX_EPOCH_OFFSET
+                ));
+            }
+        } else if self.light_travel_time {
+            return Err("light_travel_time requires an epoch.".into());
+        }
+        if self.solar_system {
+            let year = match &self.site {
+                Some(site) => ephemeris::julian_year(site.julian_date()),
+                None => self.epoch.unwrap_or(CATALOG_EPOCH),
+            };
+            if !EPHEMERIS_YEARS.contains(&year) {
+                return Err(format!(
+                    "The solar system can only be drawn between {} and {}.",
+                    EPHEMERIS_YEARS.start(),
+                    EPHEMERIS_YEARS.end()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Response to a queued render job
+#[derive(serde::Serialize)]
+pub struct SubmittedRender {
+    id: Uuid,
+    /// Sent as `Authorization: Bearer <token>` to get the job's status and links
+    owner_token: String,
+    /// Path of the job's status, relative to the API's address
+    status_url: String,
+}
+
+impl SubmittedRender {
+    fn new(render_id: Uuid, links: &LinkSigner) -> Self {
+        Self {
+            id: render_id,
+            owner_token: links.owner_token(render_id),
+            status_url: status_path(render_id),
+        }
     }
 }
 
@@ -81,35 +371,108 @@ impl RenderJob {
     path = "/renders",
     request_body = RenderJob,
     responses(
-        (status = 202, description = "Render job successfully queued."),
-        (status = 400, description = "Render job request body malformed.")
+        (status = 202, description = "Render job successfully queued, or completed at once from an identical render, with its identifier, owner token and status URL."),
+        (status = 400, description = "Render job request body malformed."),
+        (status = 401, description = "Missing, unknown or revoked API key."),
+        (status = 429, description = "API key over one of its quotas, see the Retry-After header.")
     )
 )]
-#[tracing::instrument(name = "Inserting new render job into queue", skip(body, db_pool))]
+#[tracing::instrument(
+    name = "Inserting new render job into queue",
+    skip(body, api_key, db_pool, links, resources, metrics),
+    fields(api_key_id = %api_key.id)
+)]
 pub async fn submit_render_request(
     body: web::Json<RenderJob>,
+    api_key: web::ReqData<ApiKey>,
     db_pool: web::Data<PgPool>,
+    links: web::Data<LinkSigner>,
+    resources: web::Data<RenderResources>,
+    metrics: web::Data<Metrics>,
 ) -> impl Responder {
-    // TODO Input validation
-    // -90 <= lat <= 90
-    // fov_x, fov_y > 0
-    // fundamental bases are not parallel vectors
-    // modulo longitude 360
-    // project primary direction onto fundamental plane
-    match insert_render_job(&body, &db_pool).await {
-        Ok(_) => HttpResponse::Accepted().finish(),
-        Err(_) => HttpResponse::InternalServerError().finish(),
+    let mut body = body.into_inner();
+    if let Err(e) = body
+        .resolve(
+            &resources.instruments,
+            resources.dust_map.as_ref(),
+            &resources.deep_sky,
+        )
+        .and_then(|_| body.validate())
+    {
+        tracing::warn!("Rejected render job: {}", e);
+        return HttpResponse::BadRequest().body(e);
     }
+    if let Err(e) = api_key.check_pixels(body.pixels()) {
+        return e.response();
+    }
+    let saved = match save_render_job(&body, &api_key, &db_pool).await {
+        Ok(saved) => saved,
+        Err(e) => return e.response(),
+    };
+    if saved.cached_from.is_some() {
+        Metrics::increment(&metrics.render_cache_hits);
+    } else {
+        Metrics::increment(&metrics.render_cache_misses);
+    }
+    let submitted = SubmittedRender::new(saved.id, &links);
+    HttpResponse::Accepted()
+        .insert_header((header::LOCATION, submitted.status_url.clone()))
+        .json(submitted)
+}
+
+/// Render job saved by [`insert_render_job`]
+pub struct SavedRender {
+    pub id: Uuid,
+    /// Render whose artifacts were reused, on cache hits
+    pub cached_from: Option<Uuid>,
+}
+
+/// Save a render job if its API key's quotas allow one more
+async fn save_render_job(
+    body: &RenderJob,
+    api_key: &ApiKey,
+    db_pool: &PgPool,
+) -> Result<SavedRender, ApiKeyError> {
+    let mut transaction = db_pool.begin().await?;
+    api_key
+        .check_job_quotas(&mut transaction, Utc::now())
+        .await?;
+    let saved = insert_render_job(&mut transaction, body, api_key.id).await?;
+    transaction.commit().await?;
+    Ok(saved)
 }
 
 #[tracing::instrument(
     name = "Saving new render job details in the database",
-    skip(body, db_pool),
+    skip(transaction, body),
     fields(render_id)
 )]
-pub async fn insert_render_job(body: &RenderJob, db_pool: &PgPool) -> Result<(), sqlx::Error> {
+/// Save a render job, completing it at once when an identical render is cached
+pub async fn insert_render_job(
+    transaction: &mut Transaction<'_, Postgres>,
+    body: &RenderJob,
+    api_key_id: Uuid,
+) -> Result<SavedRender, sqlx::Error> {
     let render_id = Uuid::new_v4();
     tracing::Span::current().record("render_id", render_id.to_string());
+    let cache_key = body.cache_key();
+    let fov = body
+        .fov
+        .expect("Render jobs are resolved before being saved");
+    let image_dimensions = body
+        .image_dimensions
+        .expect("Render jobs are resolved before being saved");
+    let fundamental_plane = body
+        .fundamental_plane
+        .as_ref()
+        .expect("Render jobs are resolved before being saved");
+    let latitude = body
+        .latitude
+        .expect("Render jobs are resolved before being saved");
+    let longitude = body
+        .longitude
+        .expect("Render jobs are resolved before being saved");
+    let (narrowband_filters, broadband_filters) = split_filters(&body.filters);
     sqlx::query!(
         r#"
         INSERT INTO renders (
@@ -123,32 +486,116 @@ pub async fn insert_render_job(body: &RenderJob, db_pool: &PgPool) -> Result<(),
             fundamental_plane_basis_vector_1,
             fundamental_plane_basis_vector_2,
             observer_position,
+            observer_velocity,
             latitude,
             longitude,
             narrowband_filters,
-            broadband_filters
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            broadband_filters,
+            projection,
+            render_mode,
+            aperture_diameter,
+            psf_model,
+            psf_fwhm,
+            psf_beta,
+            instrument,
+            exposure_time,
+            sky_brightness,
+            noise_seed,
+            quantum_efficiency,
+            read_noise,
+            dark_current,
+            gain,
+            full_well,
+            bit_depth,
+            extinction,
+            epoch,
+            light_travel_time,
+            site_latitude,
+            site_longitude,
+            site_elevation,
+            observation_time,
+            sky_bortle_class,
+            sky_zenith_brightness,
+            solar_system,
+            deep_sky,
+            output,
+            cache_key,
+            api_key_id
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20,
+            $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34, $35, $36, $37, $38, $39, $40,
+            $41, $42, $43, $44, $45
+        )
         "#,
         render_id,
         Utc::now(),
         body.email,
-        body.fov[0],
-        body.fov[1],
-        body.image_dimensions[0],
-        body.image_dimensions[1],
-        &body.fundamental_plane.basis_vec_1(),
-        &body.fundamental_plane.basis_vec_2(),
+        fov[0],
+        fov[1],
+        image_dimensions[0],
+        image_dimensions[1],
+        &fundamental_plane.basis_vec_1(),
+        &fundamental_plane.basis_vec_2(),
         &body.observer_position.data.as_slice().to_vec(),
-        body.latitude,
-        body.longitude,
-        &body.narrowband_filters(),
-        &body.broadband_filters(),
+        body.observer_velocity
+            .as_ref()
+            .map(|velocity| velocity.data.as_slice()),
+        latitude,
+        longitude.rem_euclid(360.0),
+        &narrowband_filters,
+        &broadband_filters,
+        body.projection.as_str(),
+        body.mode.as_str(),
+        body.optics.and_then(|optics| optics.aperture_diameter),
+        body.optics.map(|optics| optics.psf.as_str()),
+        body.optics.and_then(|optics| optics.psf.fwhm()),
+        body.optics.and_then(|optics| optics.psf.beta()),
+        body.instrument,
+        body.exposure.map(|exposure| exposure.time),
+        body.exposure.and_then(|exposure| exposure.sky_brightness),
+        // Stored bit for bit, Postgres has no unsigned integers
+        body.exposure
+            .map(|exposure| exposure.seed.unwrap_or_else(|| default_seed(&cache_key)) as i64),
+        body.detector.map(|detector| detector.quantum_efficiency),
+        body.detector.map(|detector| detector.read_noise),
+        body.detector.map(|detector| detector.dark_current),
+        body.detector.map(|detector| detector.gain),
+        body.detector.map(|detector| detector.full_well),
+        body.detector.map(|detector| detector.bit_depth as i16),
+        body.extinction.map(|extinction| extinction.as_str()),
+        body.epoch,
+        body.light_travel_time,
+        body.site.map(|site| site.latitude),
+        body.site.map(|site| site.longitude),
+        body.site.map(|site| site.elevation),
+        body.site.map(|site| site.time),
+        body.sky
+            .and_then(|sky| sky.bortle_class)
+            .map(|bortle_class| bortle_class as i16),
+        body.sky.and_then(|sky| sky.zenith_brightness),
+        body.solar_system,
+        body.deep_sky,
+        body.output.as_str(),
+        cache_key,
+        api_key_id,
     )
-    .execute(db_pool)
+    .execute(&mut *transaction)
     .await
     .map_err(|e| {
         tracing::error!("Failed to execute query: {:?}", e);
         e
     })?;
-    Ok(())
+    let cached = reuse_cached_render(transaction, render_id, &cache_key)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to reuse a cached render: {:?}", e);
+            e
+        })?;
+    if let Some(cached) = cached {
+        tracing::info!("Completed the render from the cached render {}", cached);
+    }
+    Ok(SavedRender {
+        id: render_id,
+        cached_from: cached,
+    })
 }
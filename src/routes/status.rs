@@ -0,0 +1,132 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::artifacts::render_artifacts;
+use crate::links::{artifact_path, tiles_path, LinkSigner, SignedLink};
+use crate::render::derivatives::DerivativeKind;
+use crate::render::tiles::Output;
+use crate::routes::links::{is_owner, unauthorized};
+
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Finished,
+    /// Finished, but its artifacts were removed after the retention period
+    Expired,
+}
+
+#[derive(serde::Serialize)]
+pub struct ArtifactStatus {
+    kind: DerivativeKind,
+    content_type: String,
+    width: u32,
+    height: u32,
+    /// Size in bytes
+    size: u64,
+    link: SignedLink,
+}
+
+#[derive(serde::Serialize)]
+pub struct RenderStatus {
+    id: Uuid,
+    status: JobStatus,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    output: Output,
+    /// Link to the full resolution image, once finished with the `image` output
+    image: Option<SignedLink>,
+    /// Link to the Deep Zoom descriptor, once finished with the `tiles` output
+    ///
+    /// Its query string also signs the tiles below it, at `{z}/{x}/{y}`.
+    tiles: Option<SignedLink>,
+    /// Derivatives of the image, once finished
+    artifacts: Vec<ArtifactStatus>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/renders/{id}",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the render job")
+    ),
+    responses(
+        (status = 200, description = "Status of the render job, with links to its artifacts."),
+        (status = 401, description = "Missing or invalid owner token."),
+        (status = 404, description = "Render job unknown.")
+    )
+)]
+#[tracing::instrument(
+    name = "Reporting the status of a render job",
+    skip(request, db_pool, links),
+    fields(render_id = %render_id)
+)]
+pub async fn get_render_status(
+    request: HttpRequest,
+    render_id: web::Path<Uuid>,
+    db_pool: web::Data<PgPool>,
+    links: web::Data<LinkSigner>,
+) -> HttpResponse {
+    let render_id = render_id.into_inner();
+    if !is_owner(&request, &links, render_id) {
+        return unauthorized();
+    }
+    let render = match sqlx::query!(
+        "SELECT created_at, image_key, expires_at, output FROM renders WHERE id = $1",
+        render_id
+    )
+    .fetch_optional(db_pool.get_ref())
+    .await
+    {
+        Ok(Some(render)) => render,
+        Ok(None) => return HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let now = Utc::now();
+    let output = Output::try_from(render.output.as_str()).unwrap_or_default();
+    let status = match (&render.image_key, render.expires_at) {
+        (None, _) => JobStatus::Queued,
+        (Some(_), Some(expires_at)) if expires_at <= now => JobStatus::Expired,
+        (Some(_), _) => JobStatus::Finished,
+    };
+    let artifacts = if status == JobStatus::Finished {
+        match render_artifacts(db_pool.get_ref(), render_id).await {
+            Ok(artifacts) => artifacts,
+            Err(e) => {
+                tracing::error!("Failed to execute query: {:?}", e);
+                return HttpResponse::InternalServerError().finish();
+            }
+        }
+    } else {
+        vec![]
+    };
+
+    HttpResponse::Ok().json(RenderStatus {
+        id: render_id,
+        status,
+        created_at: render.created_at,
+        expires_at: render.expires_at,
+        output,
+        image: (status == JobStatus::Finished && output == Output::Image)
+            .then(|| links.image_link(render_id, now)),
+        tiles: (status == JobStatus::Finished && output == Output::Tiles)
+            .then(|| links.link(&tiles_path(render_id), now)),
+        artifacts: artifacts
+            .into_iter()
+            .map(|artifact| ArtifactStatus {
+                kind: artifact.kind,
+                content_type: artifact.content_type,
+                width: artifact.width,
+                height: artifact.height,
+                size: artifact.size,
+                link: links.link(&artifact_path(render_id, artifact.kind), now),
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,219 @@
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use chrono::Utc;
+use nalgebra as na;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::api_keys::{ApiKey, ApiKeyError};
+use crate::links::survey_path;
+use crate::render::filter::{split_filters, validate_filters, AstronomicalFilter};
+use crate::render::hips::{filter_label, HipsSurvey, DEFAULT_MAX_ORDER, DEFAULT_TILE_WIDTH};
+use crate::routes::images::serve_artifact;
+use crate::storage::{survey_key, ArtifactStorage};
+
+#[derive(serde::Deserialize)]
+pub struct SurveyJob {
+    email: String,
+    observer_position: na::Vector3<f32>,
+    /// One HiPS is generated per filter
+    filters: Vec<AstronomicalFilter>,
+    /// Deepest HEALPix order of the tiles, [`DEFAULT_MAX_ORDER`] when omitted
+    max_order: Option<u32>,
+    /// Width of the tiles in pixels, [`DEFAULT_TILE_WIDTH`] when omitted
+    tile_width: Option<u32>,
+}
+
+impl SurveyJob {
+    pub fn survey(&self) -> HipsSurvey {
+        HipsSurvey {
+            observer_position: self.observer_position,
+            max_order: self.max_order.unwrap_or(DEFAULT_MAX_ORDER),
+            tile_width: self.tile_width.unwrap_or(DEFAULT_TILE_WIDTH),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.filters.is_empty() {
+            return Err("A survey requires at least one filter.".into());
+        }
+        // Wavelengths name the directories of their surveys
+        validate_filters(&self.filters)?;
+        self.survey().validate()
+    }
+
+    /// Pixels rendered for every filter
+    pub fn pixels(&self) -> i64 {
+        self.survey().pixels() * self.filters.len() as i64
+    }
+}
+
+/// Response to a queued survey job
+#[derive(serde::Serialize)]
+pub struct SubmittedSurvey {
+    id: Uuid,
+    /// Path below which the survey is served, relative to the API's address
+    url: String,
+    /// Base path of the HiPS of each filter, for HiPS viewers
+    hips_urls: Vec<String>,
+}
+
+impl SubmittedSurvey {
+    fn new(survey_id: Uuid, filters: &[AstronomicalFilter]) -> Self {
+        let url = survey_path(survey_id);
+        Self {
+            id: survey_id,
+            hips_urls: filters
+                .iter()
+                .map(|filter| format!("{}/{}/", url, filter_label(filter)))
+                .collect(),
+            url,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/surveys",
+    request_body = SurveyJob,
+    responses(
+        (status = 202, description = "Survey job successfully queued, with its identifier and the base URL of the HiPS of each filter."),
+        (status = 400, description = "Survey job request body malformed."),
+        (status = 401, description = "Missing, unknown or revoked API key."),
+        (status = 429, description = "API key over one of its quotas, see the Retry-After header.")
+    )
+)]
+#[tracing::instrument(
+    name = "Inserting new survey job into queue",
+    skip(body, api_key, db_pool),
+    fields(api_key_id = %api_key.id)
+)]
+pub async fn submit_survey_request(
+    body: web::Json<SurveyJob>,
+    api_key: web::ReqData<ApiKey>,
+    db_pool: web::Data<PgPool>,
+) -> impl Responder {
+    if let Err(e) = body.validate() {
+        tracing::warn!("Rejected survey job: {}", e);
+        return HttpResponse::BadRequest().body(e);
+    }
+    if let Err(e) = api_key.check_pixels(body.pixels()) {
+        return e.response();
+    }
+    let survey_id = match save_survey_job(&body, &api_key, &db_pool).await {
+        Ok(survey_id) => survey_id,
+        Err(e) => return e.response(),
+    };
+    let submitted = SubmittedSurvey::new(survey_id, &body.filters);
+    HttpResponse::Accepted()
+        .insert_header((header::LOCATION, submitted.url.clone()))
+        .json(submitted)
+}
+
+/// Save a survey job if its API key's quotas allow one more, returning its id
+async fn save_survey_job(
+    body: &SurveyJob,
+    api_key: &ApiKey,
+    db_pool: &PgPool,
+) -> Result<Uuid, ApiKeyError> {
+    let mut transaction = db_pool.begin().await?;
+    api_key
+        .check_job_quotas(&mut transaction, Utc::now())
+        .await?;
+    let survey_id = insert_survey_job(&mut transaction, body, api_key.id).await?;
+    transaction.commit().await?;
+    Ok(survey_id)
+}
+
+#[tracing::instrument(
+    name = "Saving new survey job details in the database",
+    skip(transaction, body),
+    fields(survey_id)
+)]
+pub async fn insert_survey_job(
+    transaction: &mut Transaction<'_, Postgres>,
+    body: &SurveyJob,
+    api_key_id: Uuid,
+) -> Result<Uuid, sqlx::Error> {
+    let survey_id = Uuid::new_v4();
+    tracing::Span::current().record("survey_id", survey_id.to_string());
+    let survey = body.survey();
+    let (narrowband_filters, broadband_filters) = split_filters(&body.filters);
+    sqlx::query!(
+        r#"
+        INSERT INTO surveys (
+            id,
+            created_at,
+            email,
+            observer_position,
+            narrowband_filters,
+            broadband_filters,
+            max_order,
+            tile_width,
+            api_key_id
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        survey_id,
+        Utc::now(),
+        body.email,
+        &survey.observer_position.data.as_slice().to_vec(),
+        &narrowband_filters,
+        &broadband_filters,
+        survey.max_order as i16,
+        survey.tile_width as i32,
+        api_key_id,
+    )
+    .execute(transaction)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute query: {:?}", e);
+        e
+    })?;
+    Ok(survey_id)
+}
+
+#[utoipa::path(
+    get,
+    path = "/surveys/{id}/{filter}/{path}",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the survey job"),
+        ("filter" = String, Path, description = "Filter of the HiPS, such as `SDSS_G` or `0.6563um`"),
+        ("path" = String, Path, description = "File within the HiPS, such as `properties` or `Norder3/Dir0/Npix42.png`")
+    ),
+    responses(
+        (status = 200, description = "HiPS metadata or tile."),
+        (status = 304, description = "Cached copy of the file is current."),
+        (status = 404, description = "Survey unknown, unfinished or without this file.")
+    )
+)]
+#[tracing::instrument(
+    name = "Serving a survey file",
+    skip(request, path, db_pool, storage),
+    fields(survey_id = %path.0)
+)]
+pub async fn get_survey_file(
+    request: HttpRequest,
+    path: web::Path<(Uuid, String, String)>,
+    db_pool: web::Data<PgPool>,
+    storage: web::Data<dyn ArtifactStorage>,
+) -> HttpResponse {
+    // Surveys are public, as HiPS viewers fetch every file below the survey's URL
+    let (survey_id, label, file) = path.into_inner();
+    match sqlx::query!("SELECT id FROM surveys WHERE id = $1", survey_id)
+        .fetch_optional(db_pool.get_ref())
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().finish(),
+        Err(e) => {
+            tracing::error!("Failed to execute query: {:?}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+    serve_artifact(
+        &request,
+        storage.get_ref(),
+        &survey_key(survey_id, &label, &file),
+    )
+    .await
+}
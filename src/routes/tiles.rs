@@ -0,0 +1,140 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::links::{tiles_path, LinkSigner};
+use crate::render::tiles::{tile_key, Output};
+use crate::routes::images::serve_artifact;
+use crate::storage::ArtifactStorage;
+
+/// Query of tile links, whose signature covers the whole pyramid
+#[derive(serde::Deserialize)]
+pub struct TileQuery {
+    /// Unix time after which the link stops working
+    expires: Option<i64>,
+    signature: Option<String>,
+}
+
+/// Check that a tile request is signed and addresses the pyramid of an unexpired render
+///
+/// Returns the key of the pyramid's descriptor, which renders reused from the cache share.
+async fn check_pyramid(
+    render_id: Uuid,
+    query: &TileQuery,
+    db_pool: &PgPool,
+    links: &LinkSigner,
+) -> Result<String, HttpResponse> {
+    links
+        .verify(
+            &tiles_path(render_id),
+            query.expires,
+            query.signature.as_deref(),
+            Utc::now(),
+        )
+        .map_err(|e| HttpResponse::Forbidden().body(e.to_string()))?;
+    let render = sqlx::query!(
+        "SELECT output, image_key, expires_at FROM renders WHERE id = $1",
+        render_id
+    )
+    .fetch_optional(db_pool)
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to execute query: {:?}", e);
+        HttpResponse::InternalServerError().finish()
+    })?
+    .ok_or_else(|| HttpResponse::NotFound().finish())?;
+    if Output::try_from(render.output.as_str()) != Ok(Output::Tiles) {
+        return Err(HttpResponse::NotFound().body("The render is not output as tiles."));
+    }
+    if render
+        .expires_at
+        .map_or(false, |expires_at| expires_at <= Utc::now())
+    {
+        return Err(HttpResponse::Gone().finish());
+    }
+    render
+        .image_key
+        .ok_or_else(|| HttpResponse::NotFound().body("The render has not finished."))
+}
+
+#[utoipa::path(
+    get,
+    path = "/renders/{id}/tiles",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the render job"),
+        ("expires" = i64, Query, description = "Unix time at which the tiles link expires"),
+        ("signature" = String, Query, description = "HMAC signature of the tiles link")
+    ),
+    responses(
+        (status = 200, description = "Deep Zoom descriptor of the tile pyramid."),
+        (status = 403, description = "Tiles link unsigned, forged or expired."),
+        (status = 404, description = "Render job unknown, unfinished or not output as tiles."),
+        (status = 410, description = "Tiles removed after their retention period.")
+    )
+)]
+#[tracing::instrument(
+    name = "Serving a tile pyramid descriptor",
+    skip(request, query, db_pool, storage, links),
+    fields(render_id = %render_id)
+)]
+pub async fn get_render_tiles(
+    request: HttpRequest,
+    render_id: web::Path<Uuid>,
+    query: web::Query<TileQuery>,
+    db_pool: web::Data<PgPool>,
+    storage: web::Data<dyn ArtifactStorage>,
+    links: web::Data<LinkSigner>,
+) -> HttpResponse {
+    let render_id = render_id.into_inner();
+    match check_pyramid(render_id, &query, &db_pool, &links).await {
+        Ok(descriptor_key) => serve_artifact(&request, storage.get_ref(), &descriptor_key).await,
+        Err(response) => response,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/renders/{id}/tiles/{z}/{x}/{y}",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the render job"),
+        ("z" = u32, Path, description = "Deep Zoom level, from a single pixel at 0 to the full resolution"),
+        ("x" = u32, Path, description = "Column of the tile from the left"),
+        ("y" = u32, Path, description = "Row of the tile from the top"),
+        ("expires" = i64, Query, description = "Unix time at which the tiles link expires"),
+        ("signature" = String, Query, description = "HMAC signature of the tiles link")
+    ),
+    responses(
+        (status = 200, description = "JPEG tile."),
+        (status = 304, description = "Cached copy of the tile is current."),
+        (status = 403, description = "Tiles link unsigned, forged or expired."),
+        (status = 404, description = "Render job unknown, unfinished, not output as tiles or without this tile."),
+        (status = 410, description = "Tiles removed after their retention period.")
+    )
+)]
+#[tracing::instrument(
+    name = "Serving a tile",
+    skip(request, path, query, db_pool, storage, links),
+    fields(render_id = %path.0)
+)]
+pub async fn get_render_tile(
+    request: HttpRequest,
+    path: web::Path<(Uuid, u32, u32, u32)>,
+    query: web::Query<TileQuery>,
+    db_pool: web::Data<PgPool>,
+    storage: web::Data<dyn ArtifactStorage>,
+    links: web::Data<LinkSigner>,
+) -> HttpResponse {
+    let (render_id, z, x, y) = path.into_inner();
+    match check_pyramid(render_id, &query, &db_pool, &links).await {
+        Ok(descriptor_key) => {
+            serve_artifact(
+                &request,
+                storage.get_ref(),
+                &tile_key(&descriptor_key, z, x, y),
+            )
+            .await
+        }
+        Err(response) => response,
+    }
+}
@@ -1,32 +1,124 @@
 use std::net::TcpListener;
+use std::sync::Arc;
 
 use actix_web::dev::Server;
 use actix_web::web::Data;
 use actix_web::{web, App, HttpServer};
+use secrecy::Secret;
 use sqlx::PgPool;
 use tracing_actix_web::TracingLogger;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+use crate::api_keys::RequireApiKey;
+use crate::catalog::deep_sky::DeepSkyCatalog;
+use crate::links::LinkSigner;
+use crate::metrics::Metrics;
+use crate::render::extinction::DustGrid;
+use crate::render::instrument::InstrumentRegistry;
+use crate::routes::admin::{
+    __path_erase_user_data, __path_issue_api_key, __path_revoke_api_key, erase_user_data,
+    issue_api_key, revoke_api_key, AdminToken,
+};
+use crate::routes::artifacts::{__path_get_render_artifact, get_render_artifact};
 use crate::routes::health_check::{__path_health_check, health_check};
+use crate::routes::images::{__path_get_render_image, get_render_image};
+use crate::routes::links::{__path_issue_download_link, issue_download_link};
+use crate::routes::metrics::{__path_get_metrics, get_metrics};
 use crate::routes::renders::{__path_submit_render_request, submit_render_request};
+use crate::routes::status::{__path_get_render_status, get_render_status};
+use crate::routes::surveys::{
+    __path_get_survey_file, __path_submit_survey_request, get_survey_file, submit_survey_request,
+};
+use crate::routes::tiles::{
+    __path_get_render_tile, __path_get_render_tiles, get_render_tile, get_render_tiles,
+};
+use crate::storage::ArtifactStorage;
 
-pub fn run(listener: TcpListener, db_pool: PgPool) -> Result<Server, std::io::Error> {
+/// Data loaded from configuration that render jobs can refer to
+pub struct RenderResources {
+    pub instruments: InstrumentRegistry,
+    pub dust_map: Option<DustGrid>,
+    pub deep_sky: DeepSkyCatalog,
+}
+
+pub fn run(
+    listener: TcpListener,
+    db_pool: PgPool,
+    resources: RenderResources,
+    storage: Arc<dyn ArtifactStorage>,
+    links: LinkSigner,
+    admin_token: Secret<String>,
+) -> Result<Server, std::io::Error> {
     #[derive(OpenApi)]
     #[openapi(
         info(description = "space-telescope backend API."),
-        paths(health_check, submit_render_request)
+        paths(
+            health_check,
+            get_metrics,
+            submit_render_request,
+            get_render_status,
+            get_render_image,
+            get_render_artifact,
+            get_render_tiles,
+            get_render_tile,
+            issue_download_link,
+            submit_survey_request,
+            get_survey_file,
+            erase_user_data,
+            issue_api_key,
+            revoke_api_key
+        )
     )]
     struct ApiDoc;
 
     let db_pool = Data::new(db_pool);
+    let resources = Data::new(resources);
+    let storage: Data<dyn ArtifactStorage> = Data::from(storage);
+    let links = Data::new(links);
+    let metrics = Data::new(Metrics::default());
+    let admin_token = Data::new(AdminToken(admin_token));
     let server = HttpServer::new(move || {
         App::new()
             .service(SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", ApiDoc::openapi()))
             .wrap(TracingLogger::default())
             .route("/health_check", web::get().to(health_check))
-            .route("/renders", web::post().to(submit_render_request))
+            .route("/metrics", web::get().to(get_metrics))
+            .service(
+                web::resource("/renders")
+                    .route(web::post().to(submit_render_request))
+                    .wrap(RequireApiKey),
+            )
+            .route("/renders/{id}", web::get().to(get_render_status))
+            .route("/renders/{id}/image", web::get().to(get_render_image))
+            .route(
+                "/renders/{id}/artifacts/{kind}",
+                web::get().to(get_render_artifact),
+            )
+            .route("/renders/{id}/tiles", web::get().to(get_render_tiles))
+            .route(
+                "/renders/{id}/tiles/{z}/{x}/{y}",
+                web::get().to(get_render_tile),
+            )
+            .route("/renders/{id}/links", web::post().to(issue_download_link))
+            .service(
+                web::resource("/surveys")
+                    .route(web::post().to(submit_survey_request))
+                    .wrap(RequireApiKey),
+            )
+            .route(
+                "/surveys/{id}/{filter}/{path:.*}",
+                web::get().to(get_survey_file),
+            )
+            .route("/admin/erasures", web::post().to(erase_user_data))
+            .route("/admin/api-keys", web::post().to(issue_api_key))
+            .route("/admin/api-keys/{id}", web::delete().to(revoke_api_key))
             .app_data(db_pool.clone())
+            .app_data(resources.clone())
+            .app_data(storage.clone())
+            .app_data(links.clone())
+            .app_data(metrics.clone())
+            .app_data(admin_token.clone())
     })
     .listen(listener)?
     .run();
@@ -0,0 +1,153 @@
+use std::io::SeekFrom;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use crate::storage::{
+    content_type, validate_key, validate_prefix, ArtifactMetadata, ArtifactStorage, ArtifactStream,
+    StorageError,
+};
+
+/// Artifacts kept as files below a local directory, for development
+#[derive(Debug, Clone)]
+pub struct FilesystemStorage {
+    root: PathBuf,
+}
+
+impl FilesystemStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+fn storage_error(key: &str, error: std::io::Error) -> StorageError {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => StorageError::NotFound(key.to_string()),
+        _ => StorageError::Backend(error.to_string()),
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactStorage for FilesystemStorage {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(directory) = path.parent() {
+            tokio::fs::create_dir_all(directory)
+                .await
+                .map_err(|e| storage_error(key, e))?;
+        }
+        // Readers never see a partially written artifact
+        let partial = path.with_file_name(format!(
+            "{}.partial",
+            path.file_name().unwrap().to_string_lossy()
+        ));
+        tokio::fs::write(&partial, body)
+            .await
+            .map_err(|e| storage_error(key, e))?;
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| storage_error(key, e))
+    }
+
+    async fn stream(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<ArtifactStream, StorageError> {
+        let path = self.path(key)?;
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| storage_error(key, e))?;
+        let length = match range {
+            Some(range) => {
+                file.seek(SeekFrom::Start(range.start))
+                    .await
+                    .map_err(|e| storage_error(key, e))?;
+                range.end.saturating_sub(range.start)
+            }
+            None => u64::MAX,
+        };
+        let key = key.to_string();
+        Ok(Box::pin(ReaderStream::new(file.take(length)).map(
+            move |chunk| chunk.map_err(|e| storage_error(&key, e)),
+        )))
+    }
+
+    async fn metadata(&self, key: &str) -> Result<ArtifactMetadata, StorageError> {
+        let path = self.path(key)?;
+        let metadata = tokio::fs::metadata(&path)
+            .await
+            .map_err(|e| storage_error(key, e))?;
+        if !metadata.is_file() {
+            return Err(StorageError::NotFound(key.to_string()));
+        }
+        let last_modified: DateTime<Utc> = metadata
+            .modified()
+            .map_err(|e| storage_error(key, e))?
+            .into();
+        Ok(ArtifactMetadata {
+            size: metadata.len(),
+            content_type: content_type(key),
+            last_modified,
+            // Changes whenever the file is rewritten, like the ETags of static file servers
+            etag: format!(
+                "{:x}{:08x}-{:x}",
+                last_modified.timestamp(),
+                last_modified.timestamp_subsec_nanos(),
+                metadata.len()
+            ),
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(storage_error(key, e)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, StorageError> {
+        validate_prefix(prefix)?;
+        let directory = self.root.join(prefix);
+        let prefix = prefix.to_string();
+        tokio::task::spawn_blocking(move || {
+            let deleted = count_files(&directory).map_err(|e| storage_error(&prefix, e))?;
+            match std::fs::remove_dir_all(&directory) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(storage_error(&prefix, e))
+                }
+                _ => Ok(deleted),
+            }
+        })
+        .await
+        .map_err(|e| StorageError::Backend(e.to_string()))?
+    }
+}
+
+/// Number of files below `directory`, none if it does not exist
+fn count_files(directory: &Path) -> std::io::Result<u64> {
+    let entries = match std::fs::read_dir(directory) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        entries => entries?,
+    };
+    let mut count = 0;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            count += count_files(&entry.path())?;
+        } else {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
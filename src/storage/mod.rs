@@ -0,0 +1,136 @@
+use std::ops::Range;
+use std::pin::Pin;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use uuid::Uuid;
+
+pub mod filesystem;
+pub mod s3;
+
+/// Description of a stored artifact
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtifactMetadata {
+    /// Size in bytes
+    pub size: u64,
+    pub content_type: &'static str,
+    pub last_modified: DateTime<Utc>,
+    /// Opaque identifier of the artifact's current content, without quotes
+    pub etag: String,
+}
+
+/// Content of an artifact, read chunk by chunk
+pub type ArtifactStream = Pin<Box<dyn Stream<Item = Result<Bytes, StorageError>> + Send>>;
+
+#[derive(Debug)]
+pub enum StorageError {
+    /// Keys are relative paths of safe segments
+    InvalidKey(String),
+    NotFound(String),
+    Backend(String),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::InvalidKey(key) => write!(f, "{} is not a valid artifact key.", key),
+            StorageError::NotFound(key) => write!(f, "No artifact is stored at {}.", key),
+            StorageError::Backend(message) => write!(f, "Artifact storage failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Place where render artifacts are kept
+///
+/// Artifacts are addressed by keys relative to the storage's root, such as those returned by
+/// [`render_key`], so the database never holds the absolute location of a file. Content types
+/// follow the keys' extensions, see [`content_type`].
+#[async_trait::async_trait]
+pub trait ArtifactStorage: Send + Sync {
+    /// Store `body` at `key`, replacing any previous artifact
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), StorageError>;
+    /// Stream the bytes of the artifact at `key`, or only those in `range`
+    async fn stream(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<ArtifactStream, StorageError>;
+    async fn metadata(&self, key: &str) -> Result<ArtifactMetadata, StorageError>;
+    /// Remove the artifact at `key`, succeeding if there is none
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+    /// Remove every artifact below `prefix`, a key ending with `/`, returning how many there were
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, StorageError>;
+
+    /// Bytes of the artifact at `key`, or only those in `range`
+    async fn get(&self, key: &str, range: Option<Range<u64>>) -> Result<Vec<u8>, StorageError> {
+        let mut stream = self.stream(key, range).await?;
+        let mut body = vec![];
+        while let Some(chunk) = stream.next().await {
+            body.extend_from_slice(&chunk?);
+        }
+        Ok(body)
+    }
+}
+
+/// Key of the artifact `name` of a render job
+pub fn render_key(render_id: Uuid, name: &str) -> String {
+    format!("renders/{}/{}", render_id, name)
+}
+
+/// Prefix of the keys of every file of a survey
+pub fn survey_prefix(survey_id: Uuid) -> String {
+    format!("surveys/{}/", survey_id)
+}
+
+/// Key of the file at `path` of a survey's HiPS in the filter `label`
+pub fn survey_key(survey_id: Uuid, label: &str, path: &str) -> String {
+    format!("{}{}/{}", survey_prefix(survey_id), label, path)
+}
+
+/// Check that `prefix` is a key followed by `/`
+pub fn validate_prefix(prefix: &str) -> Result<(), StorageError> {
+    match prefix.strip_suffix('/') {
+        Some(key) => validate_key(key),
+        None => Err(StorageError::InvalidKey(prefix.to_string())),
+    }
+}
+
+/// Check that `key` is a relative path that cannot escape the storage's root
+pub fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
+
+/// Media type of the artifact at `key`, from the extension of its last segment
+pub fn content_type(key: &str) -> &'static str {
+    let name = key.rsplit('/').next().unwrap_or(key);
+    if name == "properties" {
+        // HiPS metadata, which has no extension
+        return "text/plain";
+    }
+    match name.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("fits") => "application/fits",
+        Some("json") => "application/json",
+        Some("xml") | Some("dzi") => "application/xml",
+        Some("txt") => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
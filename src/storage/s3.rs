@@ -0,0 +1,306 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, StatusCode};
+use secrecy::{ExposeSecret, Secret};
+use sha2::{Digest, Sha256};
+
+use crate::storage::{
+    content_type, validate_key, validate_prefix, ArtifactMetadata, ArtifactStorage, ArtifactStream,
+    StorageError,
+};
+
+/// Headers covered by request signatures, in canonical order
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+
+/// Connection details of an S3-compatible object store
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct S3Settings {
+    /// Base URL of the service, such as `https://s3.us-east-1.amazonaws.com`
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Prepended to every key, such as `space-telescope/`
+    #[serde(default)]
+    pub prefix: String,
+    pub access_key_id: String,
+    pub secret_access_key: Secret<String>,
+}
+
+/// Artifacts kept in a bucket of an S3-compatible object store
+///
+/// Objects are addressed path-style, which every S3-compatible service accepts, and requests
+/// are signed with AWS Signature Version 4.
+#[derive(Debug, Clone)]
+pub struct S3Storage {
+    client: reqwest::Client,
+    settings: S3Settings,
+}
+
+impl S3Storage {
+    pub fn new(settings: S3Settings) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            settings,
+        }
+    }
+
+    /// Send a signed request for the object at `key`
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        body: Vec<u8>,
+        range: Option<Range<u64>>,
+    ) -> Result<reqwest::Response, StorageError> {
+        validate_key(key)?;
+        let path = format!(
+            "/{}/{}",
+            self.settings.bucket,
+            uri_encode(&format!("{}{}", self.settings.prefix, key))
+        );
+        self.send_signed(method, &path, "", key, body, range).await
+    }
+
+    /// Send a signed request for `path` with the canonical `query`, about the artifact `key`
+    async fn send_signed(
+        &self,
+        method: Method,
+        path: &str,
+        query: &str,
+        key: &str,
+        body: Vec<u8>,
+        range: Option<Range<u64>>,
+    ) -> Result<reqwest::Response, StorageError> {
+        let mut url = format!("{}{}", self.settings.endpoint.trim_end_matches('/'), path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let url = reqwest::Url::parse(&url).map_err(|e| StorageError::Backend(e.to_string()))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(StorageError::Backend("The endpoint has no host.".into())),
+        };
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = Utc::now();
+        let authorization = self.authorization(&method, path, query, &host, &payload_hash, &now);
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header("authorization", authorization);
+        if !body.is_empty() {
+            request = request.header("content-type", content_type(key)).body(body);
+        }
+        if let Some(range) = range {
+            // HTTP ranges include their last byte
+            request = request.header(
+                "range",
+                format!("bytes={}-{}", range.start, range.end.saturating_sub(1)),
+            );
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Err(StorageError::NotFound(key.to_string())),
+            status if status.is_success() => Ok(response),
+            status => Err(StorageError::Backend(format!(
+                "{} responded {}: {}",
+                self.settings.endpoint,
+                status,
+                response.text().await.unwrap_or_default()
+            ))),
+        }
+    }
+
+    /// One page of the keys below `prefix`, relative to the configured prefix, with the token of
+    /// the next page if there is one
+    async fn list(
+        &self,
+        prefix: &str,
+        continuation_token: Option<&str>,
+    ) -> Result<(Vec<String>, Option<String>), StorageError> {
+        let full_prefix = format!("{}{}", self.settings.prefix, prefix);
+        // Canonical queries are sorted by parameter name
+        let mut parameters = vec![];
+        if let Some(token) = continuation_token {
+            parameters.push(("continuation-token", token));
+        }
+        parameters.push(("list-type", "2"));
+        parameters.push(("prefix", &full_prefix));
+        let query = parameters
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, query_encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let listing = self
+            .send_signed(
+                Method::GET,
+                &format!("/{}", self.settings.bucket),
+                &query,
+                prefix,
+                vec![],
+                None,
+            )
+            .await?
+            .text()
+            .await
+            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let keys = xml_elements(&listing, "Key")
+            .into_iter()
+            .map(|key| {
+                key.strip_prefix(self.settings.prefix.as_str())
+                    .unwrap_or(key)
+                    .to_string()
+            })
+            .collect();
+        let next = xml_elements(&listing, "NextContinuationToken")
+            .first()
+            .map(|token| token.to_string());
+        Ok((keys, next))
+    }
+
+    /// Signature Version 4 `Authorization` header of a request
+    fn authorization(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &str,
+        host: &str,
+        payload_hash: &str,
+        now: &DateTime<Utc>,
+    ) -> String {
+        let date = now.format("%Y%m%d").to_string();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, query, host, payload_hash, timestamp, SIGNED_HEADERS, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.settings.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let secret = format!("AWS4{}", self.settings.secret_access_key.expose_secret());
+        let signing_key = [date.as_str(), &self.settings.region, "s3", "aws4_request"]
+            .iter()
+            .fold(secret.into_bytes(), |key, part| hmac(&key, part.as_bytes()));
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.settings.access_key_id,
+            scope,
+            SIGNED_HEADERS,
+            hex::encode(hmac(&signing_key, string_to_sign.as_bytes()))
+        )
+    }
+}
+
+fn hmac(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode `path` as S3 expects in canonical requests, keeping its slashes
+fn uri_encode(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Percent-encode a query parameter as S3 expects in canonical requests, slashes included
+fn query_encode(value: &str) -> String {
+    uri_encode(value).replace('/', "%2F")
+}
+
+/// Text of every `tag` element of an XML document without attributes or nested elements of the
+/// same name, such as S3 listings
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    xml.split(open.as_str())
+        .skip(1)
+        .filter_map(|element| element.split_once(close.as_str()))
+        .map(|(text, _)| text)
+        .collect()
+}
+
+fn header<'a>(response: &'a reqwest::Response, name: &str) -> Option<&'a str> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+#[async_trait::async_trait]
+impl ArtifactStorage for S3Storage {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), StorageError> {
+        self.send(Method::PUT, key, body, None).await.map(|_| ())
+    }
+
+    async fn stream(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<ArtifactStream, StorageError> {
+        let response = self.send(Method::GET, key, vec![], range).await?;
+        Ok(Box::pin(response.bytes_stream().map(|chunk| {
+            chunk.map_err(|e| StorageError::Backend(e.to_string()))
+        })))
+    }
+
+    async fn metadata(&self, key: &str) -> Result<ArtifactMetadata, StorageError> {
+        let response = self.send(Method::HEAD, key, vec![], None).await?;
+        let size = header(&response, "content-length")
+            .and_then(|length| length.parse().ok())
+            .ok_or_else(|| StorageError::Backend("The object has no Content-Length.".into()))?;
+        let last_modified = header(&response, "last-modified")
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+            .ok_or_else(|| StorageError::Backend("The object has no Last-Modified.".into()))?;
+        let etag = header(&response, "etag")
+            .ok_or_else(|| StorageError::Backend("The object has no ETag.".into()))?;
+        Ok(ArtifactMetadata {
+            size,
+            content_type: content_type(key),
+            last_modified: last_modified.with_timezone(&Utc),
+            etag: etag.trim_matches('"').to_string(),
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match self.send(Method::DELETE, key, vec![], None).await {
+            Err(StorageError::NotFound(_)) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, StorageError> {
+        validate_prefix(prefix)?;
+        let mut deleted = 0;
+        let mut continuation_token = None;
+        loop {
+            let (keys, next) = self.list(prefix, continuation_token.as_deref()).await?;
+            for key in keys {
+                self.delete(&key).await?;
+                deleted += 1;
+            }
+            match next {
+                Some(token) => continuation_token = Some(token),
+                None => return Ok(deleted),
+            }
+        }
+    }
+}
@@ -0,0 +1,452 @@
+use futures_util::future::join_all;
+use serde_json::json;
+
+use space_telescope::api_keys::{create_api_key, Quotas};
+use space_telescope::artifacts::finish_survey;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_post_renders_returns_a_401_without_a_valid_api_key() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let (revoked, revoked_key) = create_api_key(
+        &test_app.db_pool,
+        "revoked@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 10,
+            max_concurrent_jobs: 10,
+            max_pixels_per_job: 1_000_000,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+    sqlx::query!(
+        "UPDATE api_keys SET revoked_at = now() WHERE id = $1",
+        revoked.id
+    )
+    .execute(&test_app.db_pool)
+    .await
+    .unwrap();
+    let test_cases = vec![
+        (None, "no API key"),
+        (Some("st_unknown".to_string()), "an unknown API key"),
+        (Some(revoked_key), "a revoked API key"),
+    ];
+
+    for (api_key, description) in test_cases {
+        // Act
+        let response = test_app.post_render(api_key.as_deref(), json!({})).await;
+
+        // Assert
+        assert_eq!(
+            401,
+            response.status().as_u16(),
+            "The API did not respond with 401 for {}.",
+            description
+        );
+    }
+    let renders = sqlx::query!("SELECT id FROM renders")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert!(renders.is_empty());
+}
+
+#[tokio::test]
+async fn test_post_renders_records_the_api_key() {
+    // Arrange
+    let test_app = spawn_app().await;
+
+    // Act
+    let render_id = test_app.queue_render().await;
+
+    // Assert
+    let render = sqlx::query!(
+        r#"
+        SELECT api_keys.owner AS "owner?"
+        FROM renders LEFT JOIN api_keys ON api_keys.id = renders.api_key_id
+        WHERE renders.id = $1
+        "#,
+        render_id
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .expect("Failed to fetch queued render.");
+    assert_eq!(render.owner.as_deref(), Some("test@space-telescope.com"));
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_a_429_over_the_jobs_per_day_quota() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let (_, api_key) = create_api_key(
+        &test_app.db_pool,
+        "daily@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 2,
+            max_concurrent_jobs: 10,
+            max_pixels_per_job: 1_000_000,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+    for longitude in [0f32, 1f32] {
+        let response = test_app
+            .post_render(Some(&api_key), json!({ "longitude": longitude }))
+            .await;
+        assert_eq!(202, response.status().as_u16());
+    }
+    let oldest_id = sqlx::query!("SELECT id FROM renders ORDER BY created_at LIMIT 1")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap()
+        .id;
+    test_app.age_render(oldest_id, 20).await;
+
+    // Act
+    let response = test_app
+        .post_render(Some(&api_key), json!({ "longitude": 2f32 }))
+        .await;
+
+    // Assert
+    assert_eq!(429, response.status().as_u16());
+    let retry_after: i64 = response.headers()["Retry-After"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    // The oldest job leaves the 24 hours window in about 4 hours
+    assert!((4 * 3600 - 60..=4 * 3600 + 60).contains(&retry_after));
+
+    // Once the oldest job is more than a day old, another may be submitted
+    test_app.age_render(oldest_id, 5).await;
+    let response = test_app
+        .post_render(Some(&api_key), json!({ "longitude": 2f32 }))
+        .await;
+    assert_eq!(202, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_a_429_over_the_concurrent_jobs_quota() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let (_, api_key) = create_api_key(
+        &test_app.db_pool,
+        "concurrent@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 10,
+            max_concurrent_jobs: 1,
+            max_pixels_per_job: 1_000_000,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+    let response = test_app.post_render(Some(&api_key), json!({})).await;
+    assert_eq!(202, response.status().as_u16());
+
+    // Act
+    let response = test_app
+        .post_render(Some(&api_key), json!({ "longitude": 1f32 }))
+        .await;
+
+    // Assert
+    assert_eq!(429, response.status().as_u16());
+    assert_eq!(response.headers()["Retry-After"], "60");
+
+    // Once the queued job is done, another may be submitted
+    let render_id = sqlx::query!("SELECT id FROM renders")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap()
+        .id;
+    test_app.complete_render(render_id, b"png", b"fits").await;
+    let response = test_app
+        .post_render(Some(&api_key), json!({ "longitude": 1f32 }))
+        .await;
+    assert_eq!(202, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_unfinished_surveys_count_towards_the_concurrent_jobs_quota() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, api_key) = create_api_key(
+        &test_app.db_pool,
+        "concurrent@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 10,
+            max_concurrent_jobs: 1,
+            max_pixels_per_job: 100_000_000,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+    let post_survey = || {
+        client
+            .post(format!("{}/surveys", &test_app.address))
+            .header("X-API-Key", &api_key)
+            .header("Content-Type", "application/json")
+            .body(
+                json!({
+                    "email": "concurrent@space-telescope.com",
+                    "observer_position": [0f32, 0f32, 0f32],
+                    "filters": ["SDSS_G"],
+                    "max_order": 0u32,
+                })
+                .to_string(),
+            )
+            .send()
+    };
+    let response = post_survey().await.unwrap();
+    assert_eq!(202, response.status().as_u16());
+
+    // Act
+    let survey = post_survey().await.unwrap();
+    let render = test_app.post_render(Some(&api_key), json!({})).await;
+
+    // Assert
+    assert_eq!(429, survey.status().as_u16());
+    assert_eq!(survey.headers()["Retry-After"], "60");
+    assert_eq!(429, render.status().as_u16());
+
+    // Once the queued survey is done, another job may be submitted
+    let survey_id = sqlx::query!("SELECT id FROM surveys")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap()
+        .id;
+    finish_survey(&test_app.db_pool, survey_id)
+        .await
+        .expect("Failed to finish survey.");
+    let response = post_survey().await.unwrap();
+    assert_eq!(202, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_concurrent_submissions_cannot_exceed_the_quotas() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let (api_key, key) = create_api_key(
+        &test_app.db_pool,
+        "racing@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 3,
+            max_concurrent_jobs: 10,
+            max_pixels_per_job: 1_000_000,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+
+    // Act
+    let responses = join_all((0..10).map(|longitude| {
+        test_app.post_render(Some(&key), json!({ "longitude": longitude as f32 }))
+    }))
+    .await;
+
+    // Assert
+    let mut statuses: Vec<u16> = responses
+        .iter()
+        .map(|response| response.status().as_u16())
+        .collect();
+    statuses.sort();
+    assert_eq!(statuses, [vec![202; 3], vec![429; 7]].concat());
+    let renders = sqlx::query!(
+        r#"SELECT count(*) AS "count!" FROM renders WHERE api_key_id = $1"#,
+        api_key.id
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(renders.count, 3);
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_a_429_over_the_pixels_per_job_quota() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let (_, api_key) = create_api_key(
+        &test_app.db_pool,
+        "pixels@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 10,
+            max_concurrent_jobs: 10,
+            max_pixels_per_job: 300 * 200,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+
+    // Act
+    let response = test_app
+        .post_render(
+            Some(&api_key),
+            json!({ "image_dimensions": [301u32, 200u32] }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(429, response.status().as_u16());
+    assert!(response.headers().contains_key("Retry-After"));
+    let renders = sqlx::query!("SELECT id FROM renders")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert!(renders.is_empty());
+    let response = test_app.post_render(Some(&api_key), json!({})).await;
+    assert_eq!(202, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_admin_api_keys_are_issued_and_revoked() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let body = json!({
+        "owner": "issued@space-telescope.com",
+        "max_jobs_per_day": 5,
+        "max_concurrent_jobs": 2,
+        "max_pixels_per_job": 1_000_000,
+    });
+    let unauthorized = client
+        .post(format!("{}/admin/api-keys", &test_app.address))
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(401, unauthorized.status().as_u16());
+    let invalid = client
+        .post(format!("{}/admin/api-keys", &test_app.address))
+        .bearer_auth(&test_app.admin_token)
+        .header("Content-Type", "application/json")
+        .body(json!({ "owner": "issued@space-telescope.com", "max_jobs_per_day": 0, "max_concurrent_jobs": 2, "max_pixels_per_job": 1 }).to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(400, invalid.status().as_u16());
+
+    // Act
+    let response = client
+        .post(format!("{}/admin/api-keys", &test_app.address))
+        .bearer_auth(&test_app.admin_token)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(201, response.status().as_u16());
+    let issued: serde_json::Value =
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!(issued["owner"], "issued@space-telescope.com");
+    assert_eq!(issued["max_concurrent_jobs"], 2);
+    let key = issued["key"].as_str().unwrap();
+    let stored =
+        sqlx::query!("SELECT key_hash FROM api_keys WHERE owner = 'issued@space-telescope.com'")
+            .fetch_one(&test_app.db_pool)
+            .await
+            .unwrap();
+    assert_ne!(stored.key_hash, key);
+    let response = test_app.post_render(Some(key), json!({})).await;
+    assert_eq!(202, response.status().as_u16());
+
+    let revoke_url = format!(
+        "{}/admin/api-keys/{}",
+        &test_app.address,
+        issued["id"].as_str().unwrap()
+    );
+    let revoked = client
+        .delete(&revoke_url)
+        .bearer_auth(&test_app.admin_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(204, revoked.status().as_u16());
+    let response = test_app
+        .post_render(Some(key), json!({ "longitude": 1f32 }))
+        .await;
+    assert_eq!(401, response.status().as_u16());
+    let revoked_again = client
+        .delete(&revoke_url)
+        .bearer_auth(&test_app.admin_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, revoked_again.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_post_surveys_applies_the_api_key_quotas() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let (_, api_key) = create_api_key(
+        &test_app.db_pool,
+        "surveys@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 1,
+            max_concurrent_jobs: 10,
+            // One filter over the 12 tiles of order 0
+            max_pixels_per_job: 12 * 512 * 512,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+    let post_survey = |api_key: Option<&str>, filters: serde_json::Value| {
+        let mut request = client
+            .post(format!("{}/surveys", &test_app.address))
+            .header("Content-Type", "application/json")
+            .body(
+                json!({
+                    "email": "surveys@space-telescope.com",
+                    "observer_position": [0f32, 0f32, 0f32],
+                    "filters": filters,
+                    "max_order": 0u32,
+                })
+                .to_string(),
+            );
+        if let Some(api_key) = api_key {
+            request = request.header("X-API-Key", api_key);
+        }
+        request.send()
+    };
+
+    // Act
+    let unauthenticated = post_survey(None, json!(["SDSS_G"])).await.unwrap();
+    let too_large = post_survey(Some(&api_key), json!(["SDSS_G", "SDSS_R"]))
+        .await
+        .unwrap();
+    let accepted = post_survey(Some(&api_key), json!(["SDSS_G"]))
+        .await
+        .unwrap();
+    let over_daily_survey = post_survey(Some(&api_key), json!(["SDSS_G"]))
+        .await
+        .unwrap();
+    let over_daily_render = test_app.post_render(Some(&api_key), json!({})).await;
+
+    // Assert
+    assert_eq!(401, unauthenticated.status().as_u16());
+    assert_eq!(429, too_large.status().as_u16());
+    assert!(too_large.headers().contains_key("Retry-After"));
+    assert_eq!(202, accepted.status().as_u16());
+    assert_eq!(429, over_daily_survey.status().as_u16());
+    assert_eq!(429, over_daily_render.status().as_u16());
+    let surveys = sqlx::query!(
+        r#"
+        SELECT api_keys.owner AS "owner?"
+        FROM surveys LEFT JOIN api_keys ON api_keys.id = surveys.api_key_id
+        "#
+    )
+    .fetch_all(&test_app.db_pool)
+    .await
+    .unwrap();
+    assert_eq!(surveys.len(), 1);
+    assert_eq!(
+        surveys[0].owner.as_deref(),
+        Some("surveys@space-telescope.com")
+    );
+}
@@ -0,0 +1,75 @@
+use serde_json::json;
+use uuid::Uuid;
+
+use space_telescope::artifacts::finish_render;
+use space_telescope::render::image::Image;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_post_renders_reuses_identical_finished_renders() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let cached_id = test_app
+        .queue_render_with(json!({ "longitude": 10f32 }))
+        .await;
+    let mut image = Image::new(300, 200);
+    image.add(150, 100, 1e3);
+    finish_render(
+        &test_app.db_pool,
+        test_app.storage.as_ref(),
+        cached_id,
+        &image,
+    )
+    .await
+    .expect("Failed to finish render.");
+
+    // Act
+    // The same view, with the longitude wrapped around and another email
+    let reused_id = test_app
+        .queue_render_with(json!({ "longitude": 370f32, "email": "other@space-telescope.com" }))
+        .await;
+    let queued_id = test_app
+        .queue_render_with(json!({ "longitude": 20f32 }))
+        .await;
+    let metrics = client
+        .get(format!("{}/metrics", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .text()
+        .await
+        .unwrap();
+
+    // Assert
+    let renders = sqlx::query!("SELECT id, image_key, cache_key FROM renders")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch saved renders.");
+    let render = |id: Uuid| renders.iter().find(|render| render.id == id).unwrap();
+    assert_eq!(render(reused_id).cache_key, render(cached_id).cache_key);
+    assert_eq!(render(reused_id).image_key, render(cached_id).image_key);
+    assert_ne!(render(queued_id).cache_key, render(cached_id).cache_key);
+    assert_eq!(render(queued_id).image_key, None);
+    let response = client
+        .get(format!("{}/renders/{}", &test_app.address, reused_id))
+        .bearer_auth(test_app.links.owner_token(reused_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let status: serde_json::Value =
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!(status["status"], "finished");
+    assert_eq!(status["artifacts"].as_array().unwrap().len(), 4);
+    let image = |render_id: Uuid| client.get(test_app.image_url(render_id)).send();
+    let reused_image = image(reused_id).await.expect("Failed to execute request.");
+    let cached_image = image(cached_id).await.expect("Failed to execute request.");
+    assert_eq!(200, reused_image.status().as_u16());
+    assert_eq!(
+        reused_image.bytes().await.unwrap(),
+        cached_image.bytes().await.unwrap()
+    );
+    assert!(metrics.contains("render_cache_hits_total 1\n"));
+    assert!(metrics.contains("render_cache_misses_total 2\n"));
+}
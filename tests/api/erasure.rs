@@ -0,0 +1,240 @@
+use chrono::Utc;
+use serde_json::json;
+
+use space_telescope::api_keys::{create_api_key, Quotas};
+use space_telescope::artifacts::store_survey;
+use space_telescope::catalog::octree::Octree;
+use space_telescope::erasure::{email_digest, erase_email, ErasureReport};
+use space_telescope::render::filter::{AstronomicalFilter, BroadBandFilter};
+use space_telescope::render::hips::HipsSurvey;
+use space_telescope::storage::render_key;
+
+use crate::helpers::{spawn_app, UndeletableStorage};
+
+#[tokio::test]
+async fn test_post_admin_erasures_requires_the_admin_token() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        (
+            None,
+            json!({ "email": "test@space-telescope.com" }),
+            401,
+            "no token",
+        ),
+        (
+            Some("not-the-admin-token".to_string()),
+            json!({ "email": "test@space-telescope.com" }),
+            401,
+            "a wrong token",
+        ),
+        (
+            Some(test_app.admin_token.clone()),
+            json!({ "email": "  " }),
+            400,
+            "an empty email",
+        ),
+    ];
+
+    for (token, body, expected_status, description) in test_cases {
+        // Act
+        let mut request = client
+            .post(format!("{}/admin/erasures", &test_app.address))
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_admin_erasures_erases_everything_tied_to_an_email() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let erased_id = test_app
+        .queue_render_with(json!({ "email": "Erased@Space-Telescope.com" }))
+        .await;
+    test_app.complete_render(erased_id, b"png", b"fits").await;
+    let queued_id = test_app
+        .queue_render_with(json!({ "email": "erased@space-telescope.com", "longitude": 1f32 }))
+        .await;
+    // Another user's render reuses the erased render from the cache
+    let kept_id = test_app.queue_render().await;
+    client
+        .post(format!("{}/surveys", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(
+            json!({
+                "email": "erased@space-telescope.com",
+                "observer_position": [0f32, 0f32, 0f32],
+                "filters": ["SDSS_G"],
+                "max_order": 0u32,
+                "tile_width": 64u32,
+            })
+            .to_string(),
+        )
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let survey_id = sqlx::query!("SELECT id FROM surveys")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap()
+        .id;
+    let survey = HipsSurvey {
+        observer_position: nalgebra::Vector3::zeros(),
+        max_order: 0,
+        tile_width: 64,
+    };
+    store_survey(
+        test_app.storage.as_ref(),
+        survey_id,
+        &survey,
+        &Octree::new(vec![]),
+        &AstronomicalFilter::BroadBand(BroadBandFilter::SDSS_G),
+    )
+    .await
+    .expect("Failed to store survey.");
+    let (erased_key, _) = create_api_key(
+        &test_app.db_pool,
+        "ERASED@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 1,
+            max_concurrent_jobs: 1,
+            max_pixels_per_job: 1,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+
+    // Act
+    let response = client
+        .post(format!("{}/admin/erasures", &test_app.address))
+        .bearer_auth(&test_app.admin_token)
+        .header("Content-Type", "application/json")
+        .body(json!({ "email": " erased@space-telescope.com" }).to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(200, response.status().as_u16());
+    let report: ErasureReport = serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!(
+        report.email_sha256,
+        email_digest("erased@space-telescope.com")
+    );
+    let mut render_ids = report.render_ids.clone();
+    render_ids.sort();
+    let mut expected_ids = vec![erased_id, queued_id];
+    expected_ids.sort();
+    assert_eq!(render_ids, expected_ids);
+    assert_eq!(report.survey_ids, vec![survey_id]);
+    // The survey's twelve base tiles and its properties, the image being shared
+    assert_eq!(report.files_deleted, 13);
+    assert_eq!(report.remaining_references, 0);
+    assert!(report.verify(&test_app.links));
+    let tampered = ErasureReport {
+        files_deleted: 0,
+        ..report.clone()
+    };
+    assert!(!tampered.verify(&test_app.links));
+    let renders = sqlx::query!("SELECT id FROM renders")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(renders.len(), 1);
+    assert_eq!(renders[0].id, kept_id);
+    let erased_keys = sqlx::query!("SELECT id FROM api_keys WHERE id = $1", erased_key.id)
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert!(erased_keys.is_empty());
+    let kept_image = client
+        .get(test_app.image_url(kept_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, kept_image.status().as_u16());
+    let properties = client
+        .get(format!(
+            "{}/surveys/{}/SDSS_G/properties",
+            &test_app.address, survey_id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(404, properties.status().as_u16());
+    let audit = sqlx::query!("SELECT email_sha256, signature, report FROM erasures")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch the erasure audit.");
+    assert_eq!(audit.email_sha256, report.email_sha256);
+    assert_eq!(audit.signature, report.signature);
+    // The recorded report can be verified again without the response
+    let recorded: ErasureReport =
+        serde_json::from_str(&audit.report.expect("The erasure report was not recorded.")).unwrap();
+    assert_eq!(recorded, report);
+    assert!(recorded.verify(&test_app.links));
+}
+
+#[tokio::test]
+async fn test_erasures_report_files_that_failed_to_delete() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let render_id = test_app
+        .queue_render_with(json!({ "email": "erased@space-telescope.com" }))
+        .await;
+    test_app.complete_render(render_id, b"png", b"fits").await;
+    let storage = UndeletableStorage(test_app.storage.clone());
+
+    // Act
+    let report = erase_email(
+        &test_app.db_pool,
+        &storage,
+        &test_app.links,
+        "erased@space-telescope.com",
+        Utc::now(),
+    )
+    .await
+    .expect("Failed to erase email.");
+
+    // Assert
+    assert_eq!(report.render_ids, vec![render_id]);
+    assert_eq!(report.files_deleted, 0);
+    assert_eq!(
+        report.files_failed,
+        vec![
+            render_key(render_id, "image.png"),
+            render_key(render_id, "image.fits")
+        ]
+    );
+    assert!(report.verify(&test_app.links));
+    let renders = sqlx::query!("SELECT id FROM renders")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert!(renders.is_empty());
+    let audit = sqlx::query!("SELECT files_failed, report FROM erasures")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch the erasure audit.");
+    assert_eq!(audit.files_failed, 2);
+    let recorded: ErasureReport = serde_json::from_str(&audit.report.unwrap()).unwrap();
+    assert_eq!(recorded.files_failed, report.files_failed);
+    assert!(recorded.verify(&test_app.links));
+}
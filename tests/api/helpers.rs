@@ -0,0 +1,245 @@
+use std::net::TcpListener;
+use std::ops::Range;
+use std::sync::Arc;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use secrecy::ExposeSecret;
+use serde_json::json;
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use uuid::Uuid;
+
+use space_telescope::api_keys::{create_api_key, Quotas};
+use space_telescope::configuration::{
+    get_configuration, get_deep_sky_catalog, get_instruments, DatabaseSettings, StorageSettings,
+};
+use space_telescope::links::LinkSigner;
+use space_telescope::startup::{run, RenderResources};
+use space_telescope::storage::{
+    render_key, ArtifactMetadata, ArtifactStorage, ArtifactStream, StorageError,
+};
+use space_telescope::telemetry::{get_subscriber, init_subscriber};
+
+// Ensure that the `tracing` stack is only initialized once using `once_cell`
+static TRACING: Lazy<()> = Lazy::new(|| {
+    let default_filter_level = "info".to_string();
+    let subscriber_name = "test".to_string();
+
+    if std::env::var("TEST_LOG").is_ok() {
+        let subscriber = get_subscriber(&subscriber_name, &default_filter_level, std::io::stdout);
+        init_subscriber(subscriber);
+    } else {
+        let subscriber = get_subscriber(&subscriber_name, &default_filter_level, std::io::sink);
+        init_subscriber(subscriber);
+    }
+});
+
+pub struct TestApp {
+    pub address: String,
+    pub db_pool: PgPool,
+    pub storage: Arc<dyn ArtifactStorage>,
+    pub links: LinkSigner,
+    pub admin_token: String,
+    pub api_key: String,
+}
+
+impl TestApp {
+    /// Queue a valid render job, returning its identifier
+    pub async fn queue_render(&self) -> Uuid {
+        self.queue_render_with(json!({})).await
+    }
+
+    /// Queue a valid render job with the `fields` overriding its defaults
+    pub async fn queue_render_with(&self, fields: serde_json::Value) -> Uuid {
+        let response = self.post_render(Some(&self.api_key), fields).await;
+        assert_eq!(202, response.status().as_u16());
+        let submitted: serde_json::Value =
+            serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+        submitted["id"].as_str().unwrap().parse().unwrap()
+    }
+
+    /// Submit a valid render job with `api_key` and the `fields` overriding its defaults
+    pub async fn post_render(
+        &self,
+        api_key: Option<&str>,
+        fields: serde_json::Value,
+    ) -> reqwest::Response {
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [30f32, 20f32],
+            "image_dimensions": [300u32, 200u32],
+            "fundamental_plane": { "basis": [[1f32, 0f32, 0f32], [0f32, 1f32, 0f32]] },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+        });
+        for (name, value) in fields.as_object().unwrap() {
+            body[name] = value.clone();
+        }
+        let mut request = reqwest::Client::new()
+            .post(format!("{}/renders", &self.address))
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+        if let Some(api_key) = api_key {
+            request = request.header("X-API-Key", api_key);
+        }
+        request.send().await.expect("Failed to execute request.")
+    }
+
+    /// Move the creation of a render job `hours` into the past
+    pub async fn age_render(&self, render_id: Uuid, hours: i64) {
+        sqlx::query!(
+            "UPDATE renders SET created_at = created_at - $1 * interval '1 hour' WHERE id = $2",
+            hours as f64,
+            render_id
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to age render.");
+    }
+
+    /// Signed download link to the rendered image of a job
+    pub fn image_url(&self, render_id: Uuid) -> String {
+        format!(
+            "{}{}",
+            &self.address,
+            self.links.image_link(render_id, Utc::now()).url
+        )
+    }
+
+    /// Store the images of a render job as its worker would once done
+    pub async fn complete_render(&self, render_id: Uuid, png: &[u8], fits: &[u8]) {
+        let image_key = render_key(render_id, "image.png");
+        self.storage.put(&image_key, png.to_vec()).await.unwrap();
+        self.storage
+            .put(&render_key(render_id, "image.fits"), fits.to_vec())
+            .await
+            .unwrap();
+        sqlx::query!(
+            "UPDATE renders SET image_key = $1 WHERE id = $2",
+            image_key,
+            render_id
+        )
+        .execute(&self.db_pool)
+        .await
+        .expect("Failed to complete render.");
+    }
+}
+
+/// Storage whose deletes always fail, as when the backend is unreachable
+pub struct UndeletableStorage(pub Arc<dyn ArtifactStorage>);
+
+#[async_trait::async_trait]
+impl ArtifactStorage for UndeletableStorage {
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), StorageError> {
+        self.0.put(key, body).await
+    }
+
+    async fn stream(
+        &self,
+        key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<ArtifactStream, StorageError> {
+        self.0.stream(key, range).await
+    }
+
+    async fn metadata(&self, key: &str) -> Result<ArtifactMetadata, StorageError> {
+        self.0.metadata(key).await
+    }
+
+    async fn delete(&self, _key: &str) -> Result<(), StorageError> {
+        Err(StorageError::Backend("Deletes are unavailable.".into()))
+    }
+
+    async fn delete_prefix(&self, _prefix: &str) -> Result<u64, StorageError> {
+        Err(StorageError::Backend("Deletes are unavailable.".into()))
+    }
+}
+
+/// Spin up instance of our application
+/// and returns its address (i.e. http://localhost:XXXX)
+pub async fn spawn_app() -> TestApp {
+    Lazy::force(&TRACING);
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind TcpListener.");
+    let port = listener.local_addr().unwrap().port();
+    let address = format!("http://127.0.0.1:{}", port);
+
+    let mut configuration = get_configuration().expect("Failed to read configuration.");
+    configuration.database.database_name = Uuid::new_v4().to_string();
+
+    let db_pool = configure_database(&configuration.database).await;
+
+    let resources = RenderResources {
+        instruments: get_instruments().expect("Failed to read instrument profiles."),
+        dust_map: configuration
+            .render
+            .dust_map()
+            .expect("Failed to read dust map."),
+        deep_sky: get_deep_sky_catalog().expect("Failed to read deep-sky catalog."),
+    };
+    // Keep each test's artifacts apart from the working directory
+    configuration.storage = StorageSettings::Filesystem {
+        root: std::env::temp_dir().join(configuration.database.database_name.clone()),
+    };
+    let storage = configuration.storage.storage();
+    let links = LinkSigner::new(configuration.application.hmac_secret);
+    let server = run(
+        listener,
+        db_pool.clone(),
+        resources,
+        storage.clone(),
+        links.clone(),
+        configuration.application.admin_token.clone(),
+    )
+    .expect("Failed to bind address");
+    tokio::spawn(server);
+    let (_, api_key) = create_api_key(
+        &db_pool,
+        "test@space-telescope.com",
+        Quotas {
+            max_jobs_per_day: 1000,
+            max_concurrent_jobs: 1000,
+            max_pixels_per_job: 100_000_000,
+        },
+    )
+    .await
+    .expect("Failed to create API key.");
+
+    TestApp {
+        address,
+        db_pool,
+        storage,
+        links,
+        admin_token: configuration
+            .application
+            .admin_token
+            .expose_secret()
+            .clone(),
+        api_key,
+    }
+}
+
+pub async fn configure_database(db_config: &DatabaseSettings) -> PgPool {
+    let mut db_connection =
+        PgConnection::connect(db_config.connection_string_instance().expose_secret())
+            .await
+            .expect("Failed to connect to Postgres.");
+
+    db_connection
+        .execute(format!(r#"CREATE DATABASE "{}";"#, &db_config.database_name).as_str())
+        .await
+        .expect("Failed to create database.");
+
+    let db_pool = PgPool::connect(db_config.connection_string_db().expose_secret())
+        .await
+        .expect("Failed to connect to Postgres.");
+
+    sqlx::migrate!("./migrations")
+        .run(&db_pool)
+        .await
+        .expect("Failed to migrate the database.");
+
+    db_pool
+}
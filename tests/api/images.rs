@@ -0,0 +1,245 @@
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_get_render_image_returns_404_until_the_render_finishes() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+
+    for id in [Uuid::new_v4(), render_id] {
+        // Act
+        let response = client
+            .get(test_app.image_url(id))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(404, response.status().as_u16());
+    }
+}
+
+#[tokio::test]
+async fn test_get_render_image_negotiates_the_format() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+    test_app
+        .complete_render(render_id, b"png image", b"fits image")
+        .await;
+    let test_cases = vec![
+        (None, None, 200, Some("image/png"), "no preference"),
+        (Some("image/*"), None, 200, Some("image/png"), "any image"),
+        (
+            Some("image/png;q=0.5, application/fits"),
+            None,
+            200,
+            Some("application/fits"),
+            "a preference for FITS",
+        ),
+        (
+            Some("image/png"),
+            Some("fits"),
+            200,
+            Some("application/fits"),
+            "a format parameter",
+        ),
+        (
+            Some("image/png;q=0, */*;q=0.1"),
+            None,
+            200,
+            Some("application/fits"),
+            "PNG refused next to a wildcard",
+        ),
+        (Some("image/png;q=0"), None, 406, None, "PNG refused"),
+        (Some("*/*;q=0"), None, 406, None, "every format refused"),
+        (Some("text/html"), None, 406, None, "an unsupported format"),
+    ];
+
+    for (accept, format, expected_status, expected_type, description) in test_cases {
+        // Act
+        let mut request = client.get(test_app.image_url(render_id));
+        if let Some(accept) = accept {
+            request = request.header("Accept", accept);
+        }
+        if let Some(format) = format {
+            request = request.query(&[("format", format)]);
+        }
+        let response = request.send().await.expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+        if let Some(expected_type) = expected_type {
+            let content_type = response.headers()["content-type"]
+                .to_str()
+                .unwrap()
+                .to_string();
+            let body = response.bytes().await.unwrap();
+            assert_eq!(content_type, expected_type);
+            let expected_body: &[u8] = match expected_type {
+                "image/png" => b"png image",
+                _ => b"fits image",
+            };
+            assert_eq!(body.as_ref(), expected_body);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_get_render_image_supports_caching_and_ranges() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+    let fits: Vec<u8> = (0..=255).collect();
+    test_app
+        .complete_render(render_id, b"png image", &fits)
+        .await;
+    let url = format!("{}&format=fits", test_app.image_url(render_id));
+    let first = client
+        .get(&url)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let etag = first.headers()["etag"].clone();
+    let last_modified = first.headers()["last-modified"].clone();
+
+    // Act
+    let revalidated = client
+        .get(&url)
+        .header("If-None-Match", etag.clone())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let unmodified = client
+        .get(&url)
+        .header("If-Modified-Since", last_modified)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let partial = client
+        .get(&url)
+        .header("Range", "bytes=16-31")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let suffix = client
+        .get(&url)
+        .header("Range", "bytes=-6")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let unsatisfiable = client
+        .get(&url)
+        .header("Range", "bytes=1000-")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(200, first.status().as_u16());
+    assert_eq!(first.headers()["accept-ranges"], "bytes");
+    assert_eq!(first.content_length(), Some(256));
+    assert_eq!(304, revalidated.status().as_u16());
+    assert_eq!(304, unmodified.status().as_u16());
+    assert_eq!(206, partial.status().as_u16());
+    assert_eq!(partial.headers()["content-range"], "bytes 16-31/256");
+    assert_eq!(partial.bytes().await.unwrap().as_ref(), &fits[16..32]);
+    assert_eq!(206, suffix.status().as_u16());
+    assert_eq!(suffix.bytes().await.unwrap().as_ref(), &fits[250..]);
+    assert_eq!(416, unsatisfiable.status().as_u16());
+    assert_eq!(unsatisfiable.headers()["content-range"], "bytes */256");
+}
+
+#[tokio::test]
+async fn test_get_render_image_returns_410_after_expiry() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+    test_app
+        .complete_render(render_id, b"png image", b"fits image")
+        .await;
+    sqlx::query!(
+        "UPDATE renders SET expires_at = now() - interval '1 day' WHERE id = $1",
+        render_id
+    )
+    .execute(&test_app.db_pool)
+    .await
+    .expect("Failed to expire render.");
+
+    // Act
+    let response = client
+        .get(test_app.image_url(render_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(410, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_get_render_image_returns_403_for_invalid_links() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+    test_app
+        .complete_render(render_id, b"png image", b"fits image")
+        .await;
+    let path = format!("/renders/{}/image", render_id);
+    let expired = (Utc::now() - Duration::minutes(1)).timestamp();
+    let valid = (Utc::now() + Duration::hours(1)).timestamp();
+    let other_link = test_app.links.image_link(Uuid::new_v4(), Utc::now()).url;
+    let other_query = other_link.split_once('?').unwrap().1;
+    let test_cases = vec![
+        (String::new(), "an unsigned link"),
+        (format!("?expires={}", valid), "a link without a signature"),
+        (
+            format!(
+                "?expires={}&signature={}",
+                valid + 3600,
+                test_app.links.sign(&path, valid)
+            ),
+            "an extended expiry",
+        ),
+        (format!("?{}", other_query), "the link of another render"),
+        (
+            format!(
+                "?expires={}&signature={}",
+                expired,
+                test_app.links.sign(&path, expired)
+            ),
+            "an expired link",
+        ),
+    ];
+
+    for (query, description) in test_cases {
+        // Act
+        let response = client
+            .get(format!("{}{}{}", &test_app.address, path, query))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            403,
+            response.status().as_u16(),
+            "The API did not respond with 403 for {}.",
+            description
+        );
+    }
+}
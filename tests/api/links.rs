@@ -0,0 +1,69 @@
+use uuid::Uuid;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_post_render_links_issues_links_to_the_owner() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+    test_app
+        .complete_render(render_id, b"png image", b"fits image")
+        .await;
+    let unknown_id = Uuid::new_v4();
+    let test_cases = vec![
+        (render_id, None, 401, "no owner token"),
+        (
+            render_id,
+            Some(test_app.links.owner_token(unknown_id)),
+            401,
+            "the owner token of another render",
+        ),
+        (
+            unknown_id,
+            Some(test_app.links.owner_token(unknown_id)),
+            404,
+            "an unknown render",
+        ),
+        (
+            render_id,
+            Some(test_app.links.owner_token(render_id)),
+            200,
+            "the owner token",
+        ),
+    ];
+
+    for (id, token, expected_status, description) in test_cases {
+        // Act
+        let mut request = client.post(format!("{}/renders/{}/links", &test_app.address, id));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+        if expected_status == 200 {
+            let link: serde_json::Value =
+                serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+            let image = client
+                .get(format!(
+                    "{}{}",
+                    &test_app.address,
+                    link["url"].as_str().unwrap()
+                ))
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert_eq!(200, image.status().as_u16());
+            assert_eq!(image.bytes().await.unwrap().as_ref(), b"png image");
+        }
+    }
+}
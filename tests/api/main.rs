@@ -0,0 +1,12 @@
+mod api_keys;
+mod cache;
+mod erasure;
+mod health_check;
+mod helpers;
+mod images;
+mod links;
+mod renders;
+mod retention;
+mod status;
+mod surveys;
+mod tiles;
@@ -0,0 +1,1512 @@
+use serde_json::json;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_post_renders_returns_202_for_valid_body_fields() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fov": [50f32, 51f32],
+        "image_dimensions": [256u32, 257u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": -45f32,
+        "longitude": 120f32,
+        "filters": [
+            "SDSS_U",
+            "SDSS_G",
+            "SDSS_R",
+            0.55555f32,
+        ],
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+
+    let render = sqlx::query!("SELECT * FROM renders")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.email, body["email"]);
+    assert_eq!(render.image_key, None);
+    assert_eq!(render.fov_x, body["fov"][0]);
+    assert_eq!(render.fov_y, body["fov"][1]);
+    assert_eq!(render.image_dimension_x, body["image_dimensions"][0]);
+    assert_eq!(render.image_dimension_y, body["image_dimensions"][1]);
+    assert_eq!(
+        render.fundamental_plane_basis_vector_1,
+        vec![1f32, 0f32, 0f32]
+    );
+    assert_eq!(
+        render.fundamental_plane_basis_vector_2,
+        vec![0f32, 1f32, 0f32]
+    );
+    assert_eq!(render.observer_position, vec![0f32, 0f32, 0f32]);
+    assert_eq!(render.latitude, body["latitude"]);
+    assert_eq!(render.longitude, body["longitude"]);
+    assert_eq!(
+        render.broadband_filters,
+        vec![
+            "SDSS_U".to_string(),
+            "SDSS_G".to_string(),
+            "SDSS_R".to_string()
+        ]
+    );
+    assert_eq!(render.narrowband_filters, vec![0.55555f32]);
+    assert_eq!(render.projection, "gnomonic");
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_missing_body_fields() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fov": [50f32, 51f32],
+        "image_dimensions": [256u32, 257u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": -45f32,
+        // Oops! Forgot the longitude
+        "filters": [
+            "SDSS_U",
+            "SDSS_G",
+            "SDSS_R",
+            0.55555f32,
+        ],
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_nonpositive_fov_values() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body_zero = json!({
+        "email": "test@space-telescope.com",
+        "fov": [50f32, 0f32],
+        "image_dimensions": [256u32, 257u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": -45f32,
+        "longitude": 120f32,
+        "filters": [
+            "SDSS_U",
+            "SDSS_G",
+            "SDSS_R",
+            0.55555f32,
+        ],
+    });
+    let body_negative = json!({
+        "email": "test@space-telescope.com",
+        "fov": [-50f32, 50f32],
+        "image_dimensions": [256u32, 257u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": -45f32,
+        "longitude": 120f32,
+        "filters": [
+            "SDSS_U",
+            "SDSS_G",
+            "SDSS_R",
+            0.55555f32,
+        ],
+    });
+    let response_zero = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body_zero.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response_negative = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body_negative.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(400, response_zero.status().as_u16());
+    assert_eq!(400, response_negative.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_parallel_fundamental_plane_vectors() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fov": [50f32, 51f32],
+        "image_dimensions": [256u32, 257u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [2f32, 0f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": -45f32,
+        "longitude": 120f32,
+        "filters": [
+            "SDSS_U",
+            "SDSS_G",
+            "SDSS_R",
+            0.55555f32,
+        ],
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_latitude_out_of_range() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body_negative = json!({
+        "email": "test@space-telescope.com",
+        "fov": [50f32, 51f32],
+        "image_dimensions": [256u32, 257u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": -91f32,
+        "longitude": 120f32,
+        "filters": [
+            "SDSS_U",
+            "SDSS_G",
+            "SDSS_R",
+            0.55555f32,
+        ],
+    });
+    let body_positive = json!({
+        "email": "test@space-telescope.com",
+        "fov": [50f32, 50f32],
+        "image_dimensions": [256u32, 256u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": 91f32,
+        "longitude": 120f32,
+        "filters": [
+            "SDSS_U",
+            "SDSS_G",
+            "SDSS_R",
+            0.55555f32,
+        ],
+    });
+    let response_negative = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body_negative.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response_positive = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body_positive.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(400, response_negative.status().as_u16());
+    assert_eq!(400, response_positive.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_post_renders_accepts_wide_fov_for_all_sky_projections() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fov": [360f32, 180f32],
+        "image_dimensions": [512u32, 256u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": 0f32,
+        "longitude": 0f32,
+        "filters": ["SDSS_G"],
+        "projection": "hammer_aitoff",
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+
+    let render = sqlx::query!("SELECT projection FROM renders")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.projection, "hammer_aitoff");
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_fov_beyond_projection_limits() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        ("gnomonic", [180f32, 90f32]),
+        ("stereographic", [90f32, 360f32]),
+        ("orthographic", [181f32, 90f32]),
+        ("azimuthal_equidistant", [361f32, 90f32]),
+        ("equirectangular", [360f32, 181f32]),
+        ("hammer_aitoff", [361f32, 180f32]),
+    ];
+
+    for (projection, fov) in test_cases {
+        // Act
+        let body = json!({
+            "email": "test@space-telescope.com",
+            "fov": fov,
+            "image_dimensions": [256u32, 256u32],
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+            "projection": projection,
+        });
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject a {:?} fov with the {} projection.",
+            fov,
+            projection
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_ignores_fov_for_whole_sky_render_modes() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![("panorama", [2048u32, 1024u32]), ("cubemap", [512, 512])];
+
+    for (mode, image_dimensions) in test_cases {
+        // Act
+        let body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [0f32, 0f32],
+            "image_dimensions": image_dimensions,
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+            "mode": mode,
+        });
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            202,
+            response.status().as_u16(),
+            "The API did not accept a {} render.",
+            mode
+        );
+    }
+
+    let render_modes = sqlx::query!("SELECT render_mode FROM renders ORDER BY created_at")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued renders.");
+
+    assert_eq!(
+        render_modes
+            .into_iter()
+            .map(|render| render.render_mode)
+            .collect::<Vec<_>>(),
+        vec!["panorama".to_string(), "cubemap".to_string()]
+    );
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_mismatched_whole_sky_image_dimensions() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![("panorama", [1024u32, 1024u32]), ("cubemap", [512, 256])];
+
+    for (mode, image_dimensions) in test_cases {
+        // Act
+        let body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [50f32, 50f32],
+            "image_dimensions": image_dimensions,
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+            "mode": mode,
+        });
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject a {} render of {:?} pixels.",
+            mode,
+            image_dimensions
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_optics() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fov": [0.05f32, 0.05f32],
+        "image_dimensions": [256u32, 256u32],
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": 0f32,
+        "longitude": 0f32,
+        "filters": [0.6563f32],
+        "optics": {
+            "aperture_diameter": 2.4f32,
+            "psf": { "model": "moffat", "fwhm": 0.8f32, "beta": 2.5f32 }
+        },
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+
+    let render =
+        sqlx::query!("SELECT aperture_diameter, psf_model, psf_fwhm, psf_beta FROM renders")
+            .fetch_one(&test_app.db_pool)
+            .await
+            .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.aperture_diameter, Some(2.4));
+    assert_eq!(render.psf_model.as_deref(), Some("moffat"));
+    assert_eq!(render.psf_fwhm, Some(0.8));
+    assert_eq!(render.psf_beta, Some(2.5));
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_invalid_optics() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        (
+            json!({ "psf": { "model": "airy" } }),
+            "an Airy PSF without an aperture",
+        ),
+        (
+            json!({ "psf": { "model": "gaussian", "fwhm": 0f32 } }),
+            "a zero width PSF",
+        ),
+        (
+            json!({ "psf": { "model": "moffat", "fwhm": 1f32, "beta": 1f32 } }),
+            "a Moffat PSF with diverging wings",
+        ),
+        (
+            json!({ "aperture_diameter": -1f32, "psf": { "model": "airy" } }),
+            "a negative aperture",
+        ),
+    ];
+
+    for (optics, description) in test_cases {
+        // Act
+        let body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [1f32, 1f32],
+            "image_dimensions": [256u32, 256u32],
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_R"],
+            "optics": optics,
+        });
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_invalid_positions_and_filters() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let test_cases = vec![
+        (
+            json!({ "observer_position": [1e39f64, 0f32, 0f32] }),
+            "an observer position overflowing to infinity",
+        ),
+        (
+            json!({ "filters": [-1f32] }),
+            "a negative narrowband filter",
+        ),
+        (json!({ "filters": [0f32] }), "a zero narrowband filter"),
+        (
+            json!({ "filters": ["SDSS_G", 1e39f64] }),
+            "a narrowband filter overflowing to infinity",
+        ),
+    ];
+
+    for (fields, description) in test_cases {
+        // Act
+        let response = test_app.post_render(Some(&test_app.api_key), fields).await;
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_derives_geometry_and_optics_from_instrument() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": 0f32,
+        "longitude": 0f32,
+        "filters": ["SDSS_G", "SDSS_R"],
+        "instrument": "sdss_imaging_camera",
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+
+    let render = sqlx::query!(
+        r#"
+        SELECT
+            fov_x,
+            fov_y,
+            image_dimension_x,
+            image_dimension_y,
+            aperture_diameter,
+            psf_model,
+            instrument
+        FROM renders
+        "#
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.image_dimension_x, 2048);
+    assert_eq!(render.image_dimension_y, 1489);
+    // 24 micrometre pixels at a focal length of 12.5 metres
+    let pixel_scale = (24e-6f32 / 12.5).to_degrees();
+    assert!((render.fov_x - 2048.0 * pixel_scale).abs() < 1e-6);
+    assert!((render.fov_y - 1489.0 * pixel_scale).abs() < 1e-6);
+    assert_eq!(render.aperture_diameter, Some(2.5));
+    assert_eq!(render.psf_model.as_deref(), Some("moffat"));
+    assert_eq!(render.instrument.as_deref(), Some("sdss_imaging_camera"));
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_requests_incompatible_with_instrument() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        (
+            json!({ "instrument": "hubble_wfc3_uvis_but_better" }),
+            "an unknown instrument",
+        ),
+        (
+            json!({ "instrument": "sdss_imaging_camera", "filters": [0.6563f32] }),
+            "a filter the instrument does not have",
+        ),
+        (
+            json!({ "instrument": "sdss_imaging_camera", "image_dimensions": [4096, 4096] }),
+            "an image larger than the detector",
+        ),
+        (
+            json!({ "instrument": "sdss_imaging_camera", "image_dimensions": [-1, 1024] }),
+            "a negative image size",
+        ),
+        (
+            json!({
+                "instrument": "sdss_imaging_camera",
+                "image_dimensions": [1024, 1024],
+                "fov": [1f32, 1f32]
+            }),
+            "a fov that does not match the pixel scale",
+        ),
+        (
+            json!({
+                "instrument": "sdss_imaging_camera",
+                "optics": { "psf": { "model": "gaussian", "fwhm": 1f32 } }
+            }),
+            "optics alongside the instrument",
+        ),
+        (
+            json!({ "instrument": "sdss_imaging_camera", "mode": "panorama" }),
+            "a whole sky render",
+        ),
+        (json!({}), "neither a fov nor an instrument"),
+    ];
+
+    for (overrides, description) in test_cases {
+        // Act
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_R"],
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_a_seeded_exposure() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fundamental_plane": {
+            "basis": [
+                [1f32, 0f32, 0f32],
+                [0f32, 1f32, 0f32]
+            ]
+        },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": 0f32,
+        "longitude": 0f32,
+        "filters": ["SDSS_R"],
+        "instrument": "sdss_imaging_camera",
+        "exposure": { "time": 53.9f32, "sky_brightness": 20.8f32 },
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+
+    let render = sqlx::query!(
+        "SELECT exposure_time, sky_brightness, noise_seed, gain, bit_depth FROM renders"
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.exposure_time, Some(53.9));
+    assert_eq!(render.sky_brightness, Some(20.8));
+    assert!(render.noise_seed.is_some());
+    assert!(render.gain.is_some());
+    assert!(render.bit_depth.is_some());
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_invalid_exposures() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let detector = json!({
+        "quantum_efficiency": 0.9f32,
+        "read_noise": 3f32,
+        "dark_current": 0.002f32,
+        "gain": 1.5f32,
+        "full_well": 80000f32,
+        "bit_depth": 16,
+    });
+    let optics = json!({
+        "aperture_diameter": 1f32,
+        "psf": { "model": "gaussian", "fwhm": 1f32 }
+    });
+    let test_cases = vec![
+        (
+            json!({ "optics": optics, "exposure": { "time": 10f32 } }),
+            "an exposure without a detector",
+        ),
+        (
+            json!({
+                "optics": { "psf": { "model": "gaussian", "fwhm": 1f32 } },
+                "detector": detector,
+                "exposure": { "time": 10f32 }
+            }),
+            "an exposure without an aperture",
+        ),
+        (
+            json!({ "optics": optics, "detector": detector, "exposure": { "time": 0f32 } }),
+            "a zero length exposure",
+        ),
+        (
+            json!({
+                "optics": optics,
+                "detector": {
+                    "quantum_efficiency": 1.5f32,
+                    "read_noise": 3f32,
+                    "dark_current": 0.002f32,
+                    "gain": 1.5f32,
+                    "full_well": 80000f32,
+                    "bit_depth": 16,
+                },
+            }),
+            "a detector with a quantum efficiency above 1",
+        ),
+        (
+            json!({ "instrument": "sdss_imaging_camera", "detector": detector }),
+            "a detector alongside an instrument",
+        ),
+        (
+            json!({ "optics": optics, "detector": detector, "exposure": { "time": 1e39f64 } }),
+            "an exposure time overflowing to infinity",
+        ),
+    ];
+    let mut test_cases: Vec<(serde_json::Value, String)> = test_cases
+        .into_iter()
+        .map(|(overrides, description)| (overrides, description.to_string()))
+        .collect();
+    // Values overflowing an f32 deserialize as infinities
+    for field in ["read_noise", "dark_current", "gain", "full_well"] {
+        let mut overflowing = detector.clone();
+        overflowing[field] = json!(1e39f64);
+        test_cases.push((
+            json!({ "optics": optics, "detector": overflowing, "exposure": { "time": 10f32 } }),
+            format!("a detector {} overflowing to infinity", field),
+        ));
+    }
+
+    for (overrides, description) in test_cases {
+        // Act
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [0.1f32, 0.1f32],
+            "image_dimensions": [256u32, 256u32],
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_R"],
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_extinction_model() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        (json!("exponential_disk"), 202, "the exponential disk"),
+        // The test configuration has no dust map
+        (json!("dust_map"), 400, "a missing dust map"),
+        (json!("fog"), 400, "an unknown extinction model"),
+    ];
+
+    for (extinction, expected_status, description) in test_cases {
+        // Act
+        let body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [1f32, 1f32],
+            "image_dimensions": [256u32, 256u32],
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+            "extinction": extinction,
+        });
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+    }
+
+    let render = sqlx::query!("SELECT extinction FROM renders")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.extinction.as_deref(), Some("exponential_disk"));
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_epoch() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        (
+            json!(102016f64),
+            202,
+            "an epoch a hundred thousand years away",
+        ),
+        (json!(-2e7f64), 400, "an epoch too far in the past"),
+        (json!("tomorrow"), 400, "an epoch that is not a year"),
+        (json!(null), 400, "light travel time without an epoch"),
+    ];
+
+    for (epoch, expected_status, description) in test_cases {
+        // Act
+        let body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [1f32, 1f32],
+            "image_dimensions": [256u32, 256u32],
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+            "epoch": epoch,
+            "light_travel_time": true,
+        });
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+    }
+
+    let render = sqlx::query!("SELECT epoch, light_travel_time FROM renders")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.epoch, Some(102016.0));
+    assert!(render.light_travel_time);
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_observer_velocity() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        (
+            json!([0f32, 0f32, 0.99f32]),
+            202,
+            "a velocity below light speed",
+        ),
+        (
+            json!([0.8f32, 0.8f32, 0f32]),
+            400,
+            "a velocity above light speed",
+        ),
+        (json!([0f32, 1f32]), 400, "a two dimensional velocity"),
+    ];
+
+    for (velocity, expected_status, description) in test_cases {
+        // Act
+        let body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [1f32, 1f32],
+            "image_dimensions": [256u32, 256u32],
+            "fundamental_plane": {
+                "basis": [
+                    [1f32, 0f32, 0f32],
+                    [0f32, 1f32, 0f32]
+                ]
+            },
+            "observer_position": [0f32, 0f32, 0f32],
+            "observer_velocity": velocity,
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+        });
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+    }
+
+    let render = sqlx::query!("SELECT observer_velocity FROM renders")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.observer_velocity, Some(vec![0.0, 0.0, 0.99]));
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_ground_observer() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "fov": [60f32, 40f32],
+        "image_dimensions": [600u32, 400u32],
+        "observer_position": [0f32, 0f32, 0f32],
+        "site": {
+            "latitude": 51.4769f32,
+            "longitude": -0.0005f32,
+            "elevation": 46f32,
+            "time": "2023-12-21T22:30:00Z"
+        },
+        "altitude": 30f32,
+        "azimuth": 90f32,
+        "filters": ["SDSS_G"],
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+
+    let render = sqlx::query!(
+        r#"
+        SELECT
+            latitude,
+            longitude,
+            fundamental_plane_basis_vector_1,
+            site_latitude,
+            site_elevation,
+            observation_time
+        FROM renders
+        "#
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.latitude, 30.0);
+    // Azimuth runs through east, longitude in the horizon frame through west
+    assert_eq!(render.longitude, 270.0);
+    assert_eq!(render.fundamental_plane_basis_vector_1.len(), 3);
+    assert_eq!(render.site_latitude, Some(51.4769));
+    assert_eq!(render.site_elevation, Some(46.0));
+    assert_eq!(
+        render.observation_time.map(|time| time.to_rfc3339()),
+        Some("2023-12-21T22:30:00+00:00".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_inconsistent_ground_observers() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let site = json!({
+        "latitude": 51.4769f32,
+        "longitude": -0.0005f32,
+        "time": "2023-12-21T22:30:00Z"
+    });
+    let test_cases = vec![
+        (
+            json!({ "site": site, "altitude": 30f32 }),
+            "a site without an azimuth",
+        ),
+        (
+            json!({ "site": site, "altitude": 30f32, "azimuth": 0f32, "latitude": 30f32 }),
+            "a site alongside a latitude",
+        ),
+        (
+            json!({
+                "site": site,
+                "altitude": 30f32,
+                "azimuth": 0f32,
+                "observer_position": [100f32, 0f32, 0f32]
+            }),
+            "a site away from the Sun",
+        ),
+        (
+            json!({
+                "site": {
+                    "latitude": 95f32,
+                    "longitude": -0.0005f32,
+                    "time": "2023-12-21T22:30:00Z"
+                },
+                "altitude": 30f32,
+                "azimuth": 0f32
+            }),
+            "a site beyond the pole",
+        ),
+        (
+            json!({ "site": { "latitude": 51.4769f32, "longitude": 0f32, "time": "noon" }, "altitude": 30f32, "azimuth": 0f32 }),
+            "a site without a valid time",
+        ),
+        (
+            json!({
+                "fundamental_plane": { "basis": [[1f32, 0f32, 0f32], [0f32, 1f32, 0f32]] },
+                "latitude": 0f32,
+                "longitude": 0f32,
+                "altitude": 30f32
+            }),
+            "an altitude without a site",
+        ),
+        (json!({}), "neither a site nor a fundamental plane"),
+    ];
+
+    for (overrides, description) in test_cases {
+        // Act
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [60f32, 40f32],
+            "image_dimensions": [600u32, 400u32],
+            "observer_position": [0f32, 0f32, 0f32],
+            "filters": ["SDSS_G"],
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_sky_conditions() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "observer_position": [0f32, 0f32, 0f32],
+        "site": {
+            "latitude": 32.78f32,
+            "longitude": -105.82f32,
+            "elevation": 2788f32,
+            "time": "2023-07-03T06:00:00Z"
+        },
+        "altitude": 45f32,
+        "azimuth": 180f32,
+        "sky": { "bortle_class": 2 },
+        "filters": ["SDSS_R"],
+        "instrument": "sdss_imaging_camera",
+        "exposure": { "time": 53.9f32 },
+    });
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+
+    let render = sqlx::query!("SELECT sky_bortle_class, sky_zenith_brightness FROM renders")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued render.");
+
+    assert_eq!(render.sky_bortle_class, Some(2));
+    assert_eq!(render.sky_zenith_brightness, None);
+}
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_invalid_sky_conditions() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let site = json!({
+        "latitude": 32.78f32,
+        "longitude": -105.82f32,
+        "time": "2023-07-03T06:00:00Z"
+    });
+    let exposure = json!({ "time": 53.9f32 });
+    let test_cases = vec![
+        (
+            json!({
+                "fundamental_plane": { "basis": [[1f32, 0f32, 0f32], [0f32, 1f32, 0f32]] },
+                "latitude": 0f32,
+                "longitude": 0f32,
+                "sky": { "bortle_class": 2 },
+                "exposure": exposure
+            }),
+            "a sky without a site",
+        ),
+        (
+            json!({ "site": site, "sky": { "bortle_class": 2 } }),
+            "a sky without an exposure",
+        ),
+        (
+            json!({ "site": site, "sky": { "bortle_class": 10 }, "exposure": exposure }),
+            "a Bortle class beyond 9",
+        ),
+        (
+            json!({
+                "site": site,
+                "sky": { "bortle_class": 2, "zenith_brightness": 21.6f32 },
+                "exposure": exposure
+            }),
+            "both a Bortle class and a zenith brightness",
+        ),
+        (
+            json!({ "site": site, "sky": {}, "exposure": exposure }),
+            "a sky without a brightness",
+        ),
+        (
+            json!({ "site": site, "sky": { "zenith_brightness": 30f32 }, "exposure": exposure }),
+            "a zenith brightness darker than any sky",
+        ),
+        (
+            json!({
+                "site": site,
+                "sky": { "bortle_class": 2 },
+                "exposure": { "time": 53.9f32, "sky_brightness": 20.8f32 }
+            }),
+            "a sky alongside a uniform sky brightness",
+        ),
+    ];
+
+    for (overrides, description) in test_cases {
+        // Act
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "observer_position": [0f32, 0f32, 0f32],
+            "altitude": 45f32,
+            "azimuth": 180f32,
+            "filters": ["SDSS_R"],
+            "instrument": "sdss_imaging_camera",
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not reject {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_solar_system_flag() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let earth = [-4.40e-6f32, 1.63e-6f32, 1.04e-6f32];
+    let test_cases = vec![
+        (json!({ "solar_system": true }), 202, "the catalog's epoch"),
+        (
+            json!({ "solar_system": true, "epoch": 2023.5f64 }),
+            202,
+            "an epoch this century",
+        ),
+        (
+            json!({ "solar_system": true, "epoch": 1500f64 }),
+            400,
+            "an epoch before the ephemeris",
+        ),
+        (
+            json!({ "solar_system": true, "epoch": 3000f64 }),
+            400,
+            "an epoch after the ephemeris",
+        ),
+    ];
+
+    for (overrides, expected_status, description) in test_cases {
+        // Act
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [30f32, 20f32],
+            "image_dimensions": [300u32, 200u32],
+            "fundamental_plane": { "basis": [[1f32, 0f32, 0f32], [0f32, 1f32, 0f32]] },
+            "observer_position": earth,
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": ["SDSS_G"],
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+    }
+
+    let renders = sqlx::query!("SELECT solar_system FROM renders")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued renders.");
+    assert_eq!(renders.len(), 2);
+    assert!(renders.iter().all(|render| render.solar_system));
+}
+
+#[tokio::test]
+async fn test_post_renders_stores_deep_sky_flag() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![(json!({}), false), (json!({ "deep_sky": true }), true)];
+
+    for (overrides, _) in &test_cases {
+        // Act
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "fov": [30f32, 20f32],
+            "image_dimensions": [300u32, 200u32],
+            "fundamental_plane": { "basis": [[1f32, 0f32, 0f32], [0f32, 1f32, 0f32]] },
+            "observer_position": [0f32, 0f32, 0f32],
+            "latitude": 0f32,
+            "longitude": 0f32,
+            "filters": [0.6563f32],
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+        let response = client
+            .post(format!("{}/renders", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(202, response.status().as_u16());
+    }
+
+    let renders = sqlx::query!("SELECT deep_sky FROM renders ORDER BY created_at")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch queued renders.");
+    let stored: Vec<bool> = renders.iter().map(|render| render.deep_sky).collect();
+    let expected: Vec<bool> = test_cases.iter().map(|(_, deep_sky)| *deep_sky).collect();
+    assert_eq!(stored, expected);
+}
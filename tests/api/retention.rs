@@ -0,0 +1,232 @@
+use chrono::{Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use space_telescope::retention::{purge, PurgeReport, RetentionPolicy, PURGE_LOCK};
+use space_telescope::storage::render_key;
+
+use crate::helpers::{spawn_app, UndeletableStorage};
+
+#[test]
+fn test_retention_policies_without_positive_periods_are_rejected() {
+    let test_cases = vec![
+        (
+            RetentionPolicy {
+                queued_hours: 0,
+                ..RetentionPolicy::default()
+            },
+            "no queued retention",
+        ),
+        (
+            RetentionPolicy {
+                expired_hours: -1,
+                ..RetentionPolicy::default()
+            },
+            "a negative tombstone retention",
+        ),
+        (
+            RetentionPolicy {
+                purge_interval_minutes: 0,
+                ..RetentionPolicy::default()
+            },
+            "no purge interval",
+        ),
+    ];
+
+    for (policy, description) in test_cases {
+        // Act
+        let result = policy.validate();
+
+        // Assert
+        assert!(
+            result.is_err(),
+            "The policy was not rejected for {}.",
+            description
+        );
+    }
+    assert_eq!(RetentionPolicy::default().validate(), Ok(()));
+}
+
+#[tokio::test]
+async fn test_purge_applies_the_retention_of_each_status() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let policy = RetentionPolicy::default();
+    let stale_id = test_app
+        .queue_render_with(json!({ "longitude": 1f32 }))
+        .await;
+    let queued_id = test_app
+        .queue_render_with(json!({ "longitude": 2f32 }))
+        .await;
+    let old_id = test_app
+        .queue_render_with(json!({ "longitude": 3f32 }))
+        .await;
+    let recent_id = test_app
+        .queue_render_with(json!({ "longitude": 4f32 }))
+        .await;
+    let tombstone_id = test_app
+        .queue_render_with(json!({ "longitude": 5f32 }))
+        .await;
+    for render_id in [old_id, recent_id, tombstone_id] {
+        test_app.complete_render(render_id, b"png", b"fits").await;
+    }
+    test_app.age_render(stale_id, policy.queued_hours + 1).await;
+    test_app.age_render(old_id, policy.finished_hours + 1).await;
+    sqlx::query!(
+        "UPDATE renders SET purged_at = $1 WHERE id = $2",
+        Utc::now() - Duration::hours(policy.expired_hours + 1),
+        tombstone_id
+    )
+    .execute(&test_app.db_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let report = purge(
+        &test_app.db_pool,
+        test_app.storage.as_ref(),
+        &policy,
+        Utc::now(),
+    )
+    .await
+    .expect("Failed to purge renders.");
+
+    // Assert
+    assert_eq!(
+        report,
+        Some(PurgeReport {
+            queued_deleted: 1,
+            renders_expired: 1,
+            files_deleted: 2,
+            tombstones_deleted: 1,
+        })
+    );
+    let remaining: Vec<Uuid> = sqlx::query!("SELECT id FROM renders ORDER BY created_at")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|render| render.id)
+        .collect();
+    assert_eq!(remaining, vec![old_id, queued_id, recent_id]);
+    let old_image = test_app
+        .storage
+        .get(&render_key(old_id, "image.png"), None)
+        .await;
+    assert!(old_image.is_err());
+    let recent_image = test_app
+        .storage
+        .get(&render_key(recent_id, "image.png"), None)
+        .await;
+    assert!(recent_image.is_ok());
+    let response = reqwest::Client::new()
+        .get(format!("{}/renders/{}", &test_app.address, old_id))
+        .bearer_auth(test_app.links.owner_token(old_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let status: serde_json::Value =
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!(status["status"], "expired");
+    let audit = sqlx::query!("SELECT renders_expired, files_deleted FROM purges")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch the purge audit.");
+    assert_eq!((audit.renders_expired, audit.files_deleted), (1, 2));
+}
+
+#[tokio::test]
+async fn test_purge_keeps_files_shared_with_cached_renders() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let policy = RetentionPolicy::default();
+    let cached_id = test_app.queue_render().await;
+    test_app.complete_render(cached_id, b"png", b"fits").await;
+    let reused_id = test_app.queue_render().await;
+    test_app
+        .age_render(cached_id, policy.finished_hours + 1)
+        .await;
+
+    // Act
+    let report = purge(
+        &test_app.db_pool,
+        test_app.storage.as_ref(),
+        &policy,
+        Utc::now(),
+    )
+    .await
+    .expect("Failed to purge renders.")
+    .unwrap();
+
+    // Assert
+    assert_eq!(report.renders_expired, 1);
+    assert_eq!(report.files_deleted, 0);
+    let response = reqwest::Client::new()
+        .get(test_app.image_url(reused_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_purge_skips_while_another_instance_holds_the_lock() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let stale_id = test_app.queue_render().await;
+    test_app.age_render(stale_id, 10_000).await;
+    let mut other_instance = test_app.db_pool.acquire().await.unwrap();
+    sqlx::query!("SELECT pg_advisory_lock($1)", PURGE_LOCK)
+        .fetch_one(&mut other_instance)
+        .await
+        .unwrap();
+
+    // Act
+    let report = purge(
+        &test_app.db_pool,
+        test_app.storage.as_ref(),
+        &RetentionPolicy::default(),
+        Utc::now(),
+    )
+    .await
+    .expect("Failed to purge renders.");
+
+    // Assert
+    assert_eq!(report, None);
+    let renders = sqlx::query!("SELECT id FROM renders")
+        .fetch_all(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(renders.len(), 1);
+}
+
+#[tokio::test]
+async fn test_purge_commits_tombstones_when_files_fail_to_delete() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let policy = RetentionPolicy::default();
+    let old_id = test_app.queue_render().await;
+    test_app.complete_render(old_id, b"png", b"fits").await;
+    test_app.age_render(old_id, policy.finished_hours + 1).await;
+    let storage = UndeletableStorage(test_app.storage.clone());
+
+    // Act
+    let report = purge(&test_app.db_pool, &storage, &policy, Utc::now())
+        .await
+        .expect("Failed to purge renders.")
+        .unwrap();
+
+    // Assert
+    assert_eq!(report.renders_expired, 1);
+    assert_eq!(report.files_deleted, 0);
+    let render = sqlx::query!("SELECT purged_at FROM renders WHERE id = $1", old_id)
+        .fetch_one(&test_app.db_pool)
+        .await
+        .unwrap();
+    assert!(render.purged_at.is_some());
+    let audit = sqlx::query!("SELECT files_deleted FROM purges")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch the purge audit.");
+    assert_eq!(audit.files_deleted, 0);
+}
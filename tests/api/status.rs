@@ -0,0 +1,168 @@
+use serde_json::json;
+use uuid::Uuid;
+
+use space_telescope::artifacts::finish_render;
+use space_telescope::render::image::Image;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_submitted_renders_are_followed_with_the_returned_owner_token() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Act
+    let response = test_app
+        .post_render(Some(&test_app.api_key), json!({}))
+        .await;
+    assert_eq!(202, response.status().as_u16());
+    let location = response.headers()["location"].to_str().unwrap().to_string();
+    let submitted: serde_json::Value =
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    let status_url = format!(
+        "{}{}",
+        &test_app.address,
+        submitted["status_url"].as_str().unwrap()
+    );
+    let owner_token = submitted["owner_token"].as_str().unwrap();
+    let status = client
+        .get(&status_url)
+        .bearer_auth(owner_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let link = client
+        .post(format!("{}/links", status_url))
+        .bearer_auth(owner_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(location, submitted["status_url"].as_str().unwrap());
+    assert_eq!(200, status.status().as_u16());
+    let status: serde_json::Value = serde_json::from_slice(&status.bytes().await.unwrap()).unwrap();
+    assert_eq!(status["id"], submitted["id"]);
+    assert_eq!(status["status"], "queued");
+    assert_eq!(200, link.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_get_render_status_requires_the_owner_token() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+    let unknown_id = Uuid::new_v4();
+    let test_cases = vec![
+        (render_id, None, 401, "no owner token"),
+        (
+            render_id,
+            Some(test_app.links.owner_token(unknown_id)),
+            401,
+            "the owner token of another render",
+        ),
+        (
+            unknown_id,
+            Some(test_app.links.owner_token(unknown_id)),
+            404,
+            "an unknown render",
+        ),
+    ];
+
+    for (id, token, expected_status, description) in test_cases {
+        // Act
+        let mut request = client.get(format!("{}/renders/{}", &test_app.address, id));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_render_status_links_to_the_derivatives() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app.queue_render().await;
+    let status = |client: &reqwest::Client| {
+        client
+            .get(format!("{}/renders/{}", &test_app.address, render_id))
+            .bearer_auth(test_app.links.owner_token(render_id))
+            .send()
+    };
+    let queued: serde_json::Value =
+        serde_json::from_slice(&status(&client).await.unwrap().bytes().await.unwrap()).unwrap();
+    let mut image = Image::new(300, 200);
+    image.add(150, 100, 1e3);
+    finish_render(
+        &test_app.db_pool,
+        test_app.storage.as_ref(),
+        render_id,
+        &image,
+    )
+    .await
+    .expect("Failed to finish render.");
+
+    // Act
+    let response = status(&client).await.expect("Failed to execute request.");
+    assert_eq!(200, response.status().as_u16());
+    let finished: serde_json::Value =
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    let thumbnail_url = finished["artifacts"][0]["link"]["url"].as_str().unwrap();
+    let thumbnail = client
+        .get(format!("{}{}", &test_app.address, thumbnail_url))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let image_url = finished["image"]["url"].as_str().unwrap();
+    let fits = client
+        .get(format!("{}{}&format=fits", &test_app.address, image_url))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let (_, thumbnail_query) = thumbnail_url.split_once('?').unwrap();
+    let forged = client
+        .get(format!(
+            "{}/renders/{}/artifacts/png16?{}",
+            &test_app.address, render_id, thumbnail_query
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(queued["status"], "queued");
+    assert_eq!(queued["image"], serde_json::Value::Null);
+    assert_eq!(queued["artifacts"], json!([]));
+    assert_eq!(finished["status"], "finished");
+    let kinds: Vec<&str> = finished["artifacts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|artifact| artifact["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec!["thumbnail", "preview_jpeg", "preview_webp", "png16"]
+    );
+    assert_eq!(finished["artifacts"][3]["width"], 300);
+    assert_eq!(finished["artifacts"][3]["content_type"], "image/png");
+    assert_eq!(200, thumbnail.status().as_u16());
+    assert_eq!(thumbnail.headers()["content-type"], "image/jpeg");
+    assert_eq!(200, fits.status().as_u16());
+    assert_eq!(fits.headers()["content-type"], "application/fits");
+    assert!(fits.bytes().await.unwrap().starts_with(b"SIMPLE  ="));
+    assert_eq!(403, forged.status().as_u16());
+}
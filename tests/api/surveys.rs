@@ -0,0 +1,269 @@
+use serde_json::json;
+use uuid::Uuid;
+
+use space_telescope::artifacts::store_survey;
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::Star;
+use space_telescope::render::filter::{AstronomicalFilter, BroadBandFilter};
+use space_telescope::render::hips::HipsSurvey;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_post_surveys_returns_202_for_valid_body_fields() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "observer_position": [1f32, 2f32, 3f32],
+        "filters": ["SDSS_G", 0.6563f32],
+        "max_order": 1u32,
+    });
+
+    // Act
+    let response = client
+        .post(format!("{}/surveys", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+    let saved = sqlx::query!(
+        "SELECT observer_position, narrowband_filters, broadband_filters, max_order, tile_width FROM surveys"
+    )
+    .fetch_one(&test_app.db_pool)
+    .await
+    .expect("Failed to fetch saved survey.");
+    assert_eq!(saved.observer_position, vec![1.0, 2.0, 3.0]);
+    assert_eq!(saved.narrowband_filters, vec![0.6563]);
+    assert_eq!(saved.broadband_filters, vec!["SDSS_G"]);
+    assert_eq!(saved.max_order, 1);
+    assert_eq!(saved.tile_width, 512);
+}
+
+#[tokio::test]
+async fn test_post_surveys_returns_400_for_invalid_surveys() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let test_cases = vec![
+        (json!({ "filters": [] }), "no filters"),
+        (json!({ "max_order": 9u32 }), "too deep an order"),
+        (
+            json!({ "tile_width": 100u32 }),
+            "a tile width that is not a power of two",
+        ),
+        (json!({ "filters": [-0.5f32] }), "a negative wavelength"),
+    ];
+
+    for (fields, description) in test_cases {
+        let mut body = json!({
+            "email": "test@space-telescope.com",
+            "observer_position": [0f32, 0f32, 0f32],
+            "filters": ["SDSS_G"],
+        });
+        for (key, value) in fields.as_object().unwrap() {
+            body[key] = value.clone();
+        }
+
+        // Act
+        let response = client
+            .post(format!("{}/surveys", &test_app.address))
+            .header("X-API-Key", &test_app.api_key)
+            .header("Content-Type", "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            400,
+            response.status().as_u16(),
+            "The API did not fail with 400 Bad Request when the survey had {}.",
+            description
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_survey_file_serves_the_hips() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    client
+        .post(format!("{}/surveys", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(
+            json!({
+                "email": "test@space-telescope.com",
+                "observer_position": [0f32, 0f32, 0f32],
+                "filters": [0.6563f32],
+                "max_order": 0u32,
+                "tile_width": 64u32,
+            })
+            .to_string(),
+        )
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let survey_id = sqlx::query!("SELECT id FROM surveys")
+        .fetch_one(&test_app.db_pool)
+        .await
+        .expect("Failed to fetch saved survey.")
+        .id;
+    let catalog = Octree::new(vec![Star {
+        id: 0,
+        position: nalgebra::Vector3::new(10.0, 0.0, 0.0),
+        velocity: nalgebra::Vector3::zeros(),
+        absolute_magnitude: 2.0,
+        radius: None,
+    }]);
+    let survey = HipsSurvey {
+        observer_position: nalgebra::Vector3::zeros(),
+        max_order: 0,
+        tile_width: 64,
+    };
+    store_survey(
+        test_app.storage.as_ref(),
+        survey_id,
+        &survey,
+        &catalog,
+        &AstronomicalFilter::NarrowBand(0.6563),
+    )
+    .await
+    .expect("Failed to store survey.");
+    let test_cases = vec![
+        (
+            format!("/surveys/{}/0.6563um/properties", survey_id),
+            200,
+            Some("text/plain"),
+            "the properties",
+        ),
+        (
+            format!("/surveys/{}/0.6563um/Norder0/Dir0/Npix4.png", survey_id),
+            200,
+            Some("image/png"),
+            "a tile",
+        ),
+        (
+            format!("/surveys/{}/0.6563um/Norder1/Dir0/Npix4.png", survey_id),
+            404,
+            None,
+            "a tile deeper than the survey",
+        ),
+        (
+            format!("/surveys/{}/SDSS_G/properties", survey_id),
+            404,
+            None,
+            "a filter outside of the survey",
+        ),
+        (
+            format!("/surveys/{}/0.6563um/properties", Uuid::new_v4()),
+            404,
+            None,
+            "an unknown survey",
+        ),
+    ];
+
+    for (path, expected_status, expected_type, description) in test_cases {
+        // Act
+        let response = client
+            .get(format!("{}{}", &test_app.address, path))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+        if let Some(expected_type) = expected_type {
+            assert_eq!(response.headers()["content-type"], expected_type);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_post_surveys_returns_the_urls_of_the_hips() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let survey = HipsSurvey {
+        observer_position: nalgebra::Vector3::zeros(),
+        max_order: 0,
+        tile_width: 64,
+    };
+    let catalog = Octree::new(vec![]);
+
+    // Act
+    let response = client
+        .post(format!("{}/surveys", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(
+            json!({
+                "email": "test@space-telescope.com",
+                "observer_position": [0f32, 0f32, 0f32],
+                "filters": ["SDSS_G", 0.6563f32],
+                "max_order": 0u32,
+                "tile_width": 64u32,
+            })
+            .to_string(),
+        )
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(202, response.status().as_u16());
+    let location = response.headers()["Location"].to_str().unwrap().to_string();
+    let submitted: serde_json::Value =
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    let survey_id: Uuid = submitted["id"].as_str().unwrap().parse().unwrap();
+    assert_eq!(submitted["url"], location);
+    assert_eq!(location, format!("/surveys/{}", survey_id));
+    let hips_urls: Vec<&str> = submitted["hips_urls"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|url| url.as_str().unwrap())
+        .collect();
+    assert_eq!(
+        hips_urls,
+        vec![
+            format!("{}/SDSS_G/", location),
+            format!("{}/0.6563um/", location),
+        ]
+    );
+    for (hips_url, filter) in hips_urls.iter().zip([
+        AstronomicalFilter::BroadBand(BroadBandFilter::SDSS_G),
+        AstronomicalFilter::NarrowBand(0.6563),
+    ]) {
+        store_survey(
+            test_app.storage.as_ref(),
+            survey_id,
+            &survey,
+            &catalog,
+            &filter,
+        )
+        .await
+        .expect("Failed to store survey.");
+        let properties = client
+            .get(format!("{}{}properties", &test_app.address, hips_url))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(200, properties.status().as_u16());
+    }
+}
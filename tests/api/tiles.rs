@@ -0,0 +1,143 @@
+use chrono::Utc;
+use serde_json::json;
+
+use space_telescope::artifacts::finish_render;
+use space_telescope::render::image::Image;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn test_post_renders_returns_400_for_cubemap_tiles() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let body = json!({
+        "email": "test@space-telescope.com",
+        "image_dimensions": [512u32, 512u32],
+        "fundamental_plane": { "basis": [[1f32, 0f32, 0f32], [0f32, 1f32, 0f32]] },
+        "observer_position": [0f32, 0f32, 0f32],
+        "latitude": 0f32,
+        "longitude": 0f32,
+        "filters": ["SDSS_G"],
+        "mode": "cubemap",
+        "output": "tiles",
+    });
+
+    // Act
+    let response = client
+        .post(format!("{}/renders", &test_app.address))
+        .header("X-API-Key", &test_app.api_key)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[tokio::test]
+async fn test_get_render_tiles_serves_the_pyramid() {
+    // Arrange
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+    let render_id = test_app
+        .queue_render_with(json!({ "image_dimensions": [600u32, 300u32], "output": "tiles" }))
+        .await;
+    let image_render_id = test_app.queue_render().await;
+    let mut image = Image::new(600, 300);
+    image.add(300, 150, 1e3);
+    finish_render(
+        &test_app.db_pool,
+        test_app.storage.as_ref(),
+        render_id,
+        &image,
+    )
+    .await
+    .expect("Failed to finish render.");
+    let response = client
+        .get(format!("{}/renders/{}", &test_app.address, render_id))
+        .bearer_auth(test_app.links.owner_token(render_id))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let status: serde_json::Value =
+        serde_json::from_slice(&response.bytes().await.unwrap()).unwrap();
+    let (_, query) = status["tiles"]["url"]
+        .as_str()
+        .unwrap()
+        .split_once('?')
+        .unwrap();
+    let test_cases = vec![
+        (
+            format!("/renders/{}/tiles?{}", render_id, query),
+            200,
+            Some("application/xml"),
+            "the descriptor",
+        ),
+        (
+            format!("/renders/{}/tiles/10/2/1?{}", render_id, query),
+            200,
+            Some("image/jpeg"),
+            "a full resolution tile",
+        ),
+        (
+            format!("/renders/{}/tiles/0/0/0?{}", render_id, query),
+            200,
+            Some("image/jpeg"),
+            "the single pixel tile",
+        ),
+        (
+            format!("/renders/{}/tiles/10/3/0?{}", render_id, query),
+            404,
+            None,
+            "a tile outside of the image",
+        ),
+        (
+            format!("/renders/{}/tiles/10/0/0", render_id),
+            403,
+            None,
+            "an unsigned tile",
+        ),
+        (
+            format!(
+                "/renders/{}/tiles/0/0/0?{}",
+                image_render_id,
+                test_app
+                    .links
+                    .link(&format!("/renders/{}/tiles", image_render_id), Utc::now())
+                    .url
+                    .split_once('?')
+                    .unwrap()
+                    .1
+            ),
+            404,
+            None,
+            "a render output as an image",
+        ),
+    ];
+
+    for (path, expected_status, expected_type, description) in test_cases {
+        // Act
+        let response = client
+            .get(format!("{}{}", &test_app.address, path))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        // Assert
+        assert_eq!(
+            expected_status,
+            response.status().as_u16(),
+            "The API did not respond with {} for {}.",
+            expected_status,
+            description
+        );
+        if let Some(expected_type) = expected_type {
+            assert_eq!(response.headers()["content-type"], expected_type);
+        }
+    }
+    assert_eq!(status["output"], "tiles");
+    assert_eq!(status["image"], serde_json::Value::Null);
+}
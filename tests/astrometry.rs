@@ -0,0 +1,167 @@
+use nalgebra as na;
+
+use space_telescope::catalog::astrometry::{
+    propagate, Astrometry, CATALOG_EPOCH, KILOMETRES_PER_SECOND, SPEED_OF_LIGHT,
+};
+use space_telescope::catalog::octree::{Cone, Octree};
+use space_telescope::catalog::Star;
+
+/// Barnard's Star, the star with the largest proper motion
+fn barnards_star() -> Astrometry {
+    Astrometry {
+        ra: 269.448_5,
+        dec: 4.739_4,
+        parallax: 546.976,
+        pm_ra: -801.551,
+        pm_dec: 10_362.394,
+        radial_velocity: -110.47,
+    }
+}
+
+#[test]
+fn test_astrometry_converts_to_galactic_coordinates() {
+    let test_cases = vec![
+        (192.859_5, 27.128_3, na::Vector3::z(), "north galactic pole"),
+        (266.405, -28.936_2, na::Vector3::x(), "galactic centre"),
+    ];
+
+    for (ra, dec, expected, description) in test_cases {
+        // Arrange
+        let astrometry = Astrometry {
+            ra,
+            dec,
+            parallax: 10.0,
+            ..barnards_star()
+        };
+
+        // Act
+        let (position, _) = astrometry.position_and_velocity().unwrap();
+
+        // Assert
+        assert!((position.norm() - 100.0).abs() < 1e-3);
+        assert!(
+            position.normalize().dot(&expected) > 0.999_99,
+            "The {} is at {}.",
+            description,
+            position
+        );
+    }
+}
+
+#[test]
+fn test_astrometry_recovers_space_velocity() {
+    // Arrange
+    let astrometry = barnards_star();
+
+    // Act
+    let (position, velocity) = astrometry.position_and_velocity().unwrap();
+
+    // Assert
+    let radial_velocity = velocity.dot(&position.normalize());
+    let tangential_velocity = (velocity - radial_velocity * position.normalize()).norm();
+    assert!((position.norm() - 1.828).abs() < 1e-3);
+    assert!((radial_velocity + 110.47).abs() < 1e-3);
+    assert!((tangential_velocity - 90.08).abs() < 0.01);
+}
+
+#[test]
+fn test_stars_without_a_positive_parallax_are_skipped() {
+    for parallax in [0.0, -0.3, f64::NAN] {
+        // Arrange
+        let astrometry = Astrometry {
+            parallax,
+            ..barnards_star()
+        };
+
+        // Act
+        let star = Star::from_astrometry(0, &astrometry, 9.5);
+
+        // Assert
+        assert!(star.is_none(), "A parallax of {} placed a star.", parallax);
+    }
+}
+
+#[test]
+fn test_propagation_follows_proper_motion() {
+    // Arrange
+    let star = Star::from_astrometry(0, &barnards_star(), 9.5).unwrap();
+    let sun = na::Vector3::zeros();
+
+    // Act
+    let moved = propagate(
+        std::slice::from_ref(&star),
+        CATALOG_EPOCH + 10.0,
+        &sun,
+        false,
+    );
+
+    // Assert
+    let (before, after) = (star.position.cast::<f64>(), moved[0].position.cast::<f64>());
+    let angle = before
+        .cross(&after)
+        .norm()
+        .atan2(before.dot(&after))
+        .to_degrees()
+        * 3600.0;
+    let proper_motion = 10.393_35;
+    assert!(
+        (angle / (10.0 * proper_motion) - 1.0).abs() < 1e-3,
+        "Moved {} arcseconds in ten years.",
+        angle
+    );
+    assert_eq!(moved[0].absolute_magnitude, star.absolute_magnitude);
+}
+
+#[test]
+fn test_light_travel_time_shows_where_stars_were() {
+    // Arrange
+    let star = Star {
+        id: 0,
+        position: na::Vector3::new(1000.0, 0.0, 0.0),
+        velocity: na::Vector3::new(0.0, 100.0, 0.0),
+        absolute_magnitude: 0.0,
+        radius: None,
+    };
+    let sun = na::Vector3::zeros();
+
+    // Act
+    let seen = propagate(std::slice::from_ref(&star), CATALOG_EPOCH, &sun, true);
+
+    // Assert
+    let light_time = 1000.0 / SPEED_OF_LIGHT;
+    let expected_y = -100.0 * KILOMETRES_PER_SECOND * light_time;
+    assert!(
+        (seen[0].position.y as f64 - expected_y).abs() < 1e-4,
+        "Seen at {} instead of {} parsecs.",
+        seen[0].position.y,
+        expected_y
+    );
+}
+
+#[test]
+fn test_octree_at_epoch_finds_moved_stars() {
+    // Arrange
+    let star = Star {
+        id: 0,
+        position: na::Vector3::new(10.0, 0.0, 0.0),
+        // About a parsec per ten thousand years
+        velocity: na::Vector3::new(0.0, 100.0, 0.0),
+        absolute_magnitude: 0.0,
+        radius: None,
+    };
+    let octree = Octree::new(vec![star]);
+    let cone = Cone {
+        apex: na::Vector3::zeros(),
+        axis: na::Vector3::new(10.0, 1.0, 0.0).normalize(),
+        half_angle: 0.01,
+    };
+
+    // Act
+    let now = octree.query(&cone, f32::INFINITY);
+    let propagated = octree.at_epoch(CATALOG_EPOCH + 9778.0, &cone.apex, false);
+    let later = propagated.query(&cone, f32::INFINITY);
+
+    // Assert
+    assert!(now.is_empty());
+    assert_eq!(later.len(), 1);
+}
@@ -0,0 +1,34 @@
+use secrecy::ExposeSecret;
+
+use space_telescope::configuration::get_configuration;
+
+#[test]
+fn test_production_secrets_are_read_from_the_environment() {
+    // Arrange
+    std::env::set_var("APP_ENVIRONMENT", "production");
+    std::env::remove_var("APP_APPLICATION__HMAC_SECRET");
+    std::env::remove_var("APP_APPLICATION__ADMIN_TOKEN");
+
+    // Act
+    let without_secrets = get_configuration();
+    std::env::set_var("APP_APPLICATION__HMAC_SECRET", "production-hmac-secret");
+    std::env::set_var("APP_APPLICATION__ADMIN_TOKEN", "production-admin-token");
+    let with_secrets = get_configuration();
+
+    // Assert
+    assert!(
+        without_secrets.is_err(),
+        "Production started with the shipped secrets."
+    );
+    let application = with_secrets
+        .expect("Failed to read configuration.")
+        .application;
+    assert_eq!(
+        application.hmac_secret.expose_secret(),
+        "production-hmac-secret"
+    );
+    assert_eq!(
+        application.admin_token.expose_secret(),
+        "production-admin-token"
+    );
+}
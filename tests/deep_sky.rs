@@ -0,0 +1,201 @@
+use nalgebra as na;
+
+use space_telescope::catalog::deep_sky::{DeepSkyCatalog, Profile};
+use space_telescope::configuration::get_deep_sky_catalog;
+use space_telescope::render::camera::Camera;
+use space_telescope::render::deep_sky::{filter_flux, render_deep_sky};
+use space_telescope::render::filter::{AstronomicalFilter, BroadBandFilter};
+use space_telescope::render::image::Image;
+use space_telescope::render::optics::PsfKernel;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky::SkyFrame;
+
+/// Camera at the Sun looking at `target`
+fn camera(target: na::Vector3<f32>, fov: f32, size: u32) -> Camera {
+    let forward = target.normalize();
+    let side = forward.cross(&na::Vector3::z());
+    let frame = SkyFrame::from_basis(&[forward, side]).unwrap();
+    Camera::new(
+        na::Vector3::zeros(),
+        &frame,
+        0.0,
+        0.0,
+        Projection::Gnomonic,
+        [fov, fov],
+        [size, size],
+    )
+}
+
+/// Integral of `profile` over its plane, out to `outer_radius`
+fn integrate(profile: &Profile, outer_radius: f64) -> f64 {
+    let steps = 200_000;
+    let step = outer_radius / steps as f64;
+    (0..steps)
+        .map(|i| {
+            let radius = (i as f64 + 0.5) * step;
+            2.0 * std::f64::consts::PI * radius * profile.intensity(radius) * step
+        })
+        .sum()
+}
+
+#[test]
+fn test_bundled_catalog_loads() {
+    // Act
+    let catalog = get_deep_sky_catalog().expect("Failed to load the deep-sky catalog.");
+
+    // Assert
+    for name in ["m31", "m42", "m51", "m57", "m87"] {
+        assert!(catalog.get(name).is_some(), "{} is missing.", name);
+    }
+    // Andromeda is seen well inclined, the Whirlpool nearly face-on
+    let m31 = catalog.get("m31").unwrap();
+    let m51 = catalog.get("m51").unwrap();
+    assert!((0.25..0.35).contains(&m31.axis_ratio(&m31.position)));
+    assert!(m51.axis_ratio(&m51.position) > 0.9);
+}
+
+#[test]
+fn test_catalog_skips_files_that_are_not_yaml() {
+    // Arrange
+    let directory = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::copy(
+        "configuration/deep_sky/m57.yaml",
+        directory.join("m57.yaml"),
+    )
+    .unwrap();
+    std::fs::write(directory.join("README.md"), "# Deep-sky catalog\n").unwrap();
+
+    // Act
+    let catalog = DeepSkyCatalog::load(&directory);
+
+    // Assert
+    let catalog = catalog.expect("Stray files broke the deep-sky catalog.");
+    assert_eq!(catalog.objects.len(), 1);
+    assert!(catalog.get("m57").is_some());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_profiles_are_normalized() {
+    // Arrange
+    let profiles = [
+        Profile::Sersic { index: 1.0 },
+        Profile::Sersic { index: 4.0 },
+        Profile::Gaussian,
+        Profile::Shell { inner_radius: 0.6 },
+    ];
+
+    for profile in profiles {
+        // Act
+        let total = integrate(&profile, 100.0);
+        let enclosed = integrate(&profile, 1.0);
+
+        // Assert
+        assert!(
+            (total / profile.total_intensity() - 1.0).abs() < 1e-3,
+            "{:?} integrates to {} rather than {}.",
+            profile,
+            total,
+            profile.total_intensity()
+        );
+        assert!(
+            (enclosed / total - profile.enclosed_fraction()).abs() < 1e-3,
+            "{:?} encloses {} of its light.",
+            profile,
+            enclosed / total
+        );
+    }
+}
+
+#[test]
+fn test_resolved_and_unresolved_objects_keep_their_flux() {
+    // Arrange
+    let bundled = get_deep_sky_catalog().unwrap();
+    let filter = AstronomicalFilter::BroadBand(BroadBandFilter::SDSS_R);
+    let kernel = PsfKernel::default();
+
+    for name in ["m42", "m87"] {
+        let object = bundled.get(name).unwrap().clone();
+        let angular_radius = (object.radius / object.position.norm()).atan() as f64;
+        let expected = filter_flux(&object, &filter, angular_radius);
+        let resolved = camera(object.position, 2.0, 400);
+        let point = camera(object.position, 60.0, 400);
+        let catalog = DeepSkyCatalog {
+            objects: vec![object],
+        };
+
+        // Act
+        let mut resolved_image = Image::new(400, 400);
+        render_deep_sky(
+            &mut resolved_image,
+            &resolved,
+            &catalog,
+            &filter,
+            &kernel,
+            None,
+        );
+        let mut point_image = Image::new(400, 400);
+        render_deep_sky(&mut point_image, &point, &catalog, &filter, &kernel, None);
+
+        // Assert
+        for image in [&resolved_image, &point_image] {
+            let ratio = image.total_flux() / expected;
+            assert!(
+                (ratio - 1.0).abs() < 0.03,
+                "{} of the flux of {} was drawn.",
+                ratio,
+                name
+            );
+        }
+        let lit_pixels = resolved_image
+            .pixels
+            .iter()
+            .filter(|&&pixel| pixel > 0.0)
+            .count();
+        assert!(lit_pixels > 1_000, "{} pixels of {} lit.", lit_pixels, name);
+    }
+}
+
+#[test]
+fn test_narrowband_filters_isolate_emission_lines() {
+    // Arrange
+    let catalog = get_deep_sky_catalog().unwrap();
+    let nebula = catalog.get("m42").unwrap();
+    let galaxy = catalog.get("m87").unwrap();
+    let h_alpha = AstronomicalFilter::NarrowBand(0.6563);
+    let nitrogen = AstronomicalFilter::NarrowBand(0.6583);
+    let continuum = AstronomicalFilter::NarrowBand(0.6400);
+    let broadband = AstronomicalFilter::BroadBand(BroadBandFilter::SDSS_R);
+    let contrast = |filter: &AstronomicalFilter| {
+        filter_flux(nebula, filter, 1e-3)
+            / nebula.continuum_flux(1e-3)
+            / (filter_flux(galaxy, filter, 1e-3) / galaxy.continuum_flux(1e-3))
+    };
+
+    // Act
+    let h_alpha_contrast = contrast(&h_alpha);
+    let nitrogen_contrast = contrast(&nitrogen);
+    let continuum_contrast = contrast(&continuum);
+    let broadband_contrast = contrast(&broadband);
+
+    // Assert
+    // Only the 0.15 µm of Hα fall in the Hα filter, not the neighbouring [N II] line
+    assert!(
+        (h_alpha_contrast - 51.0).abs() < 0.1,
+        "{}",
+        h_alpha_contrast
+    );
+    assert!(
+        (nitrogen_contrast - 11.0).abs() < 0.1,
+        "{}",
+        nitrogen_contrast
+    );
+    assert_eq!(continuum_contrast, 1.0);
+    assert!(
+        h_alpha_contrast > 10.0 * broadband_contrast,
+        "{} against {} in broadband.",
+        h_alpha_contrast,
+        broadband_contrast
+    );
+}
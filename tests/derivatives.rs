@@ -0,0 +1,109 @@
+use image::{ColorType, GenericImageView};
+
+use space_telescope::render::derivatives::{
+    derivatives, Derivative, DerivativeKind, PREVIEW_SIZE, THUMBNAIL_SIZE,
+};
+use space_telescope::render::image::Image;
+
+/// Image with a bright star and a star ten thousand times fainter
+fn star_field(width: u32, height: u32) -> Image {
+    let mut image = Image::new(width, height);
+    image.add(width as i64 / 4, height as i64 / 2, 1e6);
+    image.add(3 * width as i64 / 4, height as i64 / 2, 1e2);
+    image
+}
+
+fn derivative(derivatives: &[Derivative], kind: DerivativeKind) -> &Derivative {
+    derivatives
+        .iter()
+        .find(|derivative| derivative.kind == kind)
+        .unwrap()
+}
+
+#[test]
+fn test_derivatives_are_resized_and_encoded() {
+    // Arrange
+    let image = star_field(1800, 900);
+
+    // Act
+    let derivatives = derivatives(&image).unwrap();
+
+    // Assert
+    let test_cases = [
+        (
+            DerivativeKind::Thumbnail,
+            image::ImageFormat::Jpeg,
+            (THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+        ),
+        (
+            DerivativeKind::PreviewJpeg,
+            image::ImageFormat::Jpeg,
+            (PREVIEW_SIZE, 800),
+        ),
+        (
+            DerivativeKind::PreviewWebp,
+            image::ImageFormat::WebP,
+            (PREVIEW_SIZE, 800),
+        ),
+        (DerivativeKind::Png16, image::ImageFormat::Png, (1800, 900)),
+    ];
+    assert_eq!(derivatives.len(), test_cases.len());
+    for (kind, format, dimensions) in test_cases {
+        let derivative = derivative(&derivatives, kind);
+        let decoded =
+            image::load_from_memory_with_format(&derivative.body, format).unwrap_or_else(|e| {
+                panic!(
+                    "The {:?} derivative is not a valid {:?}: {}",
+                    kind, format, e
+                )
+            });
+        assert_eq!(decoded.dimensions(), dimensions, "{:?}", kind);
+        assert_eq!(
+            (derivative.width, derivative.height),
+            dimensions,
+            "{:?}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn test_png16_is_linear_in_flux() {
+    // Arrange
+    let image = Image {
+        width: 4,
+        height: 1,
+        pixels: vec![10.0, 11.0, 12.0, 14.0],
+    };
+
+    // Act
+    let derivatives = derivatives(&image).unwrap();
+
+    // Assert
+    let png =
+        image::load_from_memory(&derivative(&derivatives, DerivativeKind::Png16).body).unwrap();
+    assert_eq!(png.color(), ColorType::L16);
+    let samples: Vec<u16> = png.to_luma16().into_raw();
+    assert_eq!(samples, vec![0, 16384, 32768, 65535]);
+}
+
+#[test]
+fn test_previews_keep_faint_sources_visible() {
+    // Arrange
+    let image = star_field(400, 200);
+
+    // Act
+    let derivatives = derivatives(&image).unwrap();
+
+    // Assert
+    let preview =
+        image::load_from_memory(&derivative(&derivatives, DerivativeKind::PreviewWebp).body)
+            .unwrap()
+            .to_luma8();
+    assert_eq!(preview.get_pixel(100, 100).0[0], u8::MAX);
+    assert!(
+        preview.get_pixel(300, 100).0[0] > 0,
+        "The faint star vanished from the preview."
+    );
+    assert_eq!(preview.get_pixel(200, 50).0[0], 0);
+}
@@ -0,0 +1,137 @@
+use nalgebra as na;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use space_telescope::render::camera::Camera;
+use space_telescope::render::detector::{Detector, Exposure};
+use space_telescope::render::expose;
+use space_telescope::render::image::Image;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky::SkyFrame;
+
+fn detector() -> Detector {
+    Detector {
+        quantum_efficiency: 0.8,
+        read_noise: 5.0,
+        dark_current: 0.01,
+        gain: 2.0,
+        full_well: 60000.0,
+        bit_depth: 16,
+    }
+}
+
+fn camera() -> Camera {
+    let frame = SkyFrame::from_basis(&[na::Vector3::x(), na::Vector3::y()]).unwrap();
+    Camera::new(
+        na::Vector3::zeros(),
+        &frame,
+        0.0,
+        0.0,
+        Projection::Gnomonic,
+        [0.1, 0.1],
+        [64, 64],
+    )
+}
+
+#[test]
+fn test_exposures_are_reproducible_from_their_seed() {
+    // Arrange
+    let mut image = Image::new(64, 64);
+    image.draw(&Default::default(), &na::Point2::new(32.0, 32.0), 1e-8);
+    let exposure = Exposure {
+        time: 30.0,
+        sky_brightness: Some(21.0),
+        seed: None,
+    };
+
+    // Act
+    let first = expose(&image, None, &camera(), 1.0, &detector(), &exposure, 42);
+    let second = expose(&image, None, &camera(), 1.0, &detector(), &exposure, 42);
+    let other = expose(&image, None, &camera(), 1.0, &detector(), &exposure, 43);
+
+    // Assert
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+}
+
+#[test]
+fn test_readout_noise_matches_the_detector_model() {
+    // Arrange
+    let detector = detector();
+    let rate = 100.0;
+    let time = 10.0;
+    let mut photons = Image::new(256, 256);
+    photons.pixels.fill(rate);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // Act
+    let readout = detector.read_out(&photons, time, &mut rng);
+
+    // Assert
+    let electrons: Vec<f64> = readout
+        .pixels
+        .iter()
+        .map(|&value| value as f64 * detector.gain as f64)
+        .collect();
+    let mean = electrons.iter().sum::<f64>() / electrons.len() as f64;
+    let variance =
+        electrons.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / electrons.len() as f64;
+    let expected_mean =
+        ((detector.quantum_efficiency * rate + detector.dark_current) * time) as f64;
+    // Shot noise, read noise and quantization noise add in quadrature
+    let expected_variance = expected_mean
+        + (detector.read_noise as f64).powi(2)
+        + (detector.gain as f64).powi(2) / 12.0;
+    assert!(
+        (mean / expected_mean - 1.0).abs() < 0.01,
+        "Mean of {} electrons, expected {}.",
+        mean,
+        expected_mean
+    );
+    assert!(
+        (variance / expected_variance - 1.0).abs() < 0.05,
+        "Variance of {} electrons squared, expected {}.",
+        variance,
+        expected_variance
+    );
+}
+
+#[test]
+fn test_bright_pixels_saturate() {
+    let test_cases = vec![
+        (
+            Detector {
+                read_noise: 0.0,
+                ..detector()
+            },
+            30000,
+            "full well",
+        ),
+        (
+            Detector {
+                read_noise: 0.0,
+                bit_depth: 12,
+                ..detector()
+            },
+            4095,
+            "analog-to-digital converter",
+        ),
+    ];
+
+    for (detector, expected, description) in test_cases {
+        // Arrange
+        let mut photons = Image::new(4, 4);
+        photons.pixels.fill(1e9);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // Act
+        let readout = detector.read_out(&photons, 1.0, &mut rng);
+
+        // Assert
+        assert!(
+            readout.pixels.iter().all(|&value| value == expected),
+            "Pixels were not clipped by the {}.",
+            description
+        );
+    }
+}
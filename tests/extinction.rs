@@ -0,0 +1,182 @@
+use nalgebra as na;
+
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::Star;
+use space_telescope::render::camera::Camera;
+use space_telescope::render::extinction::{
+    extinction_ratio, DustDisk, DustGrid, DustModel, Extinction,
+};
+use space_telescope::render::optics::PsfKernel;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::render_flux;
+use space_telescope::render::sky::SkyFrame;
+
+#[test]
+fn test_extinction_law_reddens_starlight() {
+    // Arrange
+    let wavelengths = [0.2, 0.3551, 0.4686, 0.55, 0.6166, 1.0, 2.2];
+
+    // Act
+    let ratios: Vec<f32> = wavelengths.iter().map(|&w| extinction_ratio(w)).collect();
+
+    // Assert
+    assert!((extinction_ratio(0.549) - 1.0).abs() < 0.01);
+    // Away from the 0.2175 micrometre bump, blue light is extincted more than red light
+    for pair in ratios[1..].windows(2) {
+        assert!(pair[0] > pair[1], "{:?} is not decreasing.", ratios);
+    }
+}
+
+#[test]
+fn test_dust_disk_is_thicker_along_the_galactic_plane() {
+    // Arrange
+    let disk = DustDisk::default();
+    let sun = na::Vector3::zeros();
+
+    // Act
+    let in_plane = disk.column(&sun, &na::Vector3::new(0.0, 1000.0, 0.0));
+    let towards_pole = disk.column(&sun, &na::Vector3::new(0.0, 0.0, 1000.0));
+    let towards_centre = disk.column(&sun, &na::Vector3::new(1000.0, 0.0, 0.0));
+
+    // Assert
+    assert!((in_plane - 1.0).abs() < 0.05, "{} magnitudes.", in_plane);
+    // The column towards the pole converges to the density times the scale height
+    assert!(
+        (towards_pole - 0.125).abs() < 0.005,
+        "{} magnitudes.",
+        towards_pole
+    );
+    assert!(towards_centre > in_plane);
+}
+
+#[test]
+fn test_dust_grids_round_trip_through_files() {
+    // Arrange
+    let densities = (0..24).map(|i| i as f32 * 1e-4).collect();
+    let grid = DustGrid::new(
+        na::Vector3::new(-10.0, -5.0, 0.0),
+        5.0,
+        [4, 3, 2],
+        densities,
+    )
+    .expect("Failed to build dust grid.");
+    let mut bytes = vec![];
+
+    // Act
+    grid.write(&mut bytes).expect("Failed to write dust grid.");
+    let read = DustGrid::read(&mut bytes.as_slice()).expect("Failed to read dust grid.");
+
+    // Assert
+    assert_eq!(grid, read);
+    assert!(DustGrid::read(&mut &bytes[..bytes.len() - 1]).is_err());
+    assert!(DustGrid::read(&mut &b"DUTS"[..]).is_err());
+}
+
+#[test]
+fn test_dust_grids_with_oversized_headers_are_rejected() {
+    // Arrange
+    let header = |dimensions: [u32; 3]| {
+        let mut bytes = b"DUST".to_vec();
+        for dimension in dimensions {
+            bytes.extend(dimension.to_le_bytes());
+        }
+        for value in [0f32, 0.0, 0.0, 1.0] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(0f32.to_le_bytes());
+        bytes
+    };
+    let overflowing = header([u32::MAX; 3]);
+    let huge = header([65_536, 65_536, 1]);
+
+    // Act
+    let overflowing = DustGrid::read(&mut overflowing.as_slice());
+    let huge = DustGrid::read(&mut huge.as_slice());
+
+    // Assert
+    assert!(overflowing.is_err());
+    assert!(huge.is_err());
+}
+
+#[test]
+fn test_dust_grids_interpolate_between_voxels() {
+    // Arrange
+    let grid = DustGrid::new(na::Vector3::zeros(), 10.0, [2, 1, 1], vec![1e-3, 3e-3])
+        .expect("Failed to build dust grid.");
+
+    // Act
+    let centres = [
+        grid.density(&na::Vector3::new(5.0, 5.0, 5.0)),
+        grid.density(&na::Vector3::new(15.0, 5.0, 5.0)),
+    ];
+    let between = grid.density(&na::Vector3::new(10.0, 5.0, 5.0));
+    let outside = grid.density(&na::Vector3::new(25.0, 5.0, 5.0));
+    let column = grid.column(
+        &na::Vector3::new(-10.0, 5.0, 5.0),
+        &na::Vector3::new(30.0, 5.0, 5.0),
+    );
+
+    // Assert
+    assert!((centres[0] - 1e-3).abs() < 1e-7);
+    assert!((centres[1] - 3e-3).abs() < 1e-7);
+    assert!((between - 2e-3).abs() < 1e-7);
+    assert_eq!(outside, 0.0);
+    assert!((column - 0.04).abs() < 1e-3, "{} magnitudes.", column);
+}
+
+#[test]
+fn test_render_flux_dims_stars_behind_dust() {
+    // Arrange
+    let star = Star {
+        id: 0,
+        position: na::Vector3::new(0.0, 2000.0, 0.0),
+        velocity: na::Vector3::zeros(),
+        absolute_magnitude: -5.0,
+        radius: None,
+    };
+    let octree = Octree::new(vec![star.clone()]);
+    let frame = SkyFrame::from_basis(&[na::Vector3::y(), na::Vector3::z()]).unwrap();
+    // Looking along the galactic plane
+    let observer = na::Vector3::new(0.0, -2000.0, 0.0);
+    let camera = Camera::new(
+        observer,
+        &frame,
+        0.0,
+        0.0,
+        Projection::Gnomonic,
+        [1.0, 1.0],
+        [64, 64],
+    );
+    let disk = DustDisk::default();
+    let wavelength = 0.4686;
+    let extinction = Extinction::new(&disk, wavelength);
+
+    // Act
+    let clear = render_flux(
+        &camera,
+        &octree,
+        &PsfKernel::default(),
+        wavelength,
+        f32::INFINITY,
+        None,
+    );
+    let dusty = render_flux(
+        &camera,
+        &octree,
+        &PsfKernel::default(),
+        wavelength,
+        f32::INFINITY,
+        Some(&extinction),
+    );
+
+    // Assert
+    assert!(clear.total_flux() > 0.0, "The star is not in view.");
+    let dimming = -2.5 * (dusty.total_flux() / clear.total_flux()).log10();
+    let expected = extinction_ratio(wavelength) * disk.column(&observer, &star.position);
+    assert!(
+        (dimming as f32 - expected).abs() < 1e-3,
+        "Dimmed by {} magnitudes instead of {}.",
+        dimming,
+        expected
+    );
+}
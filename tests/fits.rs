@@ -0,0 +1,35 @@
+use space_telescope::render::fits::{encode, BLOCK_SIZE};
+use space_telescope::render::image::Image;
+
+/// Value of the header card for `keyword`
+fn header_value(fits: &[u8], keyword: &str) -> Option<String> {
+    fits[..BLOCK_SIZE].chunks_exact(80).find_map(|card| {
+        let card = std::str::from_utf8(card).unwrap();
+        (card[..8].trim_end() == keyword).then(|| card[10..].trim().to_string())
+    })
+}
+
+#[test]
+fn test_fits_images_keep_the_flux_of_every_pixel() {
+    // Arrange
+    let mut image = Image::new(3, 2);
+    image.add(0, 0, 1.5);
+    image.add(2, 1, -0.25);
+
+    // Act
+    let fits = encode(&image);
+
+    // Assert
+    assert_eq!(fits.len(), 2 * BLOCK_SIZE);
+    assert_eq!(header_value(&fits, "SIMPLE").as_deref(), Some("T"));
+    assert_eq!(header_value(&fits, "BITPIX").as_deref(), Some("-32"));
+    assert_eq!(header_value(&fits, "NAXIS1").as_deref(), Some("3"));
+    assert_eq!(header_value(&fits, "NAXIS2").as_deref(), Some("2"));
+    assert!(header_value(&fits, "END").is_some());
+    let pixels: Vec<f32> = fits[BLOCK_SIZE..BLOCK_SIZE + 24]
+        .chunks_exact(4)
+        .map(|bytes| f32::from_be_bytes(bytes.try_into().unwrap()))
+        .collect();
+    // The bottom row comes first
+    assert_eq!(pixels, vec![0.0, 0.0, -0.25, 1.5, 0.0, 0.0]);
+}
@@ -0,0 +1,190 @@
+use chrono::{TimeZone, Utc};
+use nalgebra as na;
+
+use space_telescope::catalog::astrometry::Astrometry;
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::Star;
+use space_telescope::render::camera::Camera;
+use space_telescope::render::ground::{Atmosphere, Site};
+use space_telescope::render::optics::PsfKernel;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::render_flux;
+
+fn site(latitude: f32, longitude: f32) -> Site {
+    Site {
+        latitude,
+        longitude,
+        elevation: 0.0,
+        time: Utc.with_ymd_and_hms(2023, 6, 21, 4, 0, 0).unwrap(),
+    }
+}
+
+/// Catalog direction of an equatorial position
+fn direction(ra: f64, dec: f64) -> na::Vector3<f32> {
+    let (position, _) = Astrometry {
+        ra,
+        dec,
+        parallax: 1.0,
+        pm_ra: 0.0,
+        pm_dec: 0.0,
+        radial_velocity: 0.0,
+    }
+    .position_and_velocity()
+    .unwrap();
+    position.normalize()
+}
+
+#[test]
+fn test_sidereal_time_matches_almanac() {
+    // Arrange
+    let greenwich = Site {
+        // Meeus, Astronomical Algorithms, example 12.a
+        time: Utc.with_ymd_and_hms(1987, 4, 10, 0, 0, 0).unwrap(),
+        ..site(0.0, 0.0)
+    };
+    let eastern = Site {
+        longitude: 90.0,
+        ..greenwich
+    };
+
+    // Act
+    let sidereal_time = greenwich.local_sidereal_time();
+
+    // Assert
+    assert!((sidereal_time - 197.693_195).abs() < 1e-5);
+    assert!((eastern.local_sidereal_time() - (sidereal_time + 90.0)).abs() < 1e-6);
+}
+
+#[test]
+fn test_horizon_frame_tracks_the_celestial_sphere() {
+    let test_cases = vec![(51.48, -0.0), (-33.9, 18.4), (19.82, -155.47)];
+
+    for (latitude, longitude) in test_cases {
+        // Arrange
+        let site = site(latitude, longitude);
+
+        // Act
+        let atmosphere = site.atmosphere();
+        let frame = site.horizon_frame();
+
+        // Assert
+        let celestial_pole = direction(0.0, 90.0);
+        // Precession moves the pole by a fraction of a degree since J2000
+        assert!(
+            (atmosphere.altitude(&celestial_pole) - latitude).abs() < 0.5,
+            "The celestial pole is not at the altitude of a site at latitude {}.",
+            latitude
+        );
+        let zenith_ra = site.local_sidereal_time();
+        assert!(atmosphere.altitude(&direction(zenith_ra, latitude as f64)) > 89.5);
+        assert!((frame.primary.cross(&frame.secondary) - frame.pole).norm() < 1e-5);
+    }
+}
+
+#[test]
+fn test_refraction_lifts_stars_near_the_horizon() {
+    // Arrange
+    let atmosphere = Atmosphere {
+        zenith: na::Vector3::z(),
+        pressure: 1.0,
+    };
+    let at_altitude = |altitude: f32| {
+        let (sin, cos) = altitude.to_radians().sin_cos();
+        na::Vector3::new(cos, 0.0, sin)
+    };
+
+    // Act
+    let horizon = atmosphere.refraction(0.0);
+    let halfway = atmosphere.refraction(45.0);
+    let just_below = atmosphere.refract(&at_altitude(-0.3));
+    let far_below = atmosphere.refract(&at_altitude(-2.0));
+
+    // Assert
+    assert!((horizon * 60.0 - 29.0).abs() < 1.0, "{} degrees.", horizon);
+    assert!((halfway * 60.0 - 1.0).abs() < 0.1, "{} degrees.", halfway);
+    let just_below = just_below.expect("Refraction did not lift the star into view.");
+    assert!(atmosphere.altitude(&just_below) > 0.0);
+    assert!(far_below.is_none());
+}
+
+#[test]
+fn test_airmass_extinction_dims_low_blue_stars_most() {
+    // Arrange
+    let sea_level = Atmosphere {
+        zenith: na::Vector3::z(),
+        pressure: 1.0,
+    };
+    let mountain = site(19.82, -155.47);
+    let mountain = Site {
+        elevation: 4200.0,
+        ..mountain
+    }
+    .atmosphere();
+
+    // Act
+    let airmasses = [90.0, 30.0, 0.0].map(Atmosphere::airmass);
+
+    // Assert
+    assert!((airmasses[0] - 1.0).abs() < 1e-3);
+    assert!((airmasses[1] - 2.0).abs() < 0.01);
+    assert!((airmasses[2] - 38.0).abs() < 0.5);
+    assert!(sea_level.extinction_coefficient(0.36) > sea_level.extinction_coefficient(0.62));
+    assert!(mountain.extinction_coefficient(0.47) < sea_level.extinction_coefficient(0.47));
+}
+
+#[test]
+fn test_render_flux_hides_stars_below_the_horizon() {
+    // Arrange
+    let site = site(-24.63, -70.4);
+    let frame = site.horizon_frame();
+    let atmosphere = site.atmosphere();
+    // Stars due north, two degrees above and two degrees below the horizon
+    let stars: Vec<Star> = [2.0, -2.0]
+        .iter()
+        .enumerate()
+        .map(|(id, &altitude)| Star {
+            id: id as u64,
+            position: 100.0 * frame.direction(0.0, altitude),
+            velocity: na::Vector3::zeros(),
+            absolute_magnitude: 0.0,
+            radius: None,
+        })
+        .collect();
+    let expected_flux =
+        10f64.powf(-0.4 * stars[0].apparent_magnitude(&na::Vector3::zeros()) as f64);
+    let octree = Octree::new(stars);
+    let camera = Camera::new(
+        na::Vector3::zeros(),
+        &frame,
+        0.0,
+        0.0,
+        Projection::Gnomonic,
+        [10.0, 10.0],
+        [200, 200],
+    )
+    .with_atmosphere(atmosphere);
+    let wavelength = 0.55;
+
+    // Act
+    let image = render_flux(
+        &camera,
+        &octree,
+        &PsfKernel::default(),
+        wavelength,
+        f32::INFINITY,
+        None,
+    );
+
+    // Assert
+    let dimming = -2.5 * (image.total_flux() / expected_flux).log10();
+    let apparent = atmosphere
+        .refract(&frame.direction(0.0, 2.0))
+        .expect("The star above the horizon was hidden.");
+    let expected = atmosphere.magnitudes(&apparent, wavelength);
+    assert!(
+        (dimming as f32 - expected).abs() < 1e-3,
+        "Dimmed by {} magnitudes instead of {}.",
+        dimming,
+        expected
+    );
+}
@@ -0,0 +1,154 @@
+use nalgebra as na;
+
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::Star;
+use space_telescope::render::filter::{AstronomicalFilter, BroadBandFilter};
+use space_telescope::render::healpix::{nest_to_xyf, pixel_count, pixel_direction, xyf_to_nest};
+use space_telescope::render::hips::{tile_path, HipsSurvey};
+
+/// Star of apparent magnitude 2 seen from the origin, 10 parsecs away along `direction`
+fn star(direction: na::Vector3<f32>) -> Star {
+    Star {
+        id: 0,
+        position: direction.normalize() * 10.0,
+        velocity: na::Vector3::zeros(),
+        absolute_magnitude: 2.0,
+        radius: None,
+    }
+}
+
+fn survey(max_order: u32) -> HipsSurvey {
+    HipsSurvey {
+        observer_position: na::Vector3::zeros(),
+        max_order,
+        tile_width: 64,
+    }
+}
+
+#[test]
+fn test_healpix_pixels_tile_the_sphere() {
+    // Arrange
+    let order = 2;
+
+    // Act
+    let directions: Vec<na::Vector3<f32>> = (0..pixel_count(order))
+        .map(|npix| pixel_direction(order, npix))
+        .collect();
+
+    // Assert
+    // Base pixels 4 and 0 are centred on the equator at longitude 0 and above it at 45°
+    assert!((pixel_direction(0, 4) - na::Vector3::x()).norm() < 1e-6);
+    let north = pixel_direction(0, 0);
+    assert!((north.z - 2.0 / 3.0).abs() < 1e-6);
+    assert!((north.y.atan2(north.x).to_degrees() - 45.0).abs() < 1e-4);
+    assert!(directions
+        .iter()
+        .all(|direction| (direction.norm() - 1.0).abs() < 1e-5));
+    let mean: na::Vector3<f32> =
+        directions.iter().sum::<na::Vector3<f32>>() / directions.len() as f32;
+    assert!(mean.norm() < 1e-5, "The pixels are not spread evenly.");
+    for npix in 0..pixel_count(order) {
+        let (x, y, face) = nest_to_xyf(order, npix);
+        assert_eq!(xyf_to_nest(order, x, y, face), npix);
+    }
+}
+
+#[test]
+fn test_tiles_follow_the_hips_layout() {
+    assert_eq!(tile_path(0, 7), "Norder0/Dir0/Npix7.png");
+    assert_eq!(tile_path(5, 12_345), "Norder5/Dir10000/Npix12345.png");
+}
+
+#[test]
+fn test_surveys_render_stars_into_their_tile() {
+    // Arrange
+    let catalog = Octree::new(vec![star(na::Vector3::x())]);
+    let filter = AstronomicalFilter::BroadBand(BroadBandFilter::SDSS_G);
+
+    // Act
+    let lit = survey(0).face_tiles(&catalog, &filter, 4).unwrap();
+    let dark = survey(0).face_tiles(&catalog, &filter, 6).unwrap();
+
+    // Assert
+    let lit = image::load_from_memory(&lit[0].body).unwrap().to_luma8();
+    let dark = image::load_from_memory(&dark[0].body).unwrap().to_luma8();
+    assert_eq!(lit.dimensions(), (64, 64));
+    let (brightest, _) = lit
+        .enumerate_pixels()
+        .map(|(x, y, pixel)| ((x, y), pixel.0[0]))
+        .max_by_key(|(_, value)| *value)
+        .unwrap();
+    // The star lies at the centre of base pixel 4
+    assert!(
+        brightest.0.abs_diff(32) <= 1 && brightest.1.abs_diff(32) <= 1,
+        "The star was drawn at {:?}.",
+        brightest
+    );
+    assert!(dark.pixels().all(|pixel| pixel.0[0] == 0));
+}
+
+#[test]
+fn test_lower_orders_average_their_children() {
+    // Arrange
+    let catalog = Octree::new(vec![star(pixel_direction(1, 17))]);
+    let filter = AstronomicalFilter::NarrowBand(0.6563);
+
+    // Act
+    let tiles = survey(1).face_tiles(&catalog, &filter, 4).unwrap();
+
+    // Assert
+    let paths: Vec<String> = tiles.iter().map(|tile| tile.path()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "Norder1/Dir0/Npix16.png",
+            "Norder1/Dir0/Npix17.png",
+            "Norder1/Dir0/Npix18.png",
+            "Norder1/Dir0/Npix19.png",
+            "Norder0/Dir0/Npix4.png",
+        ]
+    );
+    let brightness = |index: usize| {
+        image::load_from_memory(&tiles[index].body)
+            .unwrap()
+            .to_luma8()
+            .pixels()
+            .map(|pixel| pixel.0[0] as u32)
+            .sum::<u32>()
+    };
+    assert!(brightness(1) > 0);
+    assert_eq!(brightness(0), 0);
+    // Pixel 17 holds the upper x half and lower y half of base pixel 4, the bottom right of its tile
+    let parent = image::load_from_memory(&tiles[4].body).unwrap().to_luma8();
+    let lit = |x_range: std::ops::Range<u32>, y_range: std::ops::Range<u32>| {
+        y_range
+            .flat_map(|y| x_range.clone().map(move |x| (x, y)))
+            .any(|(x, y)| parent.get_pixel(x, y).0[0] > 0)
+    };
+    assert!(lit(32..64, 32..64));
+    assert!(!lit(0..32, 0..64));
+}
+
+#[test]
+fn test_properties_describe_the_survey() {
+    // Arrange
+    let survey = survey(3);
+    let filter = AstronomicalFilter::BroadBand(BroadBandFilter::SDSS_R);
+
+    // Act
+    let properties = survey.properties(uuid::Uuid::nil(), &filter, chrono::Utc::now());
+
+    // Assert
+    for line in [
+        "hips_order           = 3",
+        "hips_order_min       = 0",
+        "hips_tile_width      = 64",
+        "hips_tile_format     = png",
+        "hips_frame           = galactic",
+        "dataproduct_type     = image",
+    ] {
+        assert!(properties.contains(line), "{} is missing.", line);
+    }
+    assert!(properties
+        .contains("ivo://space-telescope/surveys/00000000-0000-0000-0000-000000000000/SDSS_R"));
+}
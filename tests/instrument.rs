@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use uuid::Uuid;
+
+use space_telescope::render::instrument::InstrumentRegistry;
+
+#[test]
+fn test_instruments_load_only_yaml_files() {
+    // Arrange
+    let directory = std::env::temp_dir().join(Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::copy(
+        Path::new("configuration/instruments/sdss_imaging_camera.yaml"),
+        directory.join("sdss_imaging_camera.yaml"),
+    )
+    .unwrap();
+    std::fs::write(directory.join("README.md"), "# Instrument presets\n").unwrap();
+    std::fs::write(directory.join("sdss_imaging_camera.yaml~"), "not: [yaml").unwrap();
+
+    // Act
+    let registry = InstrumentRegistry::load(&directory);
+
+    // Assert
+    let registry = registry.expect("Stray files broke the instrument registry.");
+    assert!(registry.get("sdss_imaging_camera").is_some());
+    assert!(registry.get("README").is_none());
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_negative_image_dimensions_are_rejected_before_the_detector_size() {
+    // Arrange
+    let registry = InstrumentRegistry::load(Path::new("configuration/instruments")).unwrap();
+    let instrument = registry.get("sdss_imaging_camera").unwrap();
+
+    // Act
+    let result = instrument.validate_geometry([0.1, 0.1], [-1, 1024]);
+
+    // Assert
+    assert_eq!(
+        result,
+        Err("image_dimensions values must be positive.".to_string())
+    );
+}
+
+#[test]
+fn test_pixel_scales_follow_from_the_focal_length_and_pixel_size() {
+    // Arrange
+    let registry = InstrumentRegistry::load(Path::new("configuration/instruments")).unwrap();
+    let published = [
+        ("amateur_refractor", 1.62),
+        ("hubble_wfc3_uvis", 0.0395),
+        ("sdss_imaging_camera", 0.396),
+    ];
+
+    for (name, pixel_scale) in published {
+        // Act
+        let instrument = registry.get(name).unwrap();
+
+        // Assert
+        assert!(
+            (instrument.pixel_scale() / pixel_scale - 1.0).abs() < 5e-3,
+            "{} has a pixel scale of {}\".",
+            name,
+            instrument.pixel_scale()
+        );
+    }
+}
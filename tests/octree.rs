@@ -0,0 +1,153 @@
+use nalgebra as na;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use space_telescope::catalog::octree::{Cone, Octree};
+use space_telescope::catalog::{luminosity, Star};
+use space_telescope::render::camera::Camera;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky::SkyFrame;
+
+/// Stars scattered uniformly through a cube of `half_size` parsecs around the origin
+fn random_stars(rng: &mut StdRng, count: usize, half_size: f32) -> Vec<Star> {
+    (0..count)
+        .map(|id| Star {
+            id: id as u64,
+            position: na::Vector3::from_fn(|_, _| rng.gen_range(-half_size..half_size)),
+            velocity: na::Vector3::zeros(),
+            absolute_magnitude: rng.gen_range(-5.0..15.0),
+            radius: None,
+        })
+        .collect()
+}
+
+fn sorted_ids<'a>(stars: impl IntoIterator<Item = &'a Star>) -> Vec<u64> {
+    let mut ids: Vec<u64> = stars.into_iter().map(|star| star.id).collect();
+    ids.sort_unstable();
+    ids
+}
+
+#[test]
+fn test_octree_query_matches_brute_force_search() {
+    // Arrange
+    let mut rng = StdRng::seed_from_u64(42);
+    let stars = random_stars(&mut rng, 20_000, 500.0);
+    let octree = Octree::new(stars.clone());
+
+    for _ in 0..50 {
+        let cone = Cone {
+            apex: na::Vector3::from_fn(|_, _| rng.gen_range(-600.0..600.0)),
+            axis: na::Vector3::from_fn(|_, _| rng.gen_range(-1.0..1.0)).normalize(),
+            half_angle: rng.gen_range(0.01f32..2.0),
+        };
+        let limiting_magnitude = rng.gen_range(0.0..20.0);
+
+        // Act
+        let visible = octree.query(&cone, limiting_magnitude);
+
+        // Assert
+        let expected = stars.iter().filter(|star| {
+            cone.contains(&star.position)
+                && star.apparent_magnitude(&cone.apex) <= limiting_magnitude
+        });
+        assert_eq!(sorted_ids(visible), sorted_ids(expected));
+    }
+}
+
+#[test]
+fn test_view_cone_contains_every_star_in_the_image() {
+    // Arrange
+    let mut rng = StdRng::seed_from_u64(7);
+    let stars = random_stars(&mut rng, 5_000, 100.0);
+    let octree = Octree::new(stars.clone());
+    let frame = SkyFrame::from_basis(&[na::Vector3::x(), na::Vector3::y()]).unwrap();
+    let test_cases = vec![
+        (Projection::Gnomonic, [5f32, 3f32]),
+        (Projection::Gnomonic, [120f32, 90f32]),
+        (Projection::Stereographic, [200f32, 150f32]),
+        (Projection::Orthographic, [180f32, 180f32]),
+        (Projection::AzimuthalEquidistant, [360f32, 360f32]),
+        (Projection::Equirectangular, [100f32, 60f32]),
+        (Projection::HammerAitoff, [360f32, 180f32]),
+    ];
+
+    for (projection, fov) in test_cases {
+        let camera = Camera::new(
+            na::Vector3::new(3.0, -2.0, 1.0),
+            &frame,
+            30.0,
+            -20.0,
+            projection,
+            fov,
+            [400, 300],
+        );
+
+        // Act
+        let visible = camera.visible_stars(&octree, f32::INFINITY);
+
+        // Assert
+        let expected = stars.iter().filter(|star| {
+            camera
+                .pixel_coordinates(&star.position)
+                .map_or(false, |pixel| {
+                    (0.0..=400.0).contains(&pixel.x) && (0.0..=300.0).contains(&pixel.y)
+                })
+        });
+        assert_eq!(
+            sorted_ids(visible),
+            sorted_ids(expected),
+            "Stars were missed with a {:?} fov in the {:?} projection.",
+            fov,
+            projection
+        );
+    }
+}
+
+#[test]
+fn test_glows_conserve_the_light_of_faint_stars() {
+    // Arrange
+    let mut rng = StdRng::seed_from_u64(1234);
+    let stars = random_stars(&mut rng, 50_000, 1_000.0);
+    let octree = Octree::new(stars.clone());
+    let cone = Cone {
+        apex: na::Vector3::new(20_000.0, 0.0, 0.0),
+        axis: -na::Vector3::x(),
+        half_angle: std::f32::consts::PI,
+    };
+    let limiting_magnitude = 12.0;
+
+    // Act
+    let visible = octree.query_with_glow(&cone, limiting_magnitude, 1e-4);
+
+    // Assert
+    let expected_stars = stars
+        .iter()
+        .filter(|star| star.apparent_magnitude(&cone.apex) <= limiting_magnitude);
+    assert_eq!(
+        sorted_ids(visible.stars.iter().copied()),
+        sorted_ids(expected_stars)
+    );
+    assert!(
+        visible.glows.len() < stars.len() / 10,
+        "Faint stars were not aggregated: {} glows for {} stars.",
+        visible.glows.len(),
+        stars.len()
+    );
+
+    let total_luminosity: f64 = stars
+        .iter()
+        .map(|star| luminosity(star.absolute_magnitude))
+        .sum();
+    let rendered_luminosity: f64 = visible
+        .stars
+        .iter()
+        .map(|star| luminosity(star.absolute_magnitude))
+        .chain(
+            visible
+                .glows
+                .iter()
+                .map(|glow| luminosity(glow.absolute_magnitude)),
+        )
+        .sum();
+    assert!((rendered_luminosity / total_luminosity - 1.0).abs() < 1e-4);
+}
@@ -0,0 +1,156 @@
+use nalgebra as na;
+
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::Star;
+use space_telescope::render::camera::Camera;
+use space_telescope::render::image::Image;
+use space_telescope::render::optics::{Optics, Psf, PsfKernel, MAX_STAMP_RADIUS};
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky::SkyFrame;
+use space_telescope::render::{flux, render_flux};
+
+/// One arcsecond in radians
+const ARCSECOND: f32 = 4.848_137e-6;
+
+fn centroid(image: &Image) -> na::Point2<f32> {
+    let mut sum = na::Vector2::zeros();
+    for y in 0..image.height {
+        for x in 0..image.width {
+            sum += image.get(x, y) * na::Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+        }
+    }
+    na::Point2::from(sum / image.total_flux() as f32)
+}
+
+#[test]
+fn test_psf_stamps_conserve_flux_and_centroid() {
+    let kernels = vec![
+        PsfKernel::Gaussian { sigma: 0.3 },
+        PsfKernel::Gaussian { sigma: 2.5 },
+        PsfKernel::Moffat {
+            alpha: 2.0,
+            beta: 3.0,
+        },
+        PsfKernel::Airy { first_zero: 3.0 },
+    ];
+    let centers = vec![
+        na::Point2::new(32.0, 32.0),
+        na::Point2::new(31.3, 32.75),
+        na::Point2::new(32.5, 30.1),
+    ];
+
+    for kernel in &kernels {
+        for center in &centers {
+            // Arrange
+            let mut image = Image::new(64, 64);
+
+            // Act
+            image.draw(kernel, center, 100.0);
+
+            // Assert
+            assert!(
+                (image.total_flux() - 100.0).abs() < 1e-3,
+                "{:?} did not conserve flux.",
+                kernel
+            );
+            // Undersampled PSFs cannot place their centroid more precisely than a pixel
+            if kernel.sigma() < 1.0 {
+                continue;
+            }
+            let offset = (centroid(&image) - center).norm();
+            assert!(
+                offset < 0.05,
+                "{:?} placed at {} has its centroid {} pixels away.",
+                kernel,
+                center,
+                offset
+            );
+        }
+    }
+}
+
+#[test]
+fn test_wide_psf_stamps_are_capped() {
+    // Arrange
+    let glare = PsfKernel::Moffat {
+        alpha: 200.0,
+        beta: 2.5,
+    };
+    let center = na::Point2::new(150.5, 150.5);
+    let mut image = Image::new(301, 301);
+
+    // Act
+    image.draw(&glare, &center, 100.0);
+
+    // Assert
+    assert_eq!(glare.stamp_radius(), MAX_STAMP_RADIUS);
+    // The wings beyond the stamp are folded into it
+    assert!((image.total_flux() - 100.0).abs() < 1e-2);
+    assert_eq!(image.get(150, 150 - MAX_STAMP_RADIUS as u32 - 2), 0.0);
+    assert!(image.get(150, 150 - MAX_STAMP_RADIUS as u32 + 2) > 0.0);
+}
+
+#[test]
+fn test_airy_disks_grow_with_wavelength() {
+    // Arrange
+    let optics = Optics {
+        aperture_diameter: Some(2.4),
+        psf: Psf::Airy,
+    };
+
+    // Act
+    let blue = optics.kernel(0.4, 0.01 * ARCSECOND);
+    let red = optics.kernel(0.8, 0.01 * ARCSECOND);
+
+    // Assert
+    let (PsfKernel::Airy { first_zero: blue }, PsfKernel::Airy { first_zero: red }) = (blue, red)
+    else {
+        panic!("Airy optics did not produce Airy kernels.");
+    };
+    // The first dark ring of a 2.4m aperture at 400nm lies 0.042 arcseconds from the centre
+    assert!((blue - 4.19).abs() < 0.01);
+    assert!((red / blue - 2.0).abs() < 1e-4);
+    assert!(PsfKernel::Airy { first_zero: red }.profile(red) < 1e-6);
+}
+
+#[test]
+fn test_render_flux_accumulates_the_light_of_every_star_in_view() {
+    // Arrange
+    let stars: Vec<Star> = (0..10)
+        .map(|id| Star {
+            id,
+            position: na::Vector3::new(100.0, id as f32 - 4.5, 0.5 * id as f32 - 2.0),
+            velocity: na::Vector3::zeros(),
+            absolute_magnitude: id as f32,
+            radius: None,
+        })
+        .collect();
+    let expected_flux: f64 = stars
+        .iter()
+        .map(|star| flux(star.apparent_magnitude(&na::Vector3::zeros())) as f64)
+        .sum();
+    let octree = Octree::new(stars);
+    let frame = SkyFrame::from_basis(&[na::Vector3::x(), na::Vector3::y()]).unwrap();
+    let camera = Camera::new(
+        na::Vector3::zeros(),
+        &frame,
+        0.0,
+        0.0,
+        Projection::Gnomonic,
+        [10.0, 10.0],
+        [512, 512],
+    );
+
+    // Act
+    let image = render_flux(
+        &camera,
+        &octree,
+        &PsfKernel::default(),
+        0.55,
+        f32::INFINITY,
+        None,
+    );
+
+    // Assert
+    assert!((image.total_flux() / expected_flux - 1.0).abs() < 1e-4);
+}
@@ -0,0 +1,111 @@
+use nalgebra as na;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::Star;
+use space_telescope::render::camera::Camera;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::relativity::{Motion, SOLAR_TEMPERATURE};
+use space_telescope::render::sky::SkyFrame;
+
+#[test]
+fn test_aberration_crowds_stars_ahead() {
+    // Arrange
+    let motion = Motion::new(na::Vector3::new(0.0, 0.0, 0.5)).unwrap();
+    let sideways = na::Vector3::new(1.0, 0.0, 0.0);
+
+    // Act
+    let apparent = motion.aberrate(&sideways);
+    let restored = motion.inverse().aberrate(&apparent);
+
+    // Assert
+    // A source at right angles to the motion appears at arccos(beta)
+    assert!((apparent.z - 0.5).abs() < 1e-6);
+    assert!((apparent.norm() - 1.0).abs() < 1e-6);
+    assert!((restored - sideways).norm() < 1e-6);
+}
+
+#[test]
+fn test_doppler_shift_brightens_and_blueshifts_stars_ahead() {
+    // Arrange
+    let beta = 0.6;
+    let motion = Motion::new(na::Vector3::new(0.0, beta, 0.0)).unwrap();
+    let ahead = na::Vector3::y();
+    let behind = -na::Vector3::y();
+
+    // Act
+    let doppler_factor = motion.doppler_factor(&ahead);
+    let shift_ahead = motion.magnitude_shift(&ahead, 0.45, SOLAR_TEMPERATURE);
+    let shift_behind = motion.magnitude_shift(&behind, 0.45, SOLAR_TEMPERATURE);
+    let at_rest =
+        Motion::new(na::Vector3::zeros())
+            .unwrap()
+            .magnitude_shift(&ahead, 0.45, SOLAR_TEMPERATURE);
+
+    // Assert
+    assert!((doppler_factor - ((1.0 + beta) / (1.0 - beta)).sqrt()).abs() < 1e-5);
+    assert!(
+        shift_ahead < -1.0,
+        "Brightened by {} magnitudes.",
+        shift_ahead
+    );
+    assert!(shift_behind > 1.0, "Dimmed by {} magnitudes.", shift_behind);
+    assert!(at_rest.abs() < 1e-6);
+    assert!(motion.max_brightening(0.45, SOLAR_TEMPERATURE) >= -shift_ahead - 1e-4);
+}
+
+#[test]
+fn test_moving_cameras_see_the_starbow() {
+    // Arrange
+    let mut rng = StdRng::seed_from_u64(3);
+    let stars: Vec<Star> = (0..5_000)
+        .map(|id| Star {
+            id,
+            position: na::Vector3::from_fn(|_, _| rng.gen_range(-100.0..100.0)),
+            velocity: na::Vector3::zeros(),
+            absolute_magnitude: rng.gen_range(-5.0..15.0),
+            radius: None,
+        })
+        .collect();
+    let octree = Octree::new(stars.clone());
+    let frame = SkyFrame::from_basis(&[na::Vector3::x(), na::Vector3::y()]).unwrap();
+    let camera = Camera::new(
+        na::Vector3::zeros(),
+        &frame,
+        0.0,
+        0.0,
+        Projection::Stereographic,
+        [120.0, 90.0],
+        [400, 300],
+    );
+    let moving = camera
+        .clone()
+        .with_motion(Motion::new(0.9f32 * na::Vector3::x()).unwrap());
+
+    // Act
+    let visible_at_rest = camera.visible_stars(&octree, f32::INFINITY);
+    let visible = moving.visible_stars(&octree, f32::INFINITY);
+
+    // Assert
+    let expected: Vec<u64> = stars
+        .iter()
+        .filter(|star| {
+            moving
+                .pixel_coordinates(&star.position)
+                .map_or(false, |pixel| {
+                    (0.0..=400.0).contains(&pixel.x) && (0.0..=300.0).contains(&pixel.y)
+                })
+        })
+        .map(|star| star.id)
+        .collect();
+    let mut visible: Vec<u64> = visible.iter().map(|star| star.id).collect();
+    visible.sort_unstable();
+    assert_eq!(visible, expected, "The view cone missed aberrated stars.");
+    assert!(
+        visible.len() > 3 * visible_at_rest.len(),
+        "{} stars were seen moving, {} at rest.",
+        visible.len(),
+        visible_at_rest.len()
+    );
+}
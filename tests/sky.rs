@@ -0,0 +1,183 @@
+use nalgebra as na;
+
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::Star;
+use space_telescope::render::camera::Camera;
+use space_telescope::render::optics::PsfKernel;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky::{CubemapFace, RenderMode, SkyFrame};
+use space_telescope::render::{flux, render_flux};
+
+/// Frame whose fundamental plane is tilted away from the world axes
+fn tilted_frame() -> SkyFrame {
+    SkyFrame::from_basis(&[
+        na::Vector3::new(1.0, 1.0, 0.0),
+        na::Vector3::new(0.0, 0.3, 1.0),
+    ])
+    .unwrap()
+}
+
+/// Camera at the origin framing a small patch of `frame` away from its primary direction
+fn framed(frame: &SkyFrame, image_dimensions: [u32; 2]) -> Camera {
+    Camera::new(
+        na::Vector3::zeros(),
+        frame,
+        45.0,
+        10.0,
+        Projection::Gnomonic,
+        [10.0, 10.0],
+        image_dimensions,
+    )
+}
+
+/// Star 10 parsecs from the origin in `direction`
+fn star(direction: na::Vector3<f32>) -> Star {
+    Star {
+        id: 0,
+        position: 10.0 * direction.normalize(),
+        velocity: na::Vector3::zeros(),
+        absolute_magnitude: 0.0,
+        radius: None,
+    }
+}
+
+fn assert_close(actual: &na::Vector3<f32>, expected: &na::Vector3<f32>, description: &str) {
+    assert!(
+        (actual - expected).norm() < 1e-4,
+        "{}: expected {:?}, got {:?}.",
+        description,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_cubemap_faces_are_oriented_by_the_fundamental_plane() {
+    // Arrange
+    let frame = tilted_frame();
+    let expected = [
+        (CubemapFace::PositiveX, -frame.secondary, frame.pole),
+        (CubemapFace::NegativeX, frame.secondary, frame.pole),
+        (CubemapFace::PositiveY, frame.pole, frame.primary),
+        (CubemapFace::NegativeY, -frame.pole, -frame.primary),
+        (CubemapFace::PositiveZ, -frame.primary, frame.pole),
+        (CubemapFace::NegativeZ, frame.primary, frame.pole),
+    ];
+
+    for (face, forward, up) in expected {
+        // Act
+        let camera = Camera::cubemap_face(na::Vector3::zeros(), &frame, face, 64);
+        let centre = camera
+            .pixel_direction(&na::Point2::new(32.0, 32.0))
+            .unwrap();
+        let top = camera.pixel_direction(&na::Point2::new(32.0, 0.0)).unwrap();
+
+        // Assert
+        let description = face.as_str();
+        assert_close(&camera.forward, &forward, description);
+        assert_close(&camera.up, &up, description);
+        // Cubemap faces are mirrored from the view inside the sky, unlike framed renders
+        assert_close(&camera.right, &up.cross(&forward), description);
+        assert_close(&centre.normalize(), &forward, description);
+        // The top edge is 45° from the centre, towards "up"
+        assert_close(&top.normalize(), &(forward + up).normalize(), description);
+    }
+}
+
+#[test]
+fn test_cubemap_faces_draw_each_star_once() {
+    // Arrange
+    let frame = tilted_frame();
+    let expected = flux(star(frame.secondary).apparent_magnitude(&na::Vector3::zeros()));
+    let catalog = Octree::new(vec![star(frame.secondary)]);
+
+    // Act
+    let images: Vec<(Option<CubemapFace>, f32)> = RenderMode::Cubemap
+        .cameras(framed(&frame, [64, 64]), &frame)
+        .into_iter()
+        .map(|(face, camera)| {
+            let image = render_flux(
+                &camera,
+                &catalog,
+                &PsfKernel::default(),
+                0.55,
+                f32::INFINITY,
+                None,
+            );
+            (face, image.pixels.iter().sum())
+        })
+        .collect();
+
+    // Assert
+    assert_eq!(images.len(), 6);
+    for (face, total) in images {
+        if face == Some(CubemapFace::NegativeX) {
+            assert!((total / expected - 1.0).abs() < 0.01);
+        } else {
+            assert_eq!(total, 0.0, "The star was drawn on {:?}.", face);
+        }
+    }
+}
+
+#[test]
+fn test_panoramas_cover_the_sky_exactly_once() {
+    // Arrange
+    let frame = tilted_frame();
+    let [width, height] = [360u32, 180u32];
+    let camera = Camera::panorama(na::Vector3::zeros(), &frame, [width, height]);
+    let pixel_size = std::f64::consts::PI / height as f64;
+
+    // Act
+    let mut solid_angle = 0.0;
+    let mut max_round_trip_error = 0f32;
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = na::Point2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let direction = camera.pixel_direction(&pixel).unwrap();
+            let latitude = direction.dot(&frame.pole).clamp(-1.0, 1.0).asin() as f64;
+            solid_angle += latitude.cos() * pixel_size * pixel_size;
+            let round_trip = camera.direction_pixel(&direction).unwrap();
+            max_round_trip_error = max_round_trip_error.max((round_trip - pixel).norm());
+        }
+    }
+
+    // Assert
+    assert!((solid_angle / (4.0 * std::f64::consts::PI) - 1.0).abs() < 1e-3);
+    assert!(max_round_trip_error < 1e-2);
+    // Centred on the primary direction with the pole at the top and longitude 90° on the left
+    let centre = camera
+        .direction_pixel(&frame.primary)
+        .expect("The primary direction is not in the panorama.");
+    let secondary = camera.direction_pixel(&frame.secondary).unwrap();
+    let pole = camera.direction_pixel(&frame.pole).unwrap();
+    assert!((centre - na::Point2::new(180.0, 90.0)).norm() < 1e-2);
+    assert!((secondary - na::Point2::new(90.0, 90.0)).norm() < 1e-2);
+    assert!(pole.y.abs() < 1e-2);
+}
+
+#[test]
+fn test_panoramas_draw_stars_where_they_are() {
+    // Arrange
+    let frame = tilted_frame();
+    let catalog = Octree::new(vec![star(frame.secondary)]);
+    let cameras = RenderMode::Panorama.cameras(framed(&frame, [360, 180]), &frame);
+
+    // Act
+    let image = render_flux(
+        &cameras[0].1,
+        &catalog,
+        &PsfKernel::default(),
+        0.55,
+        f32::INFINITY,
+        None,
+    );
+
+    // Assert
+    assert_eq!(cameras.len(), 1);
+    let brightest = (0..image.pixels.len())
+        .max_by(|a, b| image.pixels[*a].total_cmp(&image.pixels[*b]))
+        .unwrap();
+    let (x, y) = (brightest % 360, brightest / 360);
+    // The longitude and latitude of framed renders do not move the panorama
+    assert!((89..=90).contains(&x) && (89..=90).contains(&y));
+}
@@ -0,0 +1,195 @@
+use chrono::{TimeZone, Utc};
+use nalgebra as na;
+
+use space_telescope::catalog::ephemeris;
+use space_telescope::render::camera::Camera;
+use space_telescope::render::detector::{Detector, Exposure};
+use space_telescope::render::ground::{Atmosphere, Site};
+use space_telescope::render::image::Image;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky_brightness::{SkyBrightness, SkyConditions};
+use space_telescope::render::{expose, render_sky};
+
+/// Right ascension and declination in degrees of an equatorial direction
+fn equatorial(direction: &na::Vector3<f64>) -> (f64, f64) {
+    (
+        direction
+            .y
+            .atan2(direction.x)
+            .to_degrees()
+            .rem_euclid(360.0),
+        direction.z.asin().to_degrees(),
+    )
+}
+
+/// Unit direction at an altitude and an azimuth measured from the x axis, with the zenith along z
+fn horizontal(altitude: f32, azimuth: f32) -> na::Vector3<f32> {
+    let (sin_alt, cos_alt) = altitude.to_radians().sin_cos();
+    let (sin_az, cos_az) = azimuth.to_radians().sin_cos();
+    na::Vector3::new(cos_alt * cos_az, cos_alt * sin_az, sin_alt)
+}
+
+/// Moonless night sky with the Sun well below the horizon
+fn dark_sky() -> SkyBrightness {
+    SkyBrightness {
+        dark_zenith_brightness: 21.5,
+        atmosphere: Atmosphere {
+            zenith: na::Vector3::z(),
+            pressure: 1.0,
+        },
+        sun: horizontal(-40.0, 0.0),
+        moon: horizontal(-40.0, 180.0),
+        moon_phase_angle: 0.0,
+    }
+}
+
+#[test]
+fn test_sun_and_moon_match_the_almanac() {
+    // Arrange
+    // Meeus, Astronomical Algorithms, examples 25.a and 47.a
+    let sun_date = 2_448_908.5;
+    let moon_date = 2_448_724.5;
+
+    // Act
+    let (sun_ra, sun_dec) = equatorial(&ephemeris::sun(sun_date));
+    let (moon_ra, moon_dec) = equatorial(&ephemeris::moon(moon_date));
+
+    // Assert
+    assert!((sun_ra - 198.380_83).abs() < 0.02, "{} degrees.", sun_ra);
+    assert!((sun_dec + 7.785_07).abs() < 0.02, "{} degrees.", sun_dec);
+    assert!((moon_ra - 134.688_47).abs() < 0.5, "{} degrees.", moon_ra);
+    assert!((moon_dec - 13.768_37).abs() < 0.5, "{} degrees.", moon_dec);
+}
+
+#[test]
+fn test_skies_brighten_with_light_pollution_and_towards_the_horizon() {
+    // Arrange
+    let conditions = |bortle_class| SkyConditions {
+        bortle_class: Some(bortle_class),
+        zenith_brightness: None,
+    };
+    let sky = dark_sky();
+
+    // Act
+    let zenith = sky.surface_brightness(&na::Vector3::z());
+    let low = sky.surface_brightness(&horizontal(15.0, 90.0));
+    let below = sky.surface_brightness(&horizontal(-10.0, 90.0));
+
+    // Assert
+    assert!(conditions(1).dark_zenith_brightness() > conditions(9).dark_zenith_brightness());
+    assert!(conditions(10).validate().is_err());
+    assert!((zenith - 21.5).abs() < 0.01, "{} mag/arcsec².", zenith);
+    assert!(low < zenith - 0.5, "{} mag/arcsec².", low);
+    assert!(below.is_finite());
+}
+
+#[test]
+fn test_twilight_and_moonlight_brighten_the_sky() {
+    // Arrange
+    let dark = dark_sky();
+    let dusk = |sun_altitude| SkyBrightness {
+        sun: horizontal(sun_altitude, 0.0),
+        ..dark
+    };
+    let moonlit = |moon_phase_angle| SkyBrightness {
+        moon: horizontal(40.0, 180.0),
+        moon_phase_angle,
+        ..dark
+    };
+    let near_moon = horizontal(50.0, 180.0);
+    let far_from_moon = horizontal(60.0, 0.0);
+
+    // Act
+    let twilight: Vec<f32> = [-20.0, -15.0, -10.0, -5.0]
+        .iter()
+        .map(|&altitude| dusk(altitude).surface_brightness(&na::Vector3::z()))
+        .collect();
+    let full_moon = moonlit(0.0).surface_brightness(&near_moon);
+    let crescent = moonlit(120.0).surface_brightness(&near_moon);
+    let full_moon_far = moonlit(0.0).surface_brightness(&far_from_moon);
+
+    // Assert
+    assert!((twilight[0] - 21.5).abs() < 0.01);
+    assert!(
+        twilight.windows(2).all(|pair| pair[1] < pair[0]),
+        "{:?} mag/arcsec².",
+        twilight
+    );
+    // A full moon high in the sky washes out all but the brightest nebulae
+    assert!(
+        (16.5..19.0).contains(&full_moon),
+        "{} mag/arcsec².",
+        full_moon
+    );
+    assert!(crescent > full_moon + 1.0);
+    assert!(full_moon_far > full_moon);
+    assert!(full_moon_far < dark.surface_brightness(&far_from_moon));
+}
+
+#[test]
+fn test_sky_background_adds_to_exposures() {
+    // Arrange
+    let site = Site {
+        latitude: -30.24,
+        longitude: -70.74,
+        elevation: 2700.0,
+        // Full moon
+        time: Utc.with_ymd_and_hms(2023, 7, 3, 4, 0, 0).unwrap(),
+    };
+    let sky = SkyConditions {
+        bortle_class: None,
+        zenith_brightness: Some(21.7),
+    }
+    .at(&site);
+    let camera = Camera::new(
+        na::Vector3::zeros(),
+        &site.horizon_frame(),
+        0.0,
+        60.0,
+        Projection::Gnomonic,
+        [0.5, 0.5],
+        [32, 32],
+    );
+    let detector = Detector {
+        quantum_efficiency: 0.8,
+        read_noise: 5.0,
+        dark_current: 0.01,
+        gain: 2.0,
+        full_well: 1e6,
+        bit_depth: 20,
+    };
+    let exposure = Exposure {
+        time: 60.0,
+        sky_brightness: None,
+        seed: None,
+    };
+    let image = Image::new(32, 32);
+
+    // Act
+    let background = render_sky(&camera, &sky);
+    let dark = expose(&image, None, &camera, 1.0, &detector, &exposure, 7);
+    let lit = expose(
+        &image,
+        Some(&background),
+        &camera,
+        1.0,
+        &detector,
+        &exposure,
+        7,
+    );
+
+    // Assert
+    assert!(
+        sky.moon_phase_angle.abs() < 20.0,
+        "{} degrees.",
+        sky.moon_phase_angle
+    );
+    assert!(background.pixels.iter().all(|&pixel| pixel > 0.0));
+    let mean = |values: &[u32]| values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64;
+    assert!(
+        mean(&lit.pixels) > mean(&dark.pixels) + 100.0,
+        "{} against {} ADU.",
+        mean(&lit.pixels),
+        mean(&dark.pixels)
+    );
+}
@@ -0,0 +1,169 @@
+use chrono::{TimeZone, Utc};
+use nalgebra as na;
+
+use space_telescope::catalog::ephemeris::{self, Body, ASTRONOMICAL_UNIT};
+use space_telescope::render::camera::Camera;
+use space_telescope::render::flux;
+use space_telescope::render::ground::Site;
+use space_telescope::render::image::Image;
+use space_telescope::render::optics::PsfKernel;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky::SkyFrame;
+use space_telescope::render::solar_system::{render_solar_system, SolarSystem};
+
+fn greenwich(time: chrono::DateTime<Utc>) -> Site {
+    Site {
+        latitude: 51.4769,
+        longitude: -0.0005,
+        elevation: 46.0,
+        time,
+    }
+}
+
+/// Camera at `position` looking at `target`
+fn camera(position: na::Vector3<f32>, target: na::Vector3<f32>, fov: f32, size: u32) -> Camera {
+    let forward = (target - position).normalize();
+    let side = forward.cross(&na::Vector3::z());
+    let frame = SkyFrame::from_basis(&[forward, side]).unwrap();
+    Camera::new(
+        position,
+        &frame,
+        0.0,
+        0.0,
+        Projection::Gnomonic,
+        [fov, fov],
+        [size, size],
+    )
+}
+
+#[test]
+fn test_planets_match_the_almanac() {
+    // Arrange
+    // Meeus, Astronomical Algorithms, examples 32.a and 47.a
+    let venus_date = 2_448_976.5;
+    let moon_date = 2_448_724.5;
+
+    // Act
+    let venus = Body::Venus.heliocentric_ecliptic(venus_date);
+    let moon =
+        Body::Moon.heliocentric_ecliptic(moon_date) - Body::Earth.heliocentric_ecliptic(moon_date);
+
+    // Assert
+    // Meeus gives longitudes from the equinox of date
+    let precession = 1.396_971 * (venus_date - ephemeris::J2000) / 36_525.0;
+    let longitude = venus.y.atan2(venus.x).to_degrees().rem_euclid(360.0) + precession;
+    let latitude = (venus.z / venus.norm()).asin().to_degrees();
+    assert!(
+        (longitude - 26.114_28).abs() < 0.05,
+        "{} degrees.",
+        longitude
+    );
+    assert!((latitude + 2.620_70).abs() < 0.05, "{} degrees.", latitude);
+    assert!((venus.norm() - 0.724_603).abs() < 1e-3);
+    let moon_distance = moon.norm() * 149_597_870.7;
+    assert!(
+        (moon_distance / 368_409.7 - 1.0).abs() < 5e-3,
+        "{} km.",
+        moon_distance
+    );
+}
+
+#[test]
+fn test_the_sun_is_opposite_the_earth() {
+    // Arrange
+    let site = greenwich(Utc.with_ymd_and_hms(2023, 3, 20, 21, 24, 0).unwrap());
+    let julian_date = site.julian_date();
+    let solar_system = SolarSystem::at(julian_date);
+
+    // Act
+    let from_earth = solar_system.position(Body::Sun) - solar_system.position(Body::Earth);
+    let expected = site.to_catalog_frame(&ephemeris::sun(julian_date));
+
+    // Assert
+    let error = from_earth.normalize().angle(&expected).to_degrees();
+    assert!(error < 0.02, "{} degrees apart.", error);
+    let distance = from_earth.norm() as f64 / ASTRONOMICAL_UNIT;
+    assert!((distance - 0.996).abs() < 2e-3, "{} AU.", distance);
+}
+
+#[test]
+fn test_magnitudes_follow_the_phase() {
+    // Arrange
+    let full = SolarSystem::at(
+        greenwich(Utc.with_ymd_and_hms(2023, 7, 3, 11, 39, 0).unwrap()).julian_date(),
+    );
+    let new = SolarSystem::at(
+        greenwich(Utc.with_ymd_and_hms(2023, 7, 17, 18, 32, 0).unwrap()).julian_date(),
+    );
+    let quarter = SolarSystem::at(
+        greenwich(Utc.with_ymd_and_hms(2023, 7, 10, 1, 48, 0).unwrap()).julian_date(),
+    );
+
+    // Act
+    let sun = full.apparent_magnitude(Body::Sun, &full.position(Body::Earth));
+    let full_moon = full.apparent_magnitude(Body::Moon, &full.position(Body::Earth));
+    let quarter_moon = quarter.apparent_magnitude(Body::Moon, &quarter.position(Body::Earth));
+    let new_moon_phase = new.phase_angle(Body::Moon, &new.position(Body::Earth));
+
+    // Assert
+    assert!((sun + 26.74).abs() < 0.05, "{} magnitudes.", sun);
+    assert!(
+        (-13.0..-12.4).contains(&full_moon),
+        "{} magnitudes.",
+        full_moon
+    );
+    assert!(
+        (-10.5..-9.5).contains(&quarter_moon),
+        "{} magnitudes.",
+        quarter_moon
+    );
+    assert!(new_moon_phase > 170.0, "{} degrees.", new_moon_phase);
+}
+
+#[test]
+fn test_resolved_disks_keep_the_body_magnitude_and_face_the_sun() {
+    // Arrange
+    let site = greenwich(Utc.with_ymd_and_hms(2023, 7, 10, 1, 48, 0).unwrap());
+    let solar_system = SolarSystem::at(site.julian_date());
+    let observer = solar_system.site_position(&site);
+    let moon = solar_system.position(Body::Moon);
+    let resolved = camera(observer, moon, 1.0, 200);
+    let point = camera(observer, moon, 20.0, 200);
+    let kernel = PsfKernel::default();
+
+    // Act
+    let mut disk_image = Image::new(200, 200);
+    render_solar_system(&mut disk_image, &resolved, &solar_system, &kernel, 0.55);
+    let mut point_image = Image::new(200, 200);
+    render_solar_system(&mut point_image, &point, &solar_system, &kernel, 0.55);
+
+    // Assert
+    let expected = flux(solar_system.apparent_magnitude(Body::Moon, &observer)) as f64;
+    for image in [&disk_image, &point_image] {
+        let ratio = image.total_flux() / expected;
+        assert!(
+            (ratio - 1.0).abs() < 0.05,
+            "{} of the flux was drawn.",
+            ratio
+        );
+    }
+    let lit_pixels = disk_image
+        .pixels
+        .iter()
+        .filter(|&&pixel| pixel > 0.0)
+        .count();
+    assert!(lit_pixels > 1_000, "{} pixels lit.", lit_pixels);
+    // The lit half of a quarter moon faces the Sun
+    let sun_pixel = resolved
+        .pixel_coordinates(&solar_system.position(Body::Sun))
+        .expect("The Sun is not in front of the camera.");
+    let sunward = (sun_pixel - na::Point2::new(100.0, 100.0)).normalize();
+    let mut centroid = na::Vector2::zeros();
+    for y in 0..200 {
+        for x in 0..200 {
+            let offset = na::Vector2::new(x as f32 + 0.5 - 100.0, y as f32 + 0.5 - 100.0);
+            centroid += offset * disk_image.get(x, y);
+        }
+    }
+    assert!(centroid.normalize().dot(&sunward) > 0.9);
+}
@@ -0,0 +1,142 @@
+use nalgebra as na;
+
+use space_telescope::catalog::octree::Octree;
+use space_telescope::catalog::{estimated_radius, Star};
+use space_telescope::render::camera::Camera;
+use space_telescope::render::optics::PsfKernel;
+use space_telescope::render::projection::Projection;
+use space_telescope::render::sky::SkyFrame;
+use space_telescope::render::stellar_disk::{LimbDarkening, StellarDisk};
+use space_telescope::render::{flux, render_flux};
+
+/// One astronomical unit in parsecs
+const ASTRONOMICAL_UNIT: f32 = 4.848_137e-6;
+
+/// Sun-like star `distance` parsecs from the origin along the x axis
+fn sun_like(id: u64, distance: f32, absolute_magnitude: f32) -> Star {
+    Star {
+        id,
+        position: na::Vector3::new(distance, 0.0, 0.0),
+        velocity: na::Vector3::zeros(),
+        absolute_magnitude,
+        radius: Some(1.0),
+    }
+}
+
+/// Camera at the origin looking along the x axis
+fn camera(fov: f32, size: u32) -> Camera {
+    let frame = SkyFrame::from_basis(&[na::Vector3::x(), na::Vector3::y()]).unwrap();
+    Camera::new(
+        na::Vector3::zeros(),
+        &frame,
+        0.0,
+        0.0,
+        Projection::Gnomonic,
+        [fov, fov],
+        [size, size],
+    )
+}
+
+#[test]
+fn test_radii_and_limb_darkening_follow_the_sun() {
+    // Arrange
+    let blue = LimbDarkening::solar(0.4);
+    let red = LimbDarkening::solar(0.7);
+    let steps = 100_000;
+
+    // Act
+    let integral: f64 = (0..steps)
+        .map(|i| {
+            let radius = (i as f64 + 0.5) / steps as f64;
+            2.0 * std::f64::consts::PI * radius * blue.intensity(radius) / steps as f64
+        })
+        .sum();
+
+    // Assert
+    assert!((estimated_radius(4.83) - 1.0).abs() < 1e-3);
+    assert!(estimated_radius(-5.0) > 5.0 && estimated_radius(10.0) < 0.5);
+    assert!((integral / blue.total_intensity() - 1.0).abs() < 1e-4);
+    // The limb is darker in blue light
+    assert!(blue.intensity(0.95) < red.intensity(0.95));
+    assert_eq!(blue.intensity(1.1), 0.0);
+}
+
+#[test]
+fn test_close_stars_are_limb_darkened_disks() {
+    // Arrange
+    let star = sun_like(0, ASTRONOMICAL_UNIT, 4.83);
+    let expected = flux(star.apparent_magnitude(&na::Vector3::zeros())) as f64;
+    let near = camera(1.0, 200);
+    let distant_star = sun_like(1, 10.0, 4.83);
+
+    // Act
+    let disk = StellarDisk::resolve(&near, &star).expect("The star was not resolved.");
+    let point = StellarDisk::resolve(&near, &distant_star);
+    let image = render_flux(
+        &near,
+        &Octree::new(vec![star]),
+        &PsfKernel::default(),
+        0.55,
+        f32::INFINITY,
+        None,
+    );
+
+    // Assert
+    // The Sun spans 16 arcminutes from a distance of one astronomical unit
+    let radius = disk.pixel_radius / 200.0 * 60.0;
+    assert!((radius - 16.0).abs() < 0.1, "{} arcminutes.", radius);
+    assert!(point.is_none());
+    let ratio = image.total_flux() / expected;
+    assert!(
+        (ratio - 1.0).abs() < 0.02,
+        "{} of the flux was drawn.",
+        ratio
+    );
+    let center = image.get(100, 100);
+    let limb = image.get(100 + (0.95 * disk.pixel_radius) as u32, 100);
+    let glare = image.get(100 + (1.5 * disk.pixel_radius) as u32, 100);
+    assert!(
+        limb < 0.6 * center,
+        "{} at the limb, {} at the centre.",
+        limb,
+        center
+    );
+    assert!(glare > 0.0 && glare < 1e-3 * center);
+}
+
+#[test]
+fn test_disks_hide_the_stars_behind_them() {
+    // Arrange
+    // A faint disk in front of a bright background star, beside another one
+    let disk_star = sun_like(0, ASTRONOMICAL_UNIT, 20.0);
+    let hidden = sun_like(1, 100.0, -5.0);
+    let beside = Star {
+        position: na::Vector3::new(100.0, 0.6, 0.0),
+        ..sun_like(2, 100.0, -5.0)
+    };
+    let camera = camera(1.0, 200);
+    let origin = na::Vector3::zeros();
+    let expected = flux(disk_star.apparent_magnitude(&origin)) as f64
+        + flux(beside.apparent_magnitude(&origin)) as f64;
+
+    // Act
+    let disk = StellarDisk::resolve(&camera, &disk_star).unwrap();
+    let image = render_flux(
+        &camera,
+        &Octree::new(vec![disk_star, hidden.clone(), beside.clone()]),
+        &PsfKernel::default(),
+        0.55,
+        f32::INFINITY,
+        None,
+    );
+
+    // Assert
+    assert!(disk.occults(&camera, &hidden.position));
+    assert!(!disk.occults(&camera, &beside.position));
+    let ratio = image.total_flux() / expected;
+    assert!(
+        (ratio - 1.0).abs() < 0.02,
+        "{} of the flux was drawn.",
+        ratio
+    );
+}
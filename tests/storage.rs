@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::Mutex;
+
+use actix_web::http::Method;
+use actix_web::web::{Bytes, Data, Query};
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer};
+use chrono::Utc;
+use secrecy::Secret;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use space_telescope::storage::filesystem::FilesystemStorage;
+use space_telescope::storage::s3::{S3Settings, S3Storage};
+use space_telescope::storage::{
+    content_type, render_key, survey_key, survey_prefix, validate_key, ArtifactStorage,
+    StorageError,
+};
+
+/// Objects held by the S3 stand-in, by path
+type Objects = Mutex<HashMap<String, Vec<u8>>>;
+
+/// Minimal S3-compatible object store, rejecting requests without a well-formed signature
+async fn s3_stand_in(request: HttpRequest, body: Bytes, objects: Data<Objects>) -> HttpResponse {
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let signed = header("authorization").starts_with(
+        "AWS4-HMAC-SHA256 Credential=test-key/",
+    ) && header("authorization")
+        .contains("/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature=")
+        && header("x-amz-content-sha256") == hex::encode(Sha256::digest(&body));
+    if !signed {
+        return HttpResponse::Forbidden().finish();
+    }
+
+    let path = request.path().to_string();
+    let mut objects = objects.lock().unwrap();
+    let query = Query::<HashMap<String, String>>::from_query(request.query_string()).unwrap();
+    if query.get("list-type").map(String::as_str) == Some("2") {
+        return list_objects(&objects, &path, &query);
+    }
+    match *request.method() {
+        Method::PUT => {
+            objects.insert(path, body.to_vec());
+            HttpResponse::Ok().finish()
+        }
+        Method::DELETE => {
+            objects.remove(&path);
+            HttpResponse::NoContent().finish()
+        }
+        _ => {
+            let Some(object) = objects.get(&path) else {
+                return HttpResponse::NotFound().finish();
+            };
+            let etag = hex::encode(Sha256::digest(object));
+            let mut response = HttpResponse::Ok();
+            response
+                .insert_header(("etag", format!("\"{}\"", etag)))
+                .insert_header(("last-modified", Utc::now().to_rfc2822()));
+            let range = header("range");
+            let Some((start, end)) = range
+                .strip_prefix("bytes=")
+                .and_then(|range| range.split_once('-'))
+            else {
+                return response.body(object.clone());
+            };
+            let start: usize = start.parse().unwrap();
+            let end: usize = end.parse().unwrap();
+            HttpResponse::PartialContent().body(object[start..=end].to_vec())
+        }
+    }
+}
+
+/// ListObjectsV2 response of the stand-in, two keys per page
+fn list_objects(
+    objects: &HashMap<String, Vec<u8>>,
+    bucket: &str,
+    query: &HashMap<String, String>,
+) -> HttpResponse {
+    let bucket = format!("{}/", bucket);
+    let prefix = format!("{}{}", bucket, query["prefix"]);
+    let after = query
+        .get("continuation-token")
+        .map(|token| format!("{}{}", bucket, token));
+    let mut keys: Vec<&str> = objects
+        .keys()
+        .filter(|path| path.starts_with(&prefix))
+        .filter(|path| after.as_ref().map_or(true, |after| *path > after))
+        .map(|path| &path[bucket.len()..])
+        .collect();
+    keys.sort();
+    let page = &keys[..keys.len().min(2)];
+    let mut listing = String::from("<ListBucketResult>");
+    for key in page {
+        listing += &format!("<Contents><Key>{}</Key></Contents>", key);
+    }
+    if keys.len() > page.len() {
+        listing += &format!(
+            "<NextContinuationToken>{}</NextContinuationToken>",
+            page[page.len() - 1]
+        );
+    }
+    listing += "</ListBucketResult>";
+    HttpResponse::Ok().body(listing)
+}
+
+fn spawn_s3_stand_in() -> S3Storage {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind TcpListener.");
+    let port = listener.local_addr().unwrap().port();
+    let objects = Data::new(Objects::default());
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(objects.clone())
+            .default_service(actix_web::web::to(s3_stand_in))
+    })
+    .listen(listener)
+    .expect("Failed to bind address")
+    .run();
+    tokio::spawn(server);
+
+    S3Storage::new(S3Settings {
+        endpoint: format!("http://127.0.0.1:{}/", port),
+        region: "us-east-1".into(),
+        bucket: "renders".into(),
+        prefix: "test/".into(),
+        access_key_id: "test-key".into(),
+        secret_access_key: Secret::new("test-secret".into()),
+    })
+}
+
+/// Store, read back and delete an artifact
+async fn round_trip(storage: &dyn ArtifactStorage) {
+    // Arrange
+    let key = render_key(Uuid::new_v4(), "image.fits");
+    let body: Vec<u8> = (0..=255).collect();
+
+    // Act
+    storage.put(&key, body.clone()).await.unwrap();
+    let whole = storage.get(&key, None).await.unwrap();
+    let part = storage.get(&key, Some(10..20)).await.unwrap();
+    let metadata = storage.metadata(&key).await.unwrap();
+    storage.delete(&key).await.unwrap();
+    let deleted = storage.get(&key, None).await;
+
+    // Assert
+    assert_eq!(whole, body);
+    assert_eq!(part, body[10..20]);
+    assert_eq!(metadata.size, 256);
+    assert_eq!(metadata.content_type, "application/fits");
+    assert!(!metadata.etag.is_empty());
+    assert!(matches!(deleted, Err(StorageError::NotFound(_))));
+    // Deleting twice is harmless
+    storage.delete(&key).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_filesystem_storage_round_trips_artifacts() {
+    let storage = FilesystemStorage::new(std::env::temp_dir().join(Uuid::new_v4().to_string()));
+    round_trip(&storage).await;
+}
+
+#[tokio::test]
+async fn test_s3_storage_round_trips_artifacts() {
+    let storage = spawn_s3_stand_in();
+    round_trip(&storage).await;
+}
+
+/// Delete the files of a survey, leaving those of another
+async fn delete_prefix(storage: &dyn ArtifactStorage) {
+    // Arrange
+    let (survey_id, other_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let paths = [
+        "properties",
+        "Norder0/Dir0/Npix0.png",
+        "Norder0/Dir0/Npix1.png",
+        "Norder1/Dir0/Npix4.png",
+    ];
+    for path in paths {
+        storage
+            .put(&survey_key(survey_id, "SDSS_G", path), vec![0])
+            .await
+            .unwrap();
+    }
+    storage
+        .put(&survey_key(survey_id, "0.6563um", "properties"), vec![0])
+        .await
+        .unwrap();
+    let other_key = survey_key(other_id, "SDSS_G", "properties");
+    storage.put(&other_key, vec![0]).await.unwrap();
+
+    // Act
+    let deleted = storage.delete_prefix(&survey_prefix(survey_id)).await;
+    let invalid = storage.delete_prefix("surveys").await;
+
+    // Assert
+    assert_eq!(deleted.unwrap(), 5);
+    assert!(matches!(invalid, Err(StorageError::InvalidKey(_))));
+    for path in paths {
+        let remaining = storage
+            .metadata(&survey_key(survey_id, "SDSS_G", path))
+            .await;
+        assert!(matches!(remaining, Err(StorageError::NotFound(_))));
+    }
+    assert!(storage.metadata(&other_key).await.is_ok());
+    // Deleting twice is harmless
+    assert_eq!(
+        storage
+            .delete_prefix(&survey_prefix(survey_id))
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_filesystem_storage_deletes_prefixes() {
+    let storage = FilesystemStorage::new(std::env::temp_dir().join(Uuid::new_v4().to_string()));
+    delete_prefix(&storage).await;
+}
+
+#[tokio::test]
+async fn test_s3_storage_deletes_prefixes() {
+    let storage = spawn_s3_stand_in();
+    delete_prefix(&storage).await;
+}
+
+#[tokio::test]
+async fn test_keys_cannot_escape_the_storage_root() {
+    // Arrange
+    let storage = FilesystemStorage::new(std::env::temp_dir().join(Uuid::new_v4().to_string()));
+    let invalid_keys = [
+        "",
+        "/etc/passwd",
+        "renders/../../secret",
+        "renders//image.png",
+    ];
+
+    for key in invalid_keys {
+        // Act
+        let result = storage.put(key, vec![0]).await;
+
+        // Assert
+        assert!(
+            matches!(result, Err(StorageError::InvalidKey(_))),
+            "The storage accepted {:?}.",
+            key
+        );
+        assert!(validate_key(key).is_err());
+    }
+}
+
+#[test]
+fn test_content_types_follow_the_last_segment() {
+    // Arrange
+    let survey_id = Uuid::new_v4();
+
+    // Act
+    let tile = content_type(&survey_key(survey_id, "0.6563um", "Norder0/Dir0/Npix4.png"));
+    let properties = content_type(&survey_key(survey_id, "0.6563um", "properties"));
+    let descriptor = content_type(&render_key(survey_id, "image.dzi"));
+
+    // Assert
+    assert_eq!(tile, "image/png");
+    assert_eq!(properties, "text/plain");
+    assert_eq!(descriptor, "application/xml");
+}
@@ -0,0 +1,84 @@
+use space_telescope::render::image::Image;
+use space_telescope::render::tiles::{levels, tiles, TilePyramid, TILE_SIZE};
+
+#[test]
+fn test_pyramid_levels_halve_down_to_a_single_pixel() {
+    // Arrange
+    let pyramid = TilePyramid::new(1000, 600);
+
+    // Act
+    let max_level = pyramid.max_level();
+
+    // Assert
+    assert_eq!(max_level, 10);
+    assert_eq!(pyramid.level_dimensions(10), (1000, 600));
+    assert_eq!(pyramid.level_dimensions(9), (500, 300));
+    assert_eq!(pyramid.level_dimensions(8), (250, 150));
+    assert_eq!(pyramid.level_dimensions(1), (2, 2));
+    assert_eq!(pyramid.level_dimensions(0), (1, 1));
+    assert_eq!(pyramid.tile_counts(10), (4, 3));
+    assert_eq!(pyramid.tile_counts(8), (1, 1));
+    assert_eq!(TilePyramid::new(256, 256).max_level(), 8);
+    assert_eq!(TilePyramid::new(257, 1).max_level(), 9);
+}
+
+#[test]
+fn test_levels_match_the_pyramid() {
+    // Arrange
+    let image = Image::new(1000, 600);
+    let pyramid = TilePyramid::new(1000, 600);
+
+    // Act
+    let levels: Vec<_> = levels(&image).collect();
+
+    // Assert
+    assert_eq!(levels.len() as u32, pyramid.max_level() + 1);
+    for (level, level_image) in levels {
+        assert_eq!(
+            level_image.dimensions(),
+            pyramid.level_dimensions(level),
+            "Level {} has the wrong dimensions.",
+            level
+        );
+    }
+}
+
+#[test]
+fn test_tiles_cover_their_level() {
+    // Arrange
+    let mut image = Image::new(600, 300);
+    image.add(599, 299, 1.0);
+    let (level, level_image) = levels(&image).next().unwrap();
+
+    // Act
+    let tiles = tiles(level, &level_image).unwrap();
+
+    // Assert
+    let positions: Vec<(u32, u32)> = tiles.iter().map(|tile| (tile.column, tile.row)).collect();
+    assert_eq!(
+        positions,
+        vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+    );
+    // Edge tiles are cropped to the image
+    let corner = image::load_from_memory(&tiles[5].body).unwrap();
+    assert_eq!(
+        (corner.width(), corner.height()),
+        (600 - 2 * TILE_SIZE, 300 - TILE_SIZE)
+    );
+    let full = image::load_from_memory(&tiles[0].body).unwrap();
+    assert_eq!((full.width(), full.height()), (TILE_SIZE, TILE_SIZE));
+}
+
+#[test]
+fn test_descriptor_describes_the_image() {
+    // Arrange
+    let pyramid = TilePyramid::new(1000, 600);
+
+    // Act
+    let descriptor = pyramid.descriptor();
+
+    // Assert
+    assert!(descriptor.contains(r#"TileSize="256""#));
+    assert!(descriptor.contains(r#"Overlap="0""#));
+    assert!(descriptor.contains(r#"<Size Width="1000" Height="600"/>"#));
+}